@@ -0,0 +1,3 @@
+#!/bin/sh
+
+cargo test --release bench -- --ignored --nocapture
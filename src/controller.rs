@@ -1,42 +1,104 @@
+use crate::controller::config_file_event_handler::ConfigFileEventHandler;
 use crate::controller::key_event_handler::KeyEventHandler;
 use crate::controller::resize_event_handler::ResizeEventHandler;
+use crate::controller::suspend_event_handler::SuspendEventHandler;
+use crate::controller::terminate_event_handler::TerminateEventHandler;
+use crate::model::bookmarks::Bookmarks;
 use crate::model::compare_functions::PathNodeCompare;
+use crate::model::config::keymap::Action;
+use crate::model::config::opener::OpenerRule;
 use crate::model::config::Config;
+use crate::model::config::SharedConfig;
 use crate::model::event::Event;
+use crate::model::event::Key;
+use crate::model::history::History;
 use crate::model::path_node::PathNode;
+use crate::model::path_tree::PathTree;
+use crate::model::prompt::Prompt;
 use crate::view::composer::Composer;
 use crate::view::Pager;
 use log::info;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::OsString;
 use std::io::Write;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::atomic::AtomicBool;
 use std::sync::mpsc::sync_channel;
 use std::sync::mpsc::Receiver;
 use std::sync::mpsc::SyncSender;
+use std::sync::Arc;
+use std::sync::Mutex;
 use std::thread;
+use std::time::Instant;
 
+mod command;
+mod config_file_event_handler;
+mod dir_listing;
 mod key_event_handler;
 mod key_event_matcher;
+mod location;
+mod mouse_event_matcher;
+mod option;
 mod resize_event_handler;
+mod session;
+mod suspend_event_handler;
+mod terminate_event_handler;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MarkAction {
+    Jump,
+    Set,
+}
+
+/// A running expand_all: the directories it is listing with their level
+/// below the expanded directory, and the number of entries listed so far.
+pub struct ExpandAll {
+    depths: HashMap<PathBuf, usize>,
+    entry_count: usize,
+    max_depth: usize,
+    max_entries: usize,
+}
 
 pub struct EventQueue<W: Write> {
-    config: Config,
+    bookmarks: Bookmarks,
+    config: SharedConfig,
     composer: Composer,
+    dir_listing_id: usize,
+    dir_listings: HashMap<PathBuf, (usize, Arc<AtomicBool>)>,
+    expand_all: Option<ExpandAll>,
+    history: History,
+    is_foreground: Arc<AtomicBool>,
+    key_bindings: Vec<(Action, Vec<Key>)>,
+    last_click: Option<(Instant, usize)>,
     pager: Pager<W>,
-    path_node_root: PathNode,
+    path_tree: PathTree,
     path_node_compare: PathNodeCompare,
+    pending_count: Option<usize>,
+    pending_keys: Vec<Key>,
+    pending_keys_id: usize,
+    pending_mark_action: Option<MarkAction>,
+    pending_open_with: Option<Vec<OpenerRule>>,
+    pending_toggle_option: bool,
+    prompt: Option<Prompt>,
+    prompt_history: Vec<String>,
     queue_receiver: Receiver<Event>,
     queue_sender: SyncSender<Event>,
+    stdin_lock: Arc<Mutex<()>>,
 
     // TODO: should be part of the view?
     text_entries: Vec<String>,
-    command_to_run_on_exit: Option<String>,
+    command_to_run_on_exit: Option<Vec<OsString>>,
 }
 
 impl<W: Write> EventQueue<W> {
+    /// Shares the config with the composer and the pager.
     pub fn new(
         config: Config,
-        composer: Composer,
+        mut composer: Composer,
         mut pager: Pager<W>,
-        path_node_root: PathNode,
+        mut path_tree: PathTree,
     ) -> Self {
         info!("initializing event queue");
 
@@ -47,30 +109,83 @@ impl<W: Write> EventQueue<W> {
 
         let path_node_compare = PathNode::get_path_node_compare(&config);
 
-        let text_entries = composer.compose_path_node(&path_node_root);
-        pager.update(0, &text_entries, path_node_root.get_absolute_path());
+        let bookmarks = Bookmarks::new(&config, config.config_dir.as_deref());
+        let history = History::new(&config, config.config_dir.as_deref());
+        let key_bindings = config.get_key_bindings();
+
+        let config = Rc::new(RefCell::new(config));
+        composer.share_config(config.clone());
+        pager.share_config(config.clone());
+
+        path_tree.take_splice();
+        let text_entries = composer.compose_path_tree(&path_tree);
+        pager.update(
+            0,
+            &text_entries,
+            path_tree.get_root().get_absolute_path(),
+        );
         let command_to_run_on_exit = None;
 
-        Self {
+        let mut event_queue = Self {
+            bookmarks,
             config,
             composer,
+            dir_listing_id: 0,
+            dir_listings: HashMap::new(),
+            expand_all: None,
+            history,
+            is_foreground: Arc::new(AtomicBool::new(false)),
+            key_bindings,
+            last_click: None,
             pager,
-            path_node_root,
+            path_tree,
             path_node_compare,
+            pending_count: None,
+            pending_keys: Vec::new(),
+            pending_keys_id: 0,
+            pending_mark_action: None,
+            pending_open_with: None,
+            pending_toggle_option: false,
+            prompt: None,
+            prompt_history: Vec::new(),
             queue_receiver,
             queue_sender,
+            stdin_lock: Arc::new(Mutex::new(())),
             text_entries,
-            command_to_run_on_exit
-        }
+            command_to_run_on_exit,
+        };
+
+        event_queue.restore_session();
+
+        event_queue
     }
 
-    pub fn handle_messages(&mut self) -> Option<String> {
+    pub fn handle_messages(&mut self) -> Option<Vec<OsString>> {
         let (tx1, rx1) = std::sync::mpsc::channel();
         let (tx2, rx2) = std::sync::mpsc::channel();
         let sender1 = self.queue_sender.clone();
         let sender2 = self.queue_sender.clone();
-        thread::spawn(move || KeyEventHandler::handle(sender1, rx1));
+        let sender3 = self.queue_sender.clone();
+        let stdin_lock = self.stdin_lock.clone();
+        thread::spawn(move || {
+            KeyEventHandler::handle(sender1, rx1, stdin_lock)
+        });
         thread::spawn(move || ResizeEventHandler::handle(sender2, rx2));
+        let sender4 = self.queue_sender.clone();
+        let is_foreground3 = self.is_foreground.clone();
+        let is_foreground4 = self.is_foreground.clone();
+        thread::spawn(move || {
+            TerminateEventHandler::handle(sender3, is_foreground3)
+        });
+        thread::spawn(move || {
+            SuspendEventHandler::handle(sender4, is_foreground4)
+        });
+        let (tx5, rx5) = std::sync::mpsc::channel();
+        let config_files = self.config.borrow().config_files.clone();
+        let sender5 = self.queue_sender.clone();
+        thread::spawn(move || {
+            ConfigFileEventHandler::handle(sender5, rx5, config_files)
+        });
 
         while self
             .match_event(self.queue_receiver.recv().unwrap())
@@ -78,20 +193,142 @@ impl<W: Write> EventQueue<W> {
         {}
         let _ = tx1.send(());
         let _ = tx2.send(());
+        let _ = tx5.send(());
+        self.history.save();
+        self.save_session();
         self.command_to_run_on_exit.clone()
     }
 
     fn match_event(&mut self, event: Event) -> Option<()> {
         match event {
+            Event::CommandExit(command_exit) => {
+                self.apply_command_exit(command_exit);
+                Some(())
+            }
+            Event::ConfigFileChange => {
+                self.reload_config();
+                Some(())
+            }
+            Event::Continue => {
+                self.redraw();
+                Some(())
+            }
+            Event::DirListing(dir_listing) => {
+                self.apply_dir_listing(dir_listing);
+                Some(())
+            }
             Event::Key(key) => self.match_key_event(key),
+            Event::KeySequenceTimeout(pending_keys_id) => {
+                self.apply_key_sequence_timeout(pending_keys_id);
+                Some(())
+            }
+            Event::Mouse(mouse_event) => self.match_mouse_event(mouse_event),
             Event::Resize => {
                 self.pager.update(
                     0,
                     &self.text_entries,
-                    self.path_node_root.get_absolute_path(),
+                    self.path_tree.get_root().get_absolute_path(),
                 );
                 Some(())
             }
+            Event::Suspend => self.do_suspend(),
+            Event::Terminate => {
+                info!("terminating on a signal");
+                None
+            }
         }
     }
+
+    /// Composes the entries which changed since the last update.
+    fn update_text_entries(&mut self) {
+        if let Some(splice) = self.path_tree.take_splice() {
+            let text_entries = self.composer.compose_path_tree_range(
+                &self.path_tree,
+                splice.start..splice.start + splice.inserted,
+            );
+            self.text_entries.splice(
+                splice.start..splice.start + splice.removed,
+                text_entries,
+            );
+        }
+    }
+
+    /// Replaces the whole tree, e.g. when changing the root directory.
+    fn set_path_tree(&mut self, mut path_tree: PathTree) {
+        path_tree.take_splice();
+        self.text_entries = self.composer.compose_path_tree(&path_tree);
+        self.path_tree = path_tree;
+        self.cancel_orphaned_dir_listings();
+    }
+}
+
+/// An event queue on the expanded test directories. Bookmarks, history and
+/// sessions are neither loaded nor stored, so tests don't depend on the
+/// config directory. `configure` adjusts the default config before.
+#[cfg(test)]
+pub fn prepare_event_queue<F>(configure: F) -> EventQueue<Vec<u8>>
+where
+    F: FnOnce(&mut Config),
+{
+    let mut config = Config::default();
+    config.behavior.persist_bookmarks = false;
+    config.behavior.persist_history = false;
+    config.behavior.restore_session = false;
+    config.setup.working_dir = String::from("./tests/test_dirs");
+    configure(&mut config);
+
+    let composer = Composer::from(config.clone());
+    let pager = Pager::new(config.clone(), Vec::new());
+    let path_tree = PathTree::new_expanded(config.clone());
+
+    EventQueue::new(config, composer, pager, path_tree)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn update_text_entries_matches_full_composition() {
+        let mut event_queue = prepare_event_queue(|_| {});
+
+        for cursor_row in &[1, 0, 4, 2, 12] {
+            event_queue.pager.cursor_row = *cursor_row;
+            event_queue.do_expand_all();
+            event_queue.wait_for_dir_listings();
+            assert_eq!(
+                event_queue
+                    .composer
+                    .compose_path_tree(&event_queue.path_tree),
+                event_queue.text_entries
+            );
+        }
+
+        for cursor_row in &[10, 3, 8, 0] {
+            event_queue.pager.cursor_row = *cursor_row;
+            event_queue.do_collapse_dir();
+            assert_eq!(
+                event_queue
+                    .composer
+                    .compose_path_tree(&event_queue.path_tree),
+                event_queue.text_entries
+            );
+        }
+
+        event_queue.pager.cursor_row = 5;
+        event_queue.do_collapse_siblings();
+        assert_eq!(
+            event_queue
+                .composer
+                .compose_path_tree(&event_queue.path_tree),
+            event_queue.text_entries
+        );
+    }
+
+    #[test]
+    fn terminate() {
+        let mut event_queue = prepare_event_queue(|_| {});
+
+        assert_eq!(None, event_queue.match_event(Event::Terminate));
+    }
 }
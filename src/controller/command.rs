@@ -0,0 +1,492 @@
+use crate::controller::EventQueue;
+use crate::model::command_template::render_argv;
+use crate::model::command_template::render_shell;
+use crate::model::command_template::TemplateContext;
+use crate::model::event::CommandExit;
+use crate::model::event::Event;
+use crate::utils::canonicalize_or_keep;
+use log::{info, warn};
+use std::ffi::OsString;
+use std::io::Read;
+use std::io::Write;
+use std::os::unix::process::ExitStatusExt;
+use std::process::Child;
+use std::process::Command;
+use std::process::ExitStatus;
+use std::process::Stdio;
+use std::sync::atomic::Ordering;
+use std::sync::mpsc::channel;
+use std::sync::mpsc::Receiver;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use std::time::Instant;
+
+/// How long the output of an exited command is still read, a daemon started
+/// by it may keep the pipes open much longer.
+const OUTPUT_GRACE_PERIOD: Duration = Duration::from_millis(200);
+
+/// The status line shows the last line of stderr, so only its end is kept.
+const STDERR_LIMIT: usize = 4096;
+
+/// The output of a pipe, which is read in a thread until its end.
+struct OutputReader {
+    output: Arc<Mutex<Vec<u8>>>,
+    is_done: Receiver<()>,
+}
+
+impl OutputReader {
+    /// Starts reading, of more than `limit` bytes only the last are kept.
+    fn spawn<R: Read + Send + 'static>(mut pipe: R, limit: usize) -> Self {
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let (done_sender, is_done) = channel();
+
+        let thread_output = output.clone();
+        thread::spawn(move || {
+            let mut buffer = [0; 4096];
+            while let Ok(len) = pipe.read(&mut buffer) {
+                if len == 0 {
+                    break;
+                }
+                let mut output = thread_output.lock().unwrap();
+                output.extend_from_slice(&buffer[..len]);
+                if output.len() > limit {
+                    let excess = output.len() - limit;
+                    output.drain(..excess);
+                }
+            }
+            let _ = done_sender.send(());
+        });
+
+        Self { output, is_done }
+    }
+
+    /// The output read until the end of the pipe, or until the deadline.
+    fn finish(self, deadline: Instant) -> Vec<u8> {
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        let _ = self.is_done.recv_timeout(timeout);
+        let mut output = self.output.lock().unwrap();
+        std::mem::take(&mut *output)
+    }
+}
+
+impl<W: Write> EventQueue<W> {
+    /// The mode of an action, falling back to `behavior.file_action_mode`.
+    pub fn get_command_mode(&self, mode: &Option<String>) -> String {
+        mode.clone().unwrap_or_else(|| {
+            self.config.borrow().behavior.file_action_mode.clone()
+        })
+    }
+
+    /// Renders the command for the entry under the cursor and runs it, by
+    /// bash or directly depending on `behavior.use_shell`.
+    pub fn run_command(&mut self, command: &str, mode: &str) -> Option<()> {
+        let node_id = self
+            .path_tree
+            .flat_index_to_node_id(self.pager.cursor_row as usize);
+
+        let file_path =
+            canonicalize_or_keep(&self.path_tree.get_path_node(node_id).path);
+        let root_path = canonicalize_or_keep(&self.path_tree.get_root().path);
+        let selection = vec![file_path.clone()];
+        let template_context = TemplateContext {
+            path: &file_path,
+            root: &root_path,
+            selection: &selection,
+        };
+
+        let argv = if self.config.borrow().behavior.use_shell {
+            vec![
+                OsString::from("bash"),
+                OsString::from("-c"),
+                render_shell(command, &template_context),
+            ]
+        } else {
+            match render_argv(command, &template_context) {
+                Ok(argv) if !argv.is_empty() => argv,
+                Ok(_) => return Some(()),
+                Err(err) => {
+                    self.set_status(format!("{}: {}", command, err));
+                    return Some(());
+                }
+            }
+        };
+
+        info!("executing command in {} mode:\n{:?}", mode, argv);
+
+        if self.config.borrow().behavior.quit_on_action {
+            self.command_to_run_on_exit = Some(argv);
+            return None;
+        }
+
+        match mode {
+            "capture" => self.spawn_command(command, &argv, true),
+            "foreground" => self.run_command_in_foreground(command, &argv),
+            _ => self.spawn_command(command, &argv, false),
+        }
+        Some(())
+    }
+
+    /// Runs the command without a terminal, the exit is delivered as an
+    /// `Event::CommandExit` once the child is reaped. Its output is read until
+    /// then and shortly after, not until every process holding it is gone.
+    fn spawn_command(&mut self, name: &str, argv: &[OsString], capture: bool) {
+        let stdout = if capture {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        };
+
+        let child = Command::new(&argv[0])
+            .args(&argv[1..])
+            .stdin(Stdio::null())
+            .stdout(stdout)
+            .stderr(Stdio::piped())
+            .spawn();
+
+        let mut child = match child {
+            Ok(child) => child,
+            Err(err) => {
+                self.set_status(format!("{}: {}", name, err));
+                return;
+            }
+        };
+
+        let stdout = child
+            .stdout
+            .take()
+            .map(|stdout| OutputReader::spawn(stdout, usize::MAX));
+        let stderr = child
+            .stderr
+            .take()
+            .map(|stderr| OutputReader::spawn(stderr, STDERR_LIMIT));
+
+        let name = String::from(name);
+        let sender = self.queue_sender.clone();
+        thread::spawn(move || {
+            let status = match child.wait() {
+                Ok(status) => status,
+                Err(err) => {
+                    warn!("could not wait for {}: {}", name, err);
+                    return;
+                }
+            };
+
+            let deadline = Instant::now() + OUTPUT_GRACE_PERIOD;
+            let _ = sender.send(Event::CommandExit(CommandExit {
+                name,
+                status,
+                stdout: stdout.map(|stdout| stdout.finish(deadline)),
+                stderr: stderr
+                    .map(|stderr| stderr.finish(deadline))
+                    .unwrap_or_default(),
+            }));
+        });
+    }
+
+    /// Hands the terminal to the command and waits for it. Meanwhile ctrl+c
+    /// and ctrl+z are meant for the command.
+    fn run_command_in_foreground(&mut self, name: &str, argv: &[OsString]) {
+        let stdin_lock = self.stdin_lock.clone();
+        let _stdin_guard = stdin_lock.lock();
+        self.pager.suspend();
+        self.is_foreground.store(true, Ordering::SeqCst);
+
+        let result = Command::new(&argv[0])
+            .args(&argv[1..])
+            .spawn()
+            .and_then(Self::wait_in_foreground);
+
+        self.is_foreground.store(false, Ordering::SeqCst);
+        self.pager.resume();
+        self.update_pager(0);
+
+        match result {
+            Ok(status) => self.apply_command_exit(CommandExit {
+                name: String::from(name),
+                status,
+                stdout: None,
+                stderr: Vec::new(),
+            }),
+            Err(err) => self.set_status(format!("{}: {}", name, err)),
+        }
+    }
+
+    /// Waits for the command to exit. When the command is stopped, e.g. by
+    /// ctrl+z, this process stops as well, so that the shell takes over the
+    /// terminal, and continues the command once it is continued itself.
+    fn wait_in_foreground(child: Child) -> std::io::Result<ExitStatus> {
+        let pid = child.id() as libc::pid_t;
+
+        loop {
+            let mut status = 0;
+            if unsafe { libc::waitpid(pid, &mut status, libc::WUNTRACED) } < 0 {
+                let err = std::io::Error::last_os_error();
+                if err.kind() == std::io::ErrorKind::Interrupted {
+                    continue;
+                }
+                return Err(err);
+            }
+
+            let status = ExitStatus::from_raw(status);
+            if status.stopped_signal().is_none() {
+                return Ok(status);
+            }
+
+            info!("the foreground command was stopped");
+            unsafe {
+                libc::kill(libc::getpid(), libc::SIGSTOP);
+                libc::kill(pid, libc::SIGCONT);
+            }
+        }
+    }
+
+    /// Reports a failed command in the status line and shows captured output
+    /// in the overlay.
+    pub fn apply_command_exit(&mut self, command_exit: CommandExit) {
+        info!(
+            "{} exited with {:?}",
+            command_exit.name, command_exit.status
+        );
+
+        let is_captured = command_exit.stdout.is_some();
+        let mut output = command_exit.stdout.unwrap_or_default();
+        output.extend_from_slice(&command_exit.stderr);
+
+        if !command_exit.status.success() {
+            let last_line = String::from_utf8_lossy(&command_exit.stderr)
+                .lines()
+                .rev()
+                .find(|line| !line.trim().is_empty())
+                .map(|line| format!(": {}", line.trim()))
+                .unwrap_or_default();
+            self.set_status(format!(
+                "{} {}{}",
+                command_exit.name,
+                Self::format_exit_status(command_exit.status),
+                last_line
+            ));
+        }
+
+        if is_captured && !output.is_empty() {
+            let overlay_entries = self
+                .composer
+                .compose_command_output(&command_exit.name, &output);
+            self.pager.set_overlay(overlay_entries);
+        }
+
+        self.update_pager(0);
+    }
+
+    fn format_exit_status(status: ExitStatus) -> String {
+        match (status.code(), status.signal()) {
+            (Some(code), _) => format!("exited with {}", code),
+            (None, Some(signal)) => format!("was killed by signal {}", signal),
+            (None, None) => String::from("failed"),
+        }
+    }
+
+    /// Shows a message in the footer until the next key is pressed.
+    pub fn set_status(&mut self, status: String) {
+        self.pager.set_status(status);
+        self.update_pager(0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controller;
+
+    fn prepare_event_queue(use_shell: bool) -> EventQueue<Vec<u8>> {
+        let mut event_queue = controller::prepare_event_queue(|config| {
+            config.behavior.use_shell = use_shell;
+            config.setup.working_dir = String::from("./tests/test_dirs/dir1");
+        });
+        event_queue.pager.cursor_row = event_queue
+            .text_entries
+            .iter()
+            .position(|text_entry| text_entry.ends_with("file7"))
+            .unwrap() as i32;
+        event_queue
+    }
+
+    fn receive_command_exit(event_queue: &EventQueue<Vec<u8>>) -> CommandExit {
+        loop {
+            let event = event_queue
+                .queue_receiver
+                .recv_timeout(Duration::from_secs(10))
+                .unwrap();
+            if let Event::CommandExit(command_exit) = event {
+                return command_exit;
+            }
+        }
+    }
+
+    #[test]
+    fn run_command_with_shell() {
+        let mut event_queue = prepare_event_queue(true);
+        event_queue.config.borrow_mut().behavior.quit_on_action = true;
+
+        let result =
+            event_queue.run_command("echo %{r} 'in %{b}'", "background");
+
+        assert!(result.is_none());
+        assert_eq!(
+            Some(vec![
+                OsString::from("bash"),
+                OsString::from("-c"),
+                OsString::from("echo 'file7' 'in file7'"),
+            ]),
+            event_queue.command_to_run_on_exit
+        );
+    }
+
+    #[test]
+    fn run_command_without_shell() {
+        let mut event_queue = prepare_event_queue(false);
+        event_queue.config.borrow_mut().behavior.quit_on_action = true;
+
+        event_queue.run_command("echo %{r} 'in %{b}'", "background");
+
+        assert_eq!(
+            Some(vec![
+                OsString::from("echo"),
+                OsString::from("file7"),
+                OsString::from("in %{b}"),
+            ]),
+            event_queue.command_to_run_on_exit
+        );
+    }
+
+    #[test]
+    fn run_unparsable_command() {
+        let mut event_queue = prepare_event_queue(false);
+
+        let result = event_queue.run_command("echo 'open", "background");
+
+        assert!(result.is_some());
+        assert_eq!(None, event_queue.command_to_run_on_exit);
+        assert_eq!(
+            "echo 'open: unterminated quote",
+            event_queue.pager.get_status()
+        );
+    }
+
+    #[test]
+    fn run_missing_program() {
+        let mut event_queue = prepare_event_queue(false);
+
+        event_queue.run_command("./no-such-program", "background");
+
+        assert!(event_queue
+            .pager
+            .get_status()
+            .starts_with("./no-such-program: "));
+    }
+
+    #[test]
+    fn background_failure() {
+        let mut event_queue = prepare_event_queue(true);
+
+        event_queue
+            .run_command("echo out; echo oops >&2; exit 3", "background");
+        let command_exit = receive_command_exit(&event_queue);
+
+        assert_eq!(None, command_exit.stdout);
+        assert_eq!(b"oops\n".to_vec(), command_exit.stderr);
+        assert_eq!(Some(3), command_exit.status.code());
+
+        event_queue.apply_command_exit(command_exit);
+
+        assert_eq!(
+            "echo out; echo oops >&2; exit 3 exited with 3: oops",
+            event_queue.pager.get_status()
+        );
+        assert!(!event_queue.pager.has_overlay());
+    }
+
+    #[test]
+    fn background_stderr_limit() {
+        let mut event_queue = prepare_event_queue(true);
+
+        event_queue.run_command(
+            "head -c 9999 /dev/zero | tr '\\0' x >&2; echo oops >&2; exit 1",
+            "background",
+        );
+        let command_exit = receive_command_exit(&event_queue);
+
+        assert_eq!(STDERR_LIMIT, command_exit.stderr.len());
+        assert!(command_exit.stderr.ends_with(b"xxoops\n"));
+    }
+
+    #[test]
+    fn background_daemon() {
+        let mut event_queue = prepare_event_queue(true);
+        let start = Instant::now();
+
+        event_queue.run_command("sleep 5 & echo started >&2", "capture");
+        let command_exit = receive_command_exit(&event_queue);
+
+        assert!(start.elapsed() < Duration::from_secs(4));
+        assert!(command_exit.status.success());
+        assert_eq!(Some(Vec::new()), command_exit.stdout);
+        assert_eq!(b"started\n".to_vec(), command_exit.stderr);
+    }
+
+    #[test]
+    fn background_success() {
+        let mut event_queue = prepare_event_queue(true);
+
+        event_queue.run_command("echo out", "background");
+        let command_exit = receive_command_exit(&event_queue);
+        event_queue.apply_command_exit(command_exit);
+
+        assert_eq!("", event_queue.pager.get_status());
+        assert!(!event_queue.pager.has_overlay());
+    }
+
+    #[test]
+    fn capture() {
+        let mut event_queue = prepare_event_queue(true);
+
+        event_queue.run_command("true", "capture");
+        let command_exit = receive_command_exit(&event_queue);
+
+        assert_eq!(Some(Vec::new()), command_exit.stdout);
+
+        event_queue.run_command("echo %{b}", "capture");
+        let command_exit = receive_command_exit(&event_queue);
+
+        assert_eq!(Some(b"file7\n".to_vec()), command_exit.stdout);
+
+        event_queue.apply_command_exit(command_exit);
+
+        assert_eq!("", event_queue.pager.get_status());
+        assert!(event_queue.pager.has_overlay());
+    }
+
+    #[test]
+    fn wait_in_foreground() {
+        let child = Command::new("sh").arg("-c").arg("exit 4").spawn().unwrap();
+
+        let status = EventQueue::<Vec<u8>>::wait_in_foreground(child).unwrap();
+
+        assert_eq!(Some(4), status.code());
+    }
+
+    #[test]
+    fn killed() {
+        let mut event_queue = prepare_event_queue(true);
+
+        event_queue.run_command("kill -9 $$", "background");
+        let command_exit = receive_command_exit(&event_queue);
+        event_queue.apply_command_exit(command_exit);
+
+        assert_eq!(
+            "kill -9 $$ was killed by signal 9",
+            event_queue.pager.get_status()
+        );
+    }
+}
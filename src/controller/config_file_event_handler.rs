@@ -0,0 +1,100 @@
+use crate::model::event::Event;
+use std::fs::metadata;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::sync::mpsc::RecvTimeoutError;
+use std::sync::mpsc::SyncSender;
+use std::time::Duration;
+use std::time::SystemTime;
+
+pub struct ConfigFileEventHandler {}
+
+impl ConfigFileEventHandler {
+    /// How often the modification times of the config files are checked.
+    const POLL_INTERVAL_MS: u64 = 1000;
+
+    /// Sends `Event::ConfigFileChange` whenever one of the config files is
+    /// written. A removed file is not a change, the config stays as it is.
+    pub fn handle(
+        sync_sender: SyncSender<Event>,
+        rx: mpsc::Receiver<()>,
+        config_files: Vec<PathBuf>,
+    ) {
+        let mut last_modified: Vec<Option<(SystemTime, u64)>> = config_files
+            .iter()
+            .map(|config_file| Self::get_modified(config_file))
+            .collect();
+        let poll_interval = Duration::from_millis(Self::POLL_INTERVAL_MS);
+
+        while let Err(RecvTimeoutError::Timeout) =
+            rx.recv_timeout(poll_interval)
+        {
+            let mut has_changed = false;
+            for (config_file, last_modified) in
+                config_files.iter().zip(last_modified.iter_mut())
+            {
+                has_changed |= Self::has_changed(config_file, last_modified);
+            }
+
+            if has_changed {
+                let _ = sync_sender.send(Event::ConfigFileChange);
+            }
+        }
+    }
+
+    /// Compares the modification with the last one and remembers it.
+    fn has_changed(
+        config_file: &Path,
+        last_modified: &mut Option<(SystemTime, u64)>,
+    ) -> bool {
+        let modified = Self::get_modified(config_file);
+        if modified == *last_modified {
+            return false;
+        }
+
+        *last_modified = modified;
+        modified.is_some()
+    }
+
+    /// The size is compared as well, as the modification time may be too
+    /// coarse to tell two quick saves apart.
+    fn get_modified(config_file: &Path) -> Option<(SystemTime, u64)> {
+        let metadata = metadata(config_file).ok()?;
+        Some((metadata.modified().ok()?, metadata.len()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::write_file;
+
+    #[test]
+    fn has_changed() {
+        let config_file = std::env::temp_dir().join(format!(
+            "twilight-commander-watch-{}.toml",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&config_file);
+        let mut last_modified =
+            ConfigFileEventHandler::get_modified(&config_file);
+        let has_changed = |last_modified: &mut Option<(SystemTime, u64)>| {
+            ConfigFileEventHandler::has_changed(&config_file, last_modified)
+        };
+
+        assert!(!has_changed(&mut last_modified));
+
+        write_file(config_file.to_str().unwrap(), "[behavior]\n").unwrap();
+        assert!(has_changed(&mut last_modified));
+        assert!(!has_changed(&mut last_modified));
+
+        write_file(config_file.to_str().unwrap(), "[debug]\n[setup]\n")
+            .unwrap();
+        assert!(has_changed(&mut last_modified));
+
+        std::fs::remove_file(&config_file).unwrap();
+        assert!(!has_changed(&mut last_modified));
+        assert_eq!(None, last_modified);
+    }
+}
@@ -0,0 +1,256 @@
+use crate::controller::EventQueue;
+use crate::model::event::DirListing;
+use crate::model::event::Event;
+use crate::model::path_node::PathNode;
+use crate::model::path_tree::NodeId;
+use log::info;
+use std::io::ErrorKind;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::thread;
+
+impl<W: Write> EventQueue<W> {
+    /// Lists the directory in a worker thread and marks it as loading. The
+    /// result is delivered as an `Event::DirListing`.
+    pub fn expand_dir_async(&mut self, node_id: NodeId) {
+        let path_node = self.path_tree.get_path_node(node_id);
+        if !path_node.is_dir || path_node.is_loading {
+            return;
+        }
+
+        let path = path_node.path.clone();
+        info!("listing {:?}", path);
+
+        self.dir_listing_id += 1;
+        let id = self.dir_listing_id;
+        let is_cancelled = Arc::new(AtomicBool::new(false));
+        self.dir_listings
+            .insert(path.clone(), (id, Arc::clone(&is_cancelled)));
+
+        self.path_tree.set_loading(node_id, true);
+
+        let sender = self.queue_sender.clone();
+        let compare = self.path_node_compare;
+        thread::spawn(move || {
+            let children =
+                match PathNode::list_children(&path, compare, &is_cancelled) {
+                    Ok(children) => Some(children),
+                    Err(ref err) if err.kind() == ErrorKind::Interrupted => {
+                        return;
+                    }
+                    Err(_) => None,
+                };
+
+            let _ = sender.send(Event::DirListing(DirListing {
+                id,
+                path,
+                children,
+            }));
+        });
+    }
+
+    pub fn cancel_dir_listing(&mut self, node_id: NodeId) {
+        let path = self.path_tree.get_path_node(node_id).path.clone();
+        self.cancel_listing(&path);
+
+        self.path_tree.set_loading(node_id, false);
+    }
+
+    /// Cancels the listings of directories which are not loading in the tree
+    /// anymore, e.g. because a parent directory was collapsed or the tree was
+    /// replaced.
+    pub fn cancel_orphaned_dir_listings(&mut self) {
+        let path_tree = &self.path_tree;
+        let orphaned_paths: Vec<PathBuf> = self
+            .dir_listings
+            .keys()
+            .filter(|path| match path_tree.find_node_id(path) {
+                Some(node_id) => !path_tree.get_path_node(node_id).is_loading,
+                None => true,
+            })
+            .cloned()
+            .collect();
+
+        for path in orphaned_paths {
+            self.cancel_listing(&path);
+        }
+    }
+
+    fn cancel_listing(&mut self, path: &Path) {
+        if let Some((_, is_cancelled)) = self.dir_listings.remove(path) {
+            info!("cancelling the listing of {:?}", path);
+            is_cancelled.store(true, Ordering::Relaxed);
+        }
+        if let Some(expand_all) = &mut self.expand_all {
+            expand_all.depths.remove(path);
+        }
+    }
+
+    /// Puts the listed entries into the tree. Listings which were cancelled,
+    /// replaced by a newer listing or whose directory is not part of the tree
+    /// anymore are dropped.
+    pub fn apply_dir_listing(&mut self, dir_listing: DirListing) {
+        match self.dir_listings.get(&dir_listing.path) {
+            Some((id, _)) if *id == dir_listing.id => {
+                self.dir_listings.remove(&dir_listing.path);
+            }
+            _ => return,
+        }
+
+        let node_id = match self.path_tree.find_node_id(&dir_listing.path) {
+            Some(node_id)
+                if self.path_tree.get_path_node(node_id).is_loading =>
+            {
+                node_id
+            }
+            _ => return,
+        };
+
+        let flat_index =
+            self.path_tree.node_id_to_flat_index(node_id).unwrap_or(0) as i32;
+        let text_entries_len = self.text_entries.len() as i32;
+
+        self.path_tree.set_children(node_id, dir_listing.children);
+        self.apply_expand_all_listing(node_id);
+
+        self.update_text_entries();
+
+        // keep the cursor on its entry when the new entries appear above it
+        let cursor_delta = if flat_index < self.pager.cursor_row {
+            self.text_entries.len() as i32 - text_entries_len
+        } else {
+            0
+        };
+
+        self.update_pager(cursor_delta);
+    }
+}
+
+#[cfg(test)]
+impl<W: Write> EventQueue<W> {
+    /// Applies the listings of the worker threads until none is pending.
+    pub fn wait_for_dir_listings(&mut self) {
+        while !self.dir_listings.is_empty() {
+            match self.queue_receiver.recv().unwrap() {
+                Event::DirListing(dir_listing) => {
+                    self.apply_dir_listing(dir_listing)
+                }
+                event => panic!("unexpected event {:?}", event),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controller::prepare_event_queue;
+
+    fn dir1_id(event_queue: &EventQueue<Vec<u8>>) -> NodeId {
+        event_queue.path_tree.get_node_id_at(&[1])
+    }
+
+    fn get_dir1(event_queue: &EventQueue<Vec<u8>>) -> &PathNode {
+        event_queue.path_tree.get_path_node(dir1_id(event_queue))
+    }
+
+    fn receive_dir_listing(event_queue: &EventQueue<Vec<u8>>) -> DirListing {
+        match event_queue.queue_receiver.recv().unwrap() {
+            Event::DirListing(dir_listing) => dir_listing,
+            event => panic!("unexpected event {:?}", event),
+        }
+    }
+
+    #[test]
+    fn expand_dir_async() {
+        let mut event_queue = prepare_event_queue(|_| {});
+
+        event_queue.expand_dir_async(dir1_id(&event_queue));
+
+        assert!(get_dir1(&event_queue).is_loading);
+        assert!(!get_dir1(&event_queue).is_expanded);
+
+        let dir_listing = receive_dir_listing(&event_queue);
+        assert_eq!(4, dir_listing.children.as_ref().unwrap().len());
+
+        event_queue.apply_dir_listing(dir_listing);
+
+        assert!(!get_dir1(&event_queue).is_loading);
+        assert!(get_dir1(&event_queue).is_expanded);
+        assert_eq!(17, event_queue.text_entries.len());
+        assert!(event_queue.dir_listings.is_empty());
+    }
+
+    #[test]
+    fn expand_dir_async_above_cursor() {
+        let mut event_queue = prepare_event_queue(|_| {});
+        event_queue.pager.cursor_row = 5;
+
+        event_queue.expand_dir_async(dir1_id(&event_queue));
+        let dir_listing = receive_dir_listing(&event_queue);
+        event_queue.apply_dir_listing(dir_listing);
+
+        assert_eq!(9, event_queue.pager.cursor_row);
+    }
+
+    #[test]
+    fn cancel_dir_listing() {
+        let mut event_queue = prepare_event_queue(|_| {});
+
+        event_queue.expand_dir_async(dir1_id(&event_queue));
+        event_queue.cancel_dir_listing(dir1_id(&event_queue));
+
+        assert!(!get_dir1(&event_queue).is_loading);
+        assert!(event_queue.dir_listings.is_empty());
+
+        // a result which was sent before the cancellation is dropped
+        event_queue.apply_dir_listing(DirListing {
+            id: event_queue.dir_listing_id,
+            path: PathBuf::from("./tests/test_dirs/dir1"),
+            children: Some(Vec::new()),
+        });
+
+        assert!(!get_dir1(&event_queue).is_expanded);
+        assert_eq!(13, event_queue.text_entries.len());
+    }
+
+    #[test]
+    fn collapse_parent_of_pending_listing() {
+        let mut event_queue = prepare_event_queue(|_| {});
+        event_queue
+            .path_tree
+            .expand_dir(dir1_id(&event_queue), event_queue.path_node_compare);
+        event_queue.update_text_entries();
+        let dir6_id = event_queue.path_tree.get_node_id_at(&[1, 0]);
+        event_queue.expand_dir_async(dir6_id);
+        let (_, is_cancelled) = event_queue.dir_listings
+            [&PathBuf::from("./tests/test_dirs/dir1/dir6")]
+            .clone();
+
+        event_queue.pager.cursor_row = 1;
+        event_queue.do_collapse_dir();
+
+        assert!(is_cancelled.load(Ordering::Relaxed));
+        assert!(event_queue.dir_listings.is_empty());
+        assert!(!get_dir1(&event_queue).is_expanded);
+    }
+
+    #[test]
+    fn unreadable_dir() {
+        let mut event_queue = prepare_event_queue(|_| {});
+
+        event_queue.expand_dir_async(dir1_id(&event_queue));
+        event_queue.apply_dir_listing(DirListing {
+            id: event_queue.dir_listing_id,
+            path: PathBuf::from("./tests/test_dirs/dir1"),
+            children: None,
+        });
+
+        assert!(get_dir1(&event_queue).is_err);
+        assert!(get_dir1(&event_queue).children.is_empty());
+    }
+}
@@ -1,19 +1,84 @@
 use crate::model::event::Event;
 use crate::model::event::Key;
-use std::io::stdin;
+use std::io::Read;
 use std::sync::mpsc::SyncSender;
-use termion::input::TermRead;
 use std::sync::mpsc::{self, TryRecvError};
+use std::sync::Arc;
+use std::sync::Mutex;
+use termion::event::Event as TEvent;
+use termion::input::TermRead;
+
+/// Reads the standard input only while holding the lock, so that holding the
+/// lock hands all input to a command in the foreground.
+struct LockedStdin {
+    lock: Arc<Mutex<()>>,
+}
+
+impl LockedStdin {
+    /// How long the lock is held while waiting for input.
+    const POLL_TIMEOUT_MS: i32 = 100;
+}
+
+impl Read for LockedStdin {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        loop {
+            let _guard = self.lock.lock();
+
+            let mut poll_fd = libc::pollfd {
+                fd: libc::STDIN_FILENO,
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            let ready =
+                unsafe { libc::poll(&mut poll_fd, 1, Self::POLL_TIMEOUT_MS) };
+            if ready < 0 {
+                let err = std::io::Error::last_os_error();
+                if err.kind() == std::io::ErrorKind::Interrupted {
+                    continue;
+                }
+                return Err(err);
+            }
+            if ready == 0 {
+                continue;
+            }
+
+            // reads the file descriptor directly, as the buffer of `Stdin`
+            // would hide pending input from `poll`
+            let read = unsafe {
+                libc::read(
+                    libc::STDIN_FILENO,
+                    buf.as_mut_ptr() as *mut libc::c_void,
+                    buf.len(),
+                )
+            };
+            if read < 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            return Ok(read as usize);
+        }
+    }
+}
 
 pub struct KeyEventHandler {}
 
 impl KeyEventHandler {
-    pub fn handle(sync_sender: SyncSender<Event>, rx: mpsc::Receiver<()>) {
-        let stdin = stdin();
+    pub fn handle(
+        sync_sender: SyncSender<Event>,
+        rx: mpsc::Receiver<()>,
+        stdin_lock: Arc<Mutex<()>>,
+    ) {
+        let stdin = LockedStdin { lock: stdin_lock };
 
         for termion_event in stdin.events() {
-            if let Ok(termion_event) = termion_event {
-                let _ = sync_sender.send(Event::Key(Key::from(termion_event)));
+            match termion_event {
+                Ok(TEvent::Mouse(mouse_event)) => {
+                    let _ = sync_sender.send(Event::Mouse(mouse_event));
+                }
+                Ok(termion_event) => {
+                    let _ =
+                        sync_sender.send(Event::Key(Key::from(termion_event)));
+                }
+                Err(_) => {}
             }
             match rx.try_recv() {
                 Ok(_) | Err(TryRecvError::Disconnected) => {
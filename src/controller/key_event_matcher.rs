@@ -1,35 +1,263 @@
 use crate::controller::EventQueue;
+use crate::controller::MarkAction;
+use crate::model::config::keymap::Action;
+use crate::model::event::format_key_sequence;
+use crate::model::event::Event;
 use crate::model::event::Key;
 use std::io::Write;
+use std::thread;
+use std::time::Duration;
 
+mod bookmark_jump;
+mod bookmark_list;
+mod bookmark_set;
+mod collapse_all;
 mod collapse_dir;
+mod collapse_siblings;
+mod command_prompt;
+mod enter_dir;
 mod entry_down;
 mod entry_up;
+mod expand_all;
 mod expand_dir;
 mod file_action;
+mod history_back;
+mod history_forward;
+mod open_with;
+mod parent_dir;
 mod quit;
 mod reload;
+mod suspend;
+mod toggle_option;
+
+/// Keeps counts from overflowing.
+const MAX_COUNT: usize = 99_999;
+
+/// The built-in actions a count repeats, any other runs once.
+const REPEATABLE_ACTIONS: [&str; 5] = [
+    "collapse_dir",
+    "expand_dir",
+    "history_back",
+    "history_forward",
+    "parent_dir",
+];
 
 impl<W: Write> EventQueue<W> {
-    #[rustfmt::skip]
     pub fn match_key_event(&mut self, key: Key) -> Option<()> {
-        let ck = self.config.keybinding.clone();
+        if !self.pager.get_status().is_empty() {
+            self.pager.clear_status();
+            self.update_pager(0);
+        }
+
+        if let Some(prompt) = self.prompt.take() {
+            return self.match_prompt_key(prompt, key);
+        }
+
+        if let Some(rules) = self.pending_open_with.take() {
+            return self.select_open_with(rules, key);
+        }
+
+        if self.pending_toggle_option {
+            self.pending_toggle_option = false;
+            return self.select_toggle_option(key);
+        }
 
-        if key == Key::from(ck.collapse_dir) { self.do_collapse_dir() }
-        else if key == Key::from(ck.entry_down) { self.do_entry_down() }
-        else if key == Key::from(ck.entry_up) { self.do_entry_up() }
-        else if key == Key::from(ck.expand_dir) { self.do_expand_dir() }
-        else if key == Key::from(ck.file_action) { self.do_file_action() }
-        else if key == Key::from(ck.quit) { self.do_quit() }
-        else if key == Key::from(ck.reload) { self.do_reload() }
-        else { Some(()) }
+        if self.pager.has_overlay() {
+            self.pager.clear_overlay();
+            self.update_pager(0);
+            return Some(());
+        }
+
+        if let Some(mark_action) = self.pending_mark_action.take() {
+            if let Some(mark) = key.get_char() {
+                match mark_action {
+                    MarkAction::Jump => self.jump_to_bookmark(mark),
+                    MarkAction::Set => self.set_bookmark(mark),
+                }
+            }
+            return Some(());
+        }
+
+        self.match_keybinding(key)
     }
 
-    fn update_pager(&mut self, cursor_delta: i32) {
+    /// Collects keys until they complete a bound key sequence. Keys which
+    /// start no sequence are dropped, except for the last one which may start
+    /// a new sequence. Digits in front of a sequence are a count.
+    fn match_keybinding(&mut self, key: Key) -> Option<()> {
+        if let Some(digit) = self.get_count_digit(&key) {
+            let count = self.pending_count.unwrap_or(0) * 10 + digit;
+            self.pending_count = Some(count.min(MAX_COUNT));
+            self.update_pending_keys_text();
+            self.update_pager(0);
+            return Some(());
+        }
+
+        self.pending_keys.push(key.clone());
+
+        let action = self
+            .key_bindings
+            .iter()
+            .find(|(_, key_sequence)| *key_sequence == self.pending_keys)
+            .map(|(action, _)| action.clone());
+
+        if let Some(action) = action {
+            let count = self.pending_count.unwrap_or(1);
+            self.clear_pending_keys();
+            return self.do_action(&action, count);
+        }
+
+        let is_pending = self.key_bindings.iter().any(|(_, key_sequence)| {
+            key_sequence.starts_with(&self.pending_keys)
+        });
+
+        if is_pending {
+            self.start_pending_keys_timeout();
+            self.update_pager(0);
+            return Some(());
+        }
+
+        let was_pending = self.pending_keys.len() > 1;
+        self.clear_pending_keys();
+
+        if was_pending {
+            self.update_pager(0);
+            return self.match_keybinding(key);
+        }
+
+        Some(())
+    }
+
+    /// A digit continues the count, or starts it unless it is 0 or bound
+    /// itself.
+    fn get_count_digit(&self, key: &Key) -> Option<usize> {
+        if !self.pending_keys.is_empty() {
+            return None;
+        }
+
+        let digit = key.get_char()?.to_digit(10)? as usize;
+
+        if self.pending_count.is_none() {
+            let is_bound = digit == 0
+                || self
+                    .key_bindings
+                    .iter()
+                    .any(|(_, key_sequence)| key_sequence.first() == Some(key));
+            if is_bound {
+                return None;
+            }
+        }
+
+        Some(digit)
+    }
+
+    /// Moves the cursor once for the whole count and repeats the other moves
+    /// in the tree and the history. Any other action runs once.
+    fn do_action(&mut self, action: &Action, count: usize) -> Option<()> {
+        match action {
+            Action::Builtin("entry_down") => self.do_entry_down(count),
+            Action::Builtin("entry_up") => self.do_entry_up(count),
+            Action::Builtin(name) if REPEATABLE_ACTIONS.contains(name) => {
+                for _ in 0..count {
+                    self.do_action_once(name)?;
+                }
+                Some(())
+            }
+            Action::Builtin(name) => self.do_action_once(name),
+            Action::Command(command, mode) => {
+                let mode = self.get_command_mode(mode);
+                self.do_command(command, &mode)
+            }
+        }
+    }
+
+    #[rustfmt::skip]
+    fn do_action_once(&mut self, action: &str) -> Option<()> {
+        match action {
+            "bookmark_jump" => self.do_bookmark_jump(),
+            "bookmark_list" => self.do_bookmark_list(),
+            "bookmark_set" => self.do_bookmark_set(),
+            "collapse_all" => self.do_collapse_all(),
+            "collapse_dir" => self.do_collapse_dir(),
+            "collapse_siblings" => self.do_collapse_siblings(),
+            "command_prompt" => self.do_command_prompt(),
+            "enter_dir" => self.do_enter_dir(),
+            "expand_all" => self.do_expand_all(),
+            "expand_dir" => self.do_expand_dir(),
+            "file_action" => self.do_file_action(),
+            "history_back" => self.do_history_back(),
+            "history_forward" => self.do_history_forward(),
+            "open_with" => self.do_open_with(),
+            "parent_dir" => self.do_parent_dir(),
+            "quit" => self.do_quit(),
+            "reload" => self.do_reload(),
+            "suspend" => self.do_suspend(),
+            "toggle_option" => self.do_toggle_option(),
+            _ => Some(()),
+        }
+    }
+
+    /// Shows the pending keys in the footer and drops them after the
+    /// configured timeout, unless more keys are pressed meanwhile.
+    fn start_pending_keys_timeout(&mut self) {
+        self.pending_keys_id += 1;
+        self.update_pending_keys_text();
+
+        let timeout_ms = self.config.borrow().behavior.key_sequence_timeout_ms;
+        if timeout_ms <= 0 {
+            return;
+        }
+
+        let pending_keys_id = self.pending_keys_id;
+        let sender = self.queue_sender.clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(timeout_ms as u64));
+            let _ = sender.send(Event::KeySequenceTimeout(pending_keys_id));
+        });
+    }
+
+    pub fn apply_key_sequence_timeout(&mut self, pending_keys_id: usize) {
+        if pending_keys_id == self.pending_keys_id
+            && !self.pending_keys.is_empty()
+        {
+            self.clear_pending_keys();
+            self.update_pager(0);
+        }
+    }
+
+    fn update_pending_keys_text(&mut self) {
+        let count_text = self
+            .pending_count
+            .map(|count| count.to_string())
+            .unwrap_or_default();
+        let keys_text = format_key_sequence(&self.pending_keys);
+
+        self.pager
+            .set_pending_keys(format!("{}{}", count_text, keys_text));
+    }
+
+    fn clear_pending_keys(&mut self) {
+        self.pending_count = None;
+        self.pending_keys.clear();
+        self.pager.set_pending_keys(String::new());
+    }
+
+    /// Limits a cursor movement to the first and last entry instead of
+    /// wrapping around.
+    pub fn get_clamped_cursor_delta(&self, cursor_delta: i32) -> i32 {
+        let last_flat_index = self.text_entries.len() as i32 - 1;
+        let cursor_row = (self.pager.cursor_row + cursor_delta)
+            .min(last_flat_index)
+            .max(0);
+
+        cursor_row - self.pager.cursor_row
+    }
+
+    pub fn update_pager(&mut self, cursor_delta: i32) {
         self.pager.update(
             cursor_delta,
             &self.text_entries,
-            self.path_node_root.get_absolute_path(),
+            self.path_tree.get_root().get_absolute_path(),
         );
     }
 }
@@ -37,26 +265,17 @@ impl<W: Write> EventQueue<W> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::model::config::Config;
-    use crate::model::path_node::PathNode;
-    use crate::view::composer::Composer;
-    use crate::view::Pager;
+    use crate::controller;
 
     fn prepare_event_queue() -> EventQueue<Vec<u8>> {
-        let config = Config::default();
-
-        let composer = Composer::from(config.clone());
-        let pager = Pager::new(config.clone(), Vec::new());
-        let path_node = PathNode::from(config.setup.working_dir.clone());
-
-        EventQueue::new(config, composer, pager, path_node)
+        controller::prepare_event_queue(|_| {})
     }
 
     #[test]
     fn match_key_event_default_test() {
         let result = {
             let mut event_queue = prepare_event_queue();
-            event_queue.match_key_event(Key::from("nonsense"))
+            event_queue.match_key_event(Key::from("f12"))
         };
 
         assert!(result.is_some());
@@ -66,9 +285,10 @@ mod tests {
     fn match_key_event_quit_test() {
         let result = {
             let mut event_queue = prepare_event_queue();
-            event_queue.match_key_event(Key::from(
-                event_queue.config.keybinding.quit.clone(),
-            ))
+            let key = Key::from(
+                event_queue.config.borrow().keybinding.quit.0[0].clone(),
+            );
+            event_queue.match_key_event(key)
         };
 
         assert!(result.is_none());
@@ -78,9 +298,10 @@ mod tests {
     fn match_key_event_reload_test() {
         let result = {
             let mut event_queue = prepare_event_queue();
-            event_queue.match_key_event(Key::from(
-                event_queue.config.keybinding.reload.clone(),
-            ))
+            let key = Key::from(
+                event_queue.config.borrow().keybinding.reload.0[0].clone(),
+            );
+            event_queue.match_key_event(key)
         };
 
         assert!(result.is_some());
@@ -90,9 +311,38 @@ mod tests {
     fn match_key_event_file_action_test() {
         let result = {
             let mut event_queue = prepare_event_queue();
-            event_queue.match_key_event(Key::from(
-                event_queue.config.keybinding.file_action.clone(),
-            ))
+            let key = Key::from(
+                event_queue.config.borrow().keybinding.file_action.0[0].clone(),
+            );
+            event_queue.match_key_event(key)
+        };
+
+        assert!(result.is_some());
+    }
+
+    #[test]
+    fn match_key_event_history_back_test() {
+        let result = {
+            let mut event_queue = prepare_event_queue();
+            let key = Key::from(
+                event_queue.config.borrow().keybinding.history_back.0[0]
+                    .clone(),
+            );
+            event_queue.match_key_event(key)
+        };
+
+        assert!(result.is_some());
+    }
+
+    #[test]
+    fn match_key_event_history_forward_test() {
+        let result = {
+            let mut event_queue = prepare_event_queue();
+            let key = Key::from(
+                event_queue.config.borrow().keybinding.history_forward.0[0]
+                    .clone(),
+            );
+            event_queue.match_key_event(key)
         };
 
         assert!(result.is_some());
@@ -102,9 +352,10 @@ mod tests {
     fn match_key_event_entry_up_test() {
         let result = {
             let mut event_queue = prepare_event_queue();
-            event_queue.match_key_event(Key::from(
-                event_queue.config.keybinding.entry_up.clone(),
-            ))
+            let key = Key::from(
+                event_queue.config.borrow().keybinding.entry_up.0[0].clone(),
+            );
+            event_queue.match_key_event(key)
         };
 
         assert!(result.is_some());
@@ -114,21 +365,96 @@ mod tests {
     fn match_key_event_entry_down_test() {
         let result = {
             let mut event_queue = prepare_event_queue();
-            event_queue.match_key_event(Key::from(
-                event_queue.config.keybinding.entry_down.clone(),
-            ))
+            let key = Key::from(
+                event_queue.config.borrow().keybinding.entry_down.0[0].clone(),
+            );
+            event_queue.match_key_event(key)
         };
 
         assert!(result.is_some());
     }
 
+    #[test]
+    fn match_key_event_bookmark_set_test() {
+        let mut event_queue = prepare_event_queue();
+
+        let key = Key::from(
+            event_queue.config.borrow().keybinding.bookmark_set.0[0].clone(),
+        );
+        event_queue.match_key_event(key);
+        assert_eq!(Some(MarkAction::Set), event_queue.pending_mark_action);
+
+        let result = event_queue.match_key_event(Key::from("a"));
+        assert!(result.is_some());
+        assert_eq!(None, event_queue.pending_mark_action);
+        assert!(event_queue.bookmarks.get('a').is_some());
+    }
+
+    #[test]
+    fn match_key_event_bookmark_jump_test() {
+        let mut event_queue = prepare_event_queue();
+
+        let key = Key::from(
+            event_queue.config.borrow().keybinding.bookmark_jump.0[0].clone(),
+        );
+        event_queue.match_key_event(key);
+        assert_eq!(Some(MarkAction::Jump), event_queue.pending_mark_action);
+
+        let result = event_queue.match_key_event(Key::from("q"));
+        assert!(result.is_some(), "the mark must not be matched as quit");
+        assert_eq!(None, event_queue.pending_mark_action);
+    }
+
+    #[test]
+    fn match_key_event_bookmark_list_test() {
+        let mut event_queue = prepare_event_queue();
+
+        let key = Key::from(
+            event_queue.config.borrow().keybinding.bookmark_list.0[0].clone(),
+        );
+        event_queue.match_key_event(key);
+        assert!(event_queue.pager.has_overlay());
+
+        let result = event_queue.match_key_event(Key::from("q"));
+        assert!(result.is_some(), "closing the overlay must not quit");
+        assert!(!event_queue.pager.has_overlay());
+    }
+
     #[test]
     fn match_key_event_collapse_dir_test() {
         let result = {
             let mut event_queue = prepare_event_queue();
-            event_queue.match_key_event(Key::from(
-                event_queue.config.keybinding.collapse_dir.clone(),
-            ))
+            let key = Key::from(
+                event_queue.config.borrow().keybinding.collapse_dir.0[0]
+                    .clone(),
+            );
+            event_queue.match_key_event(key)
+        };
+
+        assert!(result.is_some());
+    }
+
+    #[test]
+    fn match_key_event_enter_dir_test() {
+        let result = {
+            let mut event_queue = prepare_event_queue();
+            let key = Key::from(
+                event_queue.config.borrow().keybinding.enter_dir.0[0].clone(),
+            );
+            event_queue.match_key_event(key)
+        };
+
+        assert!(result.is_some());
+    }
+
+    #[test]
+    fn match_key_event_parent_dir_test() {
+        let result = {
+            let mut event_queue = prepare_event_queue();
+            let key = Key::from(
+                event_queue.config.borrow().keybinding.parent_dir.0[0].clone(),
+            );
+            event_queue.match_key_event(key)
         };
 
         assert!(result.is_some());
@@ -138,11 +464,231 @@ mod tests {
     fn match_key_event_expand_dir_test() {
         let result = {
             let mut event_queue = prepare_event_queue();
-            event_queue.match_key_event(Key::from(
-                event_queue.config.keybinding.expand_dir.clone(),
-            ))
+            let key = Key::from(
+                event_queue.config.borrow().keybinding.expand_dir.0[0].clone(),
+            );
+            event_queue.match_key_event(key)
         };
 
         assert!(result.is_some());
     }
+
+    mod keymap_tests {
+        use super::*;
+
+        fn prepare_event_queue() -> EventQueue<Vec<u8>> {
+            controller::prepare_event_queue(|config| {
+                let keymap = &mut config.keymap.0;
+                keymap
+                    .insert(String::from("o"), String::from("echo %s").into());
+                keymap.insert(
+                    String::from("<space>q"),
+                    String::from("quit").into(),
+                );
+                config.behavior.quit_on_action = true;
+            })
+        }
+
+        #[test]
+        fn command() {
+            let mut event_queue = prepare_event_queue();
+
+            let result = event_queue.match_key_event(Key::from("o"));
+
+            assert!(result.is_none());
+            let argv = event_queue.command_to_run_on_exit.unwrap();
+            let command = argv[2].to_str().unwrap();
+            assert!(command.starts_with("echo '/"));
+            assert!(command.ends_with("tests/test_dirs/dir0'"));
+        }
+
+        #[test]
+        fn builtin() {
+            let mut event_queue = prepare_event_queue();
+
+            event_queue.match_key_event(Key::from("space"));
+            let result = event_queue.match_key_event(Key::from("q"));
+
+            assert!(result.is_none());
+            assert_eq!(None, event_queue.command_to_run_on_exit);
+        }
+    }
+
+    mod key_sequence_tests {
+        use super::*;
+        use crate::model::config::keybinding::Keys;
+
+        fn prepare_event_queue() -> EventQueue<Vec<u8>> {
+            controller::prepare_event_queue(|config| {
+                config.keybinding.bookmark_set = Keys::from("<space>m");
+                config.keybinding.quit =
+                    Keys(vec![String::from("q"), String::from("ctrl+c")]);
+            })
+        }
+
+        #[test]
+        fn complete_sequence() {
+            let mut event_queue = prepare_event_queue();
+
+            event_queue.match_key_event(Key::from("space"));
+            assert_eq!(vec![Key::from("space")], event_queue.pending_keys);
+            assert_eq!(None, event_queue.pending_mark_action);
+
+            event_queue.match_key_event(Key::from("m"));
+            assert!(event_queue.pending_keys.is_empty());
+            assert_eq!(Some(MarkAction::Set), event_queue.pending_mark_action);
+        }
+
+        #[test]
+        fn old_key_unbound() {
+            let mut event_queue = prepare_event_queue();
+
+            event_queue.match_key_event(Key::from("m"));
+
+            assert_eq!(None, event_queue.pending_mark_action);
+        }
+
+        #[test]
+        fn second_key() {
+            let mut event_queue = prepare_event_queue();
+
+            let result = event_queue.match_key_event(Key::from("ctrl+c"));
+
+            assert!(result.is_none());
+        }
+
+        #[test]
+        fn broken_sequence() {
+            let mut event_queue = prepare_event_queue();
+
+            event_queue.match_key_event(Key::from("space"));
+            event_queue.match_key_event(Key::from("B"));
+
+            assert!(event_queue.pending_keys.is_empty());
+            assert!(event_queue.pager.has_overlay());
+        }
+
+        #[test]
+        fn timeout() {
+            let mut event_queue = prepare_event_queue();
+            event_queue.match_key_event(Key::from("space"));
+
+            event_queue.apply_key_sequence_timeout(0);
+            assert_eq!(1, event_queue.pending_keys.len());
+
+            event_queue.apply_key_sequence_timeout(event_queue.pending_keys_id);
+            assert!(event_queue.pending_keys.is_empty());
+
+            event_queue.match_key_event(Key::from("m"));
+            assert_eq!(None, event_queue.pending_mark_action);
+        }
+
+        #[test]
+        fn timeout_event() {
+            let mut event_queue = controller::prepare_event_queue(|config| {
+                config.keybinding.bookmark_set = Keys::from("<space>m");
+                config.behavior.key_sequence_timeout_ms = 1;
+            });
+            event_queue.match_key_event(Key::from("space"));
+
+            let event = event_queue.queue_receiver.recv().unwrap();
+
+            assert_eq!(
+                Event::KeySequenceTimeout(event_queue.pending_keys_id),
+                event
+            );
+        }
+    }
+
+    mod count_tests {
+        use super::*;
+        use crate::model::config::keybinding::Keys;
+
+        fn prepare_event_queue() -> EventQueue<Vec<u8>> {
+            controller::prepare_event_queue(|config| {
+                config.keybinding.entry_down =
+                    Keys(vec![String::from("down"), String::from("j")]);
+                config.keybinding.reload = Keys::from("1");
+            })
+        }
+
+        fn press_keys(event_queue: &mut EventQueue<Vec<u8>>, keys: &[&str]) {
+            for key in keys {
+                event_queue.match_key_event(Key::from(*key));
+            }
+        }
+
+        #[test]
+        fn counted_move() {
+            let mut event_queue = prepare_event_queue();
+
+            press_keys(&mut event_queue, &["5"]);
+            assert_eq!(Some(5), event_queue.pending_count);
+
+            press_keys(&mut event_queue, &["j"]);
+            assert_eq!(5, event_queue.pager.cursor_row);
+            assert_eq!(None, event_queue.pending_count);
+
+            press_keys(&mut event_queue, &["3", "up"]);
+            assert_eq!(2, event_queue.pager.cursor_row);
+        }
+
+        #[test]
+        fn multiple_digits() {
+            let mut event_queue = prepare_event_queue();
+
+            press_keys(&mut event_queue, &["2", "1", "0", "up"]);
+            assert_eq!(0, event_queue.pager.cursor_row);
+
+            press_keys(&mut event_queue, &["2", "1", "0", "down"]);
+            assert_eq!(12, event_queue.pager.cursor_row);
+        }
+
+        #[test]
+        fn bound_digits() {
+            let mut event_queue = prepare_event_queue();
+
+            press_keys(&mut event_queue, &["0"]);
+            assert_eq!(None, event_queue.pending_count);
+
+            press_keys(&mut event_queue, &["1"]);
+            assert_eq!(None, event_queue.pending_count);
+        }
+
+        #[test]
+        fn counted_action_once() {
+            let mut event_queue = prepare_event_queue();
+            event_queue.pager.cursor_row = 1;
+            let enter_dir =
+                event_queue.config.borrow().keybinding.enter_dir.0[0].clone();
+
+            press_keys(&mut event_queue, &["3", &enter_dir]);
+
+            assert!(event_queue.path_tree.get_root().path.ends_with("dir1"));
+            assert_eq!(None, event_queue.pending_count);
+        }
+
+        #[test]
+        fn counted_parent_dir() {
+            let mut event_queue = controller::prepare_event_queue(|config| {
+                config.setup.working_dir =
+                    String::from("./tests/test_dirs/dir1/dir6/dir9");
+            });
+            let parent_dir =
+                event_queue.config.borrow().keybinding.parent_dir.0[0].clone();
+
+            press_keys(&mut event_queue, &["2", &parent_dir]);
+
+            assert!(event_queue.path_tree.get_root().path.ends_with("dir1"));
+        }
+
+        #[test]
+        fn unbound_key() {
+            let mut event_queue = prepare_event_queue();
+
+            press_keys(&mut event_queue, &["5", "z", "down"]);
+
+            assert_eq!(1, event_queue.pager.cursor_row);
+        }
+    }
 }
@@ -0,0 +1,82 @@
+use crate::controller::EventQueue;
+use crate::controller::MarkAction;
+use std::io::Write;
+
+impl<W: Write> EventQueue<W> {
+    pub fn do_bookmark_jump(&mut self) -> Option<()> {
+        self.pending_mark_action = Some(MarkAction::Jump);
+        Some(())
+    }
+
+    /// Reveals the bookmarked path, see `reveal`.
+    pub fn jump_to_bookmark(&mut self, mark: char) {
+        match self.bookmarks.get(mark) {
+            Some(path) if path.exists() => {
+                let path = path.clone();
+                self.reveal(path);
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controller;
+    use std::fs::canonicalize;
+
+    fn prepare_event_queue() -> EventQueue<Vec<u8>> {
+        controller::prepare_event_queue(|config| {
+            config.setup.working_dir = String::from("./tests/test_dirs/dir1");
+        })
+    }
+
+    #[test]
+    fn jump_inside_tree() {
+        let mut event_queue = prepare_event_queue();
+        event_queue.pager.cursor_row = 3;
+        event_queue.set_bookmark('a');
+        event_queue.pager.cursor_row = 0;
+
+        event_queue.jump_to_bookmark('a');
+
+        assert!(event_queue.path_tree.get_root().path.ends_with("dir1"));
+        assert_eq!(3, event_queue.pager.cursor_row);
+    }
+
+    #[test]
+    fn jump_to_dir_outside_tree() {
+        let mut event_queue = prepare_event_queue();
+        let dir = canonicalize("./tests/test_dirs/dir0").unwrap();
+        event_queue.bookmarks.set('a', dir);
+
+        event_queue.jump_to_bookmark('a');
+
+        assert!(event_queue.path_tree.get_root().path.ends_with("dir0"));
+        assert_eq!(0, event_queue.pager.cursor_row);
+    }
+
+    #[test]
+    fn jump_to_file_outside_tree() {
+        let mut event_queue = prepare_event_queue();
+        let file = canonicalize("./tests/test_dirs/dir0/dir3/file5").unwrap();
+        event_queue.bookmarks.set('a', file);
+
+        event_queue.jump_to_bookmark('a');
+
+        assert!(event_queue.path_tree.get_root().path.ends_with("dir3"));
+        assert_eq!(1, event_queue.pager.cursor_row);
+    }
+
+    #[test]
+    fn jump_to_unknown_mark() {
+        let mut event_queue = prepare_event_queue();
+        event_queue.pager.cursor_row = 2;
+
+        event_queue.jump_to_bookmark('z');
+
+        assert!(event_queue.path_tree.get_root().path.ends_with("dir1"));
+        assert_eq!(2, event_queue.pager.cursor_row);
+    }
+}
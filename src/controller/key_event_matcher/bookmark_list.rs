@@ -0,0 +1,12 @@
+use crate::controller::EventQueue;
+use std::io::Write;
+
+impl<W: Write> EventQueue<W> {
+    pub fn do_bookmark_list(&mut self) -> Option<()> {
+        let overlay_entries = self.composer.compose_bookmarks(&self.bookmarks);
+        self.pager.set_overlay(overlay_entries);
+
+        self.update_pager(0);
+        Some(())
+    }
+}
@@ -0,0 +1,23 @@
+use crate::controller::EventQueue;
+use crate::controller::MarkAction;
+use std::io::Write;
+
+impl<W: Write> EventQueue<W> {
+    pub fn do_bookmark_set(&mut self) -> Option<()> {
+        self.pending_mark_action = Some(MarkAction::Set);
+        Some(())
+    }
+
+    /// Bookmarks the entry under the cursor, unreadable entries are skipped.
+    pub fn set_bookmark(&mut self, mark: char) {
+        let node_id = self
+            .path_tree
+            .flat_index_to_node_id(self.pager.cursor_row as usize);
+        if self.path_tree.get_path_node(node_id).is_err {
+            return;
+        }
+
+        let location = self.get_location();
+        self.bookmarks.set(mark, location.cursor);
+    }
+}
@@ -0,0 +1,47 @@
+use crate::controller::EventQueue;
+use crate::model::path_tree::PathTree;
+use std::io::Write;
+
+impl<W: Write> EventQueue<W> {
+    pub fn do_collapse_all(&mut self) -> Option<()> {
+        let mut node_id = self
+            .path_tree
+            .flat_index_to_node_id(self.pager.cursor_row as usize);
+
+        // the cursor stays on the top level entry containing the old entry
+        while let Some(parent_id) = self.path_tree.get_path_node(node_id).parent
+        {
+            if parent_id == PathTree::ROOT_ID {
+                break;
+            }
+            node_id = parent_id;
+        }
+
+        self.path_tree.collapse_all();
+        self.cancel_orphaned_dir_listings();
+
+        self.update_text_entries();
+
+        self.update_pager_to_node_id(node_id);
+        Some(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::controller::prepare_event_queue;
+
+    #[test]
+    fn collapse_all() {
+        let mut event_queue = prepare_event_queue(|_| {});
+        event_queue.pager.cursor_row = 1;
+        event_queue.do_expand_all();
+        event_queue.wait_for_dir_listings();
+        event_queue.pager.cursor_row = 8;
+
+        event_queue.do_collapse_all();
+
+        assert_eq!(13, event_queue.text_entries.len());
+        assert_eq!(1, event_queue.pager.cursor_row);
+    }
+}
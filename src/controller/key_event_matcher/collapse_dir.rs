@@ -1,42 +1,48 @@
 use crate::controller::EventQueue;
-use crate::model::tree_index::TreeIndex;
+use crate::model::path_tree::NodeId;
 use std::io::Write;
 
 impl<W: Write> EventQueue<W> {
     pub fn do_collapse_dir(&mut self) -> Option<()> {
-        let tree_index = self
-            .path_node_root
-            .flat_index_to_tree_index(self.pager.cursor_row as usize);
+        let node_id = self
+            .path_tree
+            .flat_index_to_node_id(self.pager.cursor_row as usize);
 
-        let cursor_delta = self.get_parent_dir_cursor_delta(&tree_index);
+        if self.path_tree.get_path_node(node_id).is_loading {
+            self.cancel_dir_listing(node_id);
+
+            self.update_text_entries();
+
+            self.update_pager(0);
+            return Some(());
+        }
+
+        let cursor_delta = self.get_parent_dir_cursor_delta(node_id);
 
         if cursor_delta == 0 {
-            self.path_node_root.collapse_dir(&tree_index);
+            self.path_tree.collapse_dir(node_id);
+            self.cancel_orphaned_dir_listings();
         }
 
-        self.text_entries =
-            self.composer.compose_path_node(&self.path_node_root);
+        self.update_text_entries();
 
         self.update_pager(cursor_delta);
         Some(())
     }
 
-    fn get_parent_dir_cursor_delta(&mut self, tree_index: &TreeIndex) -> i32 {
-        let child_path_node =
-            self.path_node_root.get_child_path_node(tree_index);
+    fn get_parent_dir_cursor_delta(&mut self, node_id: NodeId) -> i32 {
+        let child_path_node = self.path_tree.get_path_node(node_id);
         if child_path_node.is_dir && child_path_node.is_expanded {
             return 0;
         }
 
-        let parent_path_node_tree_index = tree_index.get_parent();
-        if parent_path_node_tree_index == TreeIndex::new() {
-            return 0;
-        }
-
-        let parent_flat_index = self
-            .path_node_root
-            .tree_index_to_flat_index(&parent_path_node_tree_index)
-            as i32;
+        let parent_flat_index =
+            match child_path_node.parent.and_then(|parent_id| {
+                self.path_tree.node_id_to_flat_index(parent_id)
+            }) {
+                Some(parent_flat_index) => parent_flat_index as i32,
+                None => return 0,
+            };
 
         parent_flat_index - self.pager.cursor_row
     }
@@ -45,52 +51,27 @@ impl<W: Write> EventQueue<W> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::model::config::Config;
+    use crate::controller;
     use crate::model::path_node::PathNode;
-    use crate::model::tree_index::TreeIndex;
-    use crate::view::composer::Composer;
-    use crate::view::Pager;
+    use crate::model::path_tree::PathTree;
 
     // TODO: duplicate code, create test utils?
-    fn get_expanded_path_node() -> PathNode {
-        let mut path_node = PathNode::from("./tests/test_dirs");
-        path_node
-            .expand_dir(&TreeIndex::new(), PathNode::compare_dirs_top_simple);
-        path_node.expand_dir(
-            &TreeIndex::from(vec![0]),
-            PathNode::compare_dirs_top_simple,
-        );
-        path_node.expand_dir(
-            &TreeIndex::from(vec![0, 0]),
-            PathNode::compare_dirs_top_simple,
-        );
-        path_node.expand_dir(
-            &TreeIndex::from(vec![1]),
-            PathNode::compare_dirs_top_simple,
-        );
-        path_node.expand_dir(
-            &TreeIndex::from(vec![1, 0]),
-            PathNode::compare_dirs_top_simple,
-        );
-        path_node.expand_dir(
-            &TreeIndex::from(vec![1, 0, 2]),
-            PathNode::compare_dirs_top_simple,
-        );
-        path_node
+    fn get_expanded_path_tree() -> PathTree {
+        let mut path_tree = PathTree::from(PathNode::from("./tests/test_dirs"));
+        for child_indices in
+            &[&[][..], &[0], &[0, 0], &[1], &[1, 0], &[1, 0, 2]]
+        {
+            path_tree.expand_dir(
+                path_tree.get_node_id_at(child_indices),
+                PathNode::compare_dirs_top_simple,
+            );
+        }
+        path_tree
     }
 
     fn prepare_event_queue() -> EventQueue<Vec<u8>> {
-        let config = Config::default();
-
-        let composer = Composer::from(config.clone());
-        let pager = Pager::new(config.clone(), Vec::new());
-        let path_node = PathNode::from(config.setup.working_dir.clone());
-
-        let mut event_queue =
-            EventQueue::new(config, composer, pager, path_node);
-
-        event_queue.path_node_root = get_expanded_path_node();
-
+        let mut event_queue = controller::prepare_event_queue(|_| {});
+        event_queue.set_path_tree(get_expanded_path_tree());
         event_queue
     }
 
@@ -100,19 +81,19 @@ mod tests {
         #[test]
         fn expanded() {
             let mut event_queue = prepare_event_queue();
+            let node_id = event_queue.path_tree.get_node_id_at(&[0]);
 
-            let delta = event_queue
-                .get_parent_dir_cursor_delta(&TreeIndex::from(vec![0]));
+            let delta = event_queue.get_parent_dir_cursor_delta(node_id);
 
             assert_eq!(0, delta);
         }
 
         #[test]
-        fn empty_tree_index() {
+        fn root() {
             let mut event_queue = prepare_event_queue();
 
             let delta =
-                event_queue.get_parent_dir_cursor_delta(&TreeIndex::new());
+                event_queue.get_parent_dir_cursor_delta(PathTree::ROOT_ID);
 
             assert_eq!(0, delta);
         }
@@ -120,9 +101,9 @@ mod tests {
         #[test]
         fn jump() {
             let mut event_queue = prepare_event_queue();
+            let node_id = event_queue.path_tree.get_node_id_at(&[1, 0, 4]);
 
-            let delta = event_queue
-                .get_parent_dir_cursor_delta(&TreeIndex::from(vec![1, 0, 4]));
+            let delta = event_queue.get_parent_dir_cursor_delta(node_id);
 
             assert_eq!(7, delta);
         }
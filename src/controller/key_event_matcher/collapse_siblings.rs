@@ -0,0 +1,42 @@
+use crate::controller::EventQueue;
+use std::io::Write;
+
+impl<W: Write> EventQueue<W> {
+    pub fn do_collapse_siblings(&mut self) -> Option<()> {
+        let node_id = self
+            .path_tree
+            .flat_index_to_node_id(self.pager.cursor_row as usize);
+
+        self.path_tree.collapse_siblings(node_id);
+        self.cancel_orphaned_dir_listings();
+
+        self.update_text_entries();
+
+        self.update_pager_to_node_id(node_id);
+        Some(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::controller::prepare_event_queue;
+
+    #[test]
+    fn collapse_siblings() {
+        let mut event_queue = prepare_event_queue(|_| {});
+        event_queue.pager.cursor_row = 0;
+        event_queue.do_expand_all();
+        event_queue.wait_for_dir_listings();
+        event_queue.pager.cursor_row = 8;
+        event_queue.do_expand_all();
+        event_queue.wait_for_dir_listings();
+        assert_eq!(40, event_queue.text_entries.len());
+
+        // dir1/dir6/dir10
+        event_queue.pager.cursor_row = 10;
+        event_queue.do_collapse_siblings();
+
+        assert_eq!(24, event_queue.text_entries.len());
+        assert_eq!(3, event_queue.pager.cursor_row);
+    }
+}
@@ -0,0 +1,360 @@
+use crate::controller::EventQueue;
+use crate::model::event::Key;
+use crate::model::history::Location;
+use crate::model::prompt::resolve_path;
+use crate::model::prompt::Prompt;
+use crate::utils::canonicalize_or_keep;
+use log::info;
+use std::fs::canonicalize;
+use std::fs::create_dir_all;
+use std::io::Write;
+use std::path::PathBuf;
+use termion::event::Key as TKey;
+
+/// The number of commands kept in the prompt history.
+const PROMPT_HISTORY_SIZE: usize = 100;
+
+impl<W: Write> EventQueue<W> {
+    pub fn do_command_prompt(&mut self) -> Option<()> {
+        self.update_prompt(Prompt::default());
+        Some(())
+    }
+
+    /// Edits the prompt like a shell line, enter runs the command and esc
+    /// cancels it.
+    pub fn match_prompt_key(
+        &mut self,
+        mut prompt: Prompt,
+        key: Key,
+    ) -> Option<()> {
+        if self.pager.has_overlay() {
+            self.pager.clear_overlay();
+        }
+
+        let termion_key = match key.get_termion_key() {
+            Some(termion_key) => termion_key,
+            None => {
+                self.update_prompt(prompt);
+                return Some(());
+            }
+        };
+
+        match termion_key {
+            TKey::Char('\n') => return self.run_prompt(prompt.text),
+            TKey::Esc | TKey::Ctrl('c') | TKey::Ctrl('g') => {
+                return self.close_prompt();
+            }
+            TKey::Backspace if prompt.text.is_empty() => {
+                return self.close_prompt();
+            }
+            TKey::Char('\t') => {
+                let base_dir =
+                    canonicalize_or_keep(&self.path_tree.get_root().path);
+                let candidates = prompt.complete(&base_dir);
+                if !candidates.is_empty() {
+                    let overlay_entries =
+                        self.composer.compose_completions(&candidates);
+                    self.pager.set_overlay(overlay_entries);
+                }
+            }
+            TKey::Up | TKey::Ctrl('p') => {
+                prompt.history_up(&self.prompt_history)
+            }
+            TKey::Down | TKey::Ctrl('n') => {
+                prompt.history_down(&self.prompt_history)
+            }
+            TKey::Left | TKey::Ctrl('b') => prompt.move_left(),
+            TKey::Right | TKey::Ctrl('f') => prompt.move_right(),
+            TKey::Home | TKey::Ctrl('a') => prompt.move_home(),
+            TKey::End | TKey::Ctrl('e') => prompt.move_end(),
+            TKey::Backspace => prompt.delete_before_cursor(),
+            TKey::Delete | TKey::Ctrl('d') => prompt.delete_at_cursor(),
+            TKey::Ctrl('w') => prompt.delete_word_before_cursor(),
+            TKey::Ctrl('u') => prompt.delete_line_before_cursor(),
+            TKey::Char(c) if !c.is_control() => prompt.insert(c),
+            _ => {}
+        }
+
+        self.update_prompt(prompt);
+        Some(())
+    }
+
+    fn update_prompt(&mut self, prompt: Prompt) {
+        self.pager
+            .set_prompt(Some((prompt.text.clone(), prompt.cursor)));
+        self.prompt = Some(prompt);
+        self.update_pager(0);
+    }
+
+    fn close_prompt(&mut self) -> Option<()> {
+        self.pager.set_prompt(None);
+        self.update_pager(0);
+        Some(())
+    }
+
+    fn run_prompt(&mut self, text: String) -> Option<()> {
+        self.close_prompt();
+
+        let text = text.trim();
+        if text.is_empty() {
+            return Some(());
+        }
+
+        if self.prompt_history.last().map(String::as_str) != Some(text) {
+            self.prompt_history.push(String::from(text));
+            if self.prompt_history.len() > PROMPT_HISTORY_SIZE {
+                self.prompt_history.remove(0);
+            }
+        }
+
+        info!("running prompt command: {}", text);
+
+        if let Some(command) = text.strip_prefix('!') {
+            let mode = self.get_command_mode(&None);
+            return self.do_command(command, &mode);
+        }
+
+        let (command, argument) = match text.find(' ') {
+            Some(index) => (&text[..index], text[index + 1..].trim()),
+            None => (text, ""),
+        };
+
+        let result = match command {
+            "cd" => self.prompt_cd(argument),
+            "mkdir" => self.prompt_mkdir(argument),
+            "reveal" => self.prompt_reveal(argument),
+            "set" => self.prompt_set(argument),
+            "sort" => self.prompt_sort(argument),
+            _ => Err(format!("unknown command: {}", command)),
+        };
+
+        if let Err(err) = result {
+            self.set_status(err);
+        }
+
+        Some(())
+    }
+
+    /// Resolves the argument against the root directory.
+    fn resolve_prompt_path(&self, path: &str) -> PathBuf {
+        let base_dir = canonicalize_or_keep(&self.path_tree.get_root().path);
+        resolve_path(&base_dir, path)
+    }
+
+    fn prompt_cd(&mut self, path: &str) -> Result<(), String> {
+        let path = if path.is_empty() { "~" } else { path };
+
+        let dir = canonicalize(self.resolve_prompt_path(path))
+            .map_err(|err| format!("cd: {}: {}", path, err))?;
+        if !dir.is_dir() {
+            return Err(format!("cd: {}: not a directory", path));
+        }
+
+        self.record_location();
+        self.jump_to_location(&Location {
+            root: dir.clone(),
+            cursor: dir,
+        });
+        Ok(())
+    }
+
+    fn prompt_mkdir(&mut self, path: &str) -> Result<(), String> {
+        if path.is_empty() {
+            return Err(String::from("mkdir: missing directory name"));
+        }
+
+        let dir = self.resolve_prompt_path(path);
+        create_dir_all(&dir)
+            .map_err(|err| format!("mkdir: {}: {}", path, err))?;
+
+        self.reload_openend_dirs();
+        self.reveal(canonicalize_or_keep(&dir));
+        Ok(())
+    }
+
+    fn prompt_reveal(&mut self, path: &str) -> Result<(), String> {
+        let path_buf = canonicalize(self.resolve_prompt_path(path))
+            .map_err(|err| format!("reveal: {}: {}", path, err))?;
+
+        self.reveal(path_buf);
+        Ok(())
+    }
+
+    fn prompt_sort(&mut self, sort: &str) -> Result<(), String> {
+        self.set_option("behavior.path_node_sort", sort)
+            .map_err(|err| format!("sort: {}: {}", sort, err))?;
+
+        self.set_status(format!("behavior.path_node_sort={}", sort));
+        Ok(())
+    }
+
+    /// Sets an option like `--key=value` on the command line.
+    fn prompt_set(&mut self, argument: &str) -> Result<(), String> {
+        let argument = argument.trim_start_matches("--");
+        let (key, value) = match argument.find('=') {
+            Some(index) => (&argument[..index], &argument[index + 1..]),
+            None => {
+                return Err(format!("set: {}: expected key=value", argument))
+            }
+        };
+
+        self.set_option(key, value)
+            .map_err(|err| format!("set: {}: {}", argument, err))?;
+
+        self.set_status(format!("{}={}", key, value));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controller::prepare_event_queue;
+
+    fn type_line(event_queue: &mut EventQueue<Vec<u8>>, line: &str) {
+        event_queue.match_key_event(Key::from(":"));
+        for c in line.chars() {
+            event_queue.match_key_event(Key::from(c.to_string()));
+        }
+    }
+
+    fn run_line(event_queue: &mut EventQueue<Vec<u8>>, line: &str) {
+        type_line(event_queue, line);
+        event_queue.match_key_event(Key::from("return"));
+    }
+
+    #[test]
+    fn type_and_cancel() {
+        let mut event_queue = prepare_event_queue(|_| {});
+
+        type_line(&mut event_queue, "qcd");
+        assert_eq!("qcd", event_queue.prompt.as_ref().unwrap().text);
+
+        let result = event_queue.match_key_event(Key::from("esc"));
+        assert!(result.is_some());
+        assert_eq!(None, event_queue.prompt);
+
+        let result = event_queue.match_key_event(Key::from("q"));
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn cd() {
+        let mut event_queue = prepare_event_queue(|_| {});
+
+        run_line(&mut event_queue, "cd dir1/dir6");
+
+        let location = event_queue.get_location();
+        assert!(location.root.ends_with("tests/test_dirs/dir1/dir6"));
+        assert_eq!(None, event_queue.prompt);
+        assert_eq!(vec!["cd dir1/dir6"], event_queue.prompt_history);
+
+        run_line(&mut event_queue, "cd ..");
+        assert!(event_queue.get_location().root.ends_with("dir1"));
+
+        event_queue.do_history_back();
+        assert!(event_queue.get_location().root.ends_with("dir6"));
+    }
+
+    #[test]
+    fn cd_to_file() {
+        let mut event_queue = prepare_event_queue(|_| {});
+
+        run_line(&mut event_queue, "cd file1");
+
+        assert_eq!(
+            "cd: file1: not a directory",
+            event_queue.pager.get_status()
+        );
+        assert!(event_queue.get_location().root.ends_with("test_dirs"));
+    }
+
+    #[test]
+    fn reveal() {
+        let mut event_queue = prepare_event_queue(|_| {});
+
+        run_line(&mut event_queue, "reveal dir1/dir6/file9");
+
+        let location = event_queue.get_location();
+        assert!(location.root.ends_with("tests/test_dirs"));
+        assert!(location.cursor.ends_with("dir1/dir6/file9"));
+
+        run_line(&mut event_queue, "reveal dir1/missing");
+        assert!(event_queue
+            .pager
+            .get_status()
+            .starts_with("reveal: dir1/missing: "));
+    }
+
+    #[test]
+    fn set() {
+        let mut event_queue = prepare_event_queue(|_| {});
+
+        run_line(&mut event_queue, "set keybinding.quit=Q");
+
+        assert_eq!("keybinding.quit=Q", event_queue.pager.get_status());
+        assert!(event_queue.match_key_event(Key::from("q")).is_some());
+        assert!(event_queue.match_key_event(Key::from("Q")).is_none());
+    }
+
+    #[test]
+    fn set_errors() {
+        let mut event_queue = prepare_event_queue(|_| {});
+
+        run_line(&mut event_queue, "set behavior.mouse=maybe");
+        assert_eq!(
+            "set: behavior.mouse=maybe: value not parsable",
+            event_queue.pager.get_status()
+        );
+
+        run_line(&mut event_queue, "set nothing=1");
+        assert_eq!(
+            "set: nothing=1: unknown option",
+            event_queue.pager.get_status()
+        );
+
+        run_line(&mut event_queue, "sort sideways");
+        assert!(event_queue
+            .pager
+            .get_status()
+            .starts_with("sort: sideways: expected one of "));
+
+        run_line(&mut event_queue, "dance");
+        assert_eq!("unknown command: dance", event_queue.pager.get_status());
+    }
+
+    #[test]
+    fn sort() {
+        let mut event_queue = prepare_event_queue(|_| {});
+        event_queue.pager.cursor_row = 3;
+
+        run_line(&mut event_queue, "sort dirs_bot_simple");
+
+        assert_eq!(
+            "dirs_bot_simple",
+            event_queue.config.borrow().behavior.path_node_sort
+        );
+        assert!(event_queue.text_entries[0].ends_with("file0"));
+        assert!(event_queue.get_location().cursor.ends_with("file0"));
+    }
+
+    #[test]
+    fn complete_and_history() {
+        let mut event_queue = prepare_event_queue(|_| {});
+
+        type_line(&mut event_queue, "cd dir1/d");
+        event_queue.match_key_event(Key::from("tab"));
+        assert_eq!("cd dir1/dir", event_queue.prompt.as_ref().unwrap().text);
+        assert!(event_queue.pager.has_overlay());
+
+        event_queue.match_key_event(Key::from("6"));
+        assert!(!event_queue.pager.has_overlay());
+        event_queue.match_key_event(Key::from("return"));
+
+        type_line(&mut event_queue, "x");
+        event_queue.match_key_event(Key::from("up"));
+        assert_eq!("cd dir1/dir6", event_queue.prompt.as_ref().unwrap().text);
+        event_queue.match_key_event(Key::from("down"));
+        assert_eq!("x", event_queue.prompt.as_ref().unwrap().text);
+    }
+}
@@ -0,0 +1,79 @@
+use crate::controller::EventQueue;
+use crate::model::path_tree::PathTree;
+use std::io::Write;
+
+impl<W: Write> EventQueue<W> {
+    pub fn do_enter_dir(&mut self) -> Option<()> {
+        let node_id = self
+            .path_tree
+            .flat_index_to_node_id(self.pager.cursor_row as usize);
+
+        let child_node = self.path_tree.get_path_node(node_id);
+
+        if !child_node.is_dir || node_id == PathTree::ROOT_ID {
+            return Some(());
+        }
+
+        // the subtree keeps its expansions, it only has to be opened itself
+        let mut new_path_tree = self.path_tree.get_subtree(node_id);
+
+        self.record_location();
+
+        if !new_path_tree.get_root().is_expanded {
+            new_path_tree.expand_dir(PathTree::ROOT_ID, self.path_node_compare);
+        }
+        self.set_path_tree(new_path_tree);
+
+        let cursor_delta = -self.pager.cursor_row;
+        self.update_pager(cursor_delta);
+        Some(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::controller::prepare_event_queue;
+    use crate::model::path_node::PathNode;
+
+    #[test]
+    fn enter_collapsed_dir() {
+        let mut event_queue = prepare_event_queue(|_| {});
+        event_queue.pager.cursor_row = 1;
+
+        event_queue.do_enter_dir();
+
+        assert!(event_queue.path_tree.get_root().path.ends_with("dir1"));
+        assert!(event_queue.path_tree.get_root().is_expanded);
+        assert_eq!(4, event_queue.text_entries.len());
+        assert_eq!(0, event_queue.pager.cursor_row);
+    }
+
+    #[test]
+    fn enter_expanded_dir_keeps_expansions() {
+        let mut event_queue = prepare_event_queue(|_| {});
+        for child_indices in &[&[1][..], &[1, 0]] {
+            event_queue.path_tree.expand_dir(
+                event_queue.path_tree.get_node_id_at(child_indices),
+                PathNode::compare_dirs_top_simple,
+            );
+        }
+        event_queue.pager.cursor_row = 1;
+
+        event_queue.do_enter_dir();
+
+        let node_id = event_queue.path_tree.get_node_id_at(&[0]);
+        assert!(event_queue.path_tree.get_path_node(node_id).is_expanded);
+        assert_eq!(10, event_queue.text_entries.len());
+    }
+
+    #[test]
+    fn enter_file() {
+        let mut event_queue = prepare_event_queue(|_| {});
+        event_queue.pager.cursor_row = 3;
+
+        event_queue.do_enter_dir();
+
+        assert!(event_queue.path_tree.get_root().path.ends_with("test_dirs"));
+        assert_eq!(3, event_queue.pager.cursor_row);
+    }
+}
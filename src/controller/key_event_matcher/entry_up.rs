@@ -2,8 +2,15 @@ use crate::controller::EventQueue;
 use std::io::Write;
 
 impl<W: Write> EventQueue<W> {
-    pub fn do_entry_up(&mut self) -> Option<()> {
-        self.update_pager(-1);
+    /// Moves the cursor up by `count` entries in a single update, a
+    /// single step wraps around.
+    pub fn do_entry_up(&mut self, count: usize) -> Option<()> {
+        if count > 1 {
+            let cursor_delta = self.get_clamped_cursor_delta(-(count as i32));
+            self.update_pager(cursor_delta);
+        } else {
+            self.update_pager(-1);
+        }
         Some(())
     }
 }
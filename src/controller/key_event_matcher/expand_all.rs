@@ -0,0 +1,168 @@
+use crate::controller::EventQueue;
+use crate::controller::ExpandAll;
+use crate::model::path_tree::NodeId;
+use std::collections::HashMap;
+use std::io::Write;
+
+impl<W: Write> EventQueue<W> {
+    /// Expands the directory and its subdirectories level by level, every
+    /// directory is listed in a worker thread. Stops below
+    /// `behavior.expand_all_max_depth` levels and once at least
+    /// `behavior.expand_all_max_entries` new entries were listed.
+    pub fn do_expand_all(&mut self) -> Option<()> {
+        let node_id = self
+            .path_tree
+            .flat_index_to_node_id(self.pager.cursor_row as usize);
+
+        let behavior = self.config.borrow().behavior.clone();
+        self.expand_all = Some(ExpandAll {
+            depths: HashMap::new(),
+            entry_count: 0,
+            max_depth: behavior.expand_all_max_depth.max(0) as usize,
+            max_entries: behavior.expand_all_max_entries.max(0) as usize,
+        });
+
+        self.continue_expand_all(node_id, 1);
+
+        self.update_text_entries();
+
+        self.update_pager(0);
+        Some(())
+    }
+
+    /// Continues the running expand_all below a directory whose listing
+    /// arrived.
+    pub fn apply_expand_all_listing(&mut self, node_id: NodeId) {
+        let path_node = self.path_tree.get_path_node(node_id);
+        let expand_all = match &mut self.expand_all {
+            Some(expand_all) => expand_all,
+            None => return,
+        };
+        let depth = match expand_all.depths.remove(&path_node.path) {
+            Some(depth) => depth,
+            None => return,
+        };
+
+        expand_all.entry_count += path_node.children.len();
+        for child_id in path_node.children.clone() {
+            self.continue_expand_all(child_id, depth + 1);
+        }
+
+        if let Some(expand_all) = &self.expand_all {
+            if expand_all.depths.is_empty() {
+                self.expand_all = None;
+            }
+        }
+    }
+
+    /// Lists the directory as a part of the running expand_all, an expanded
+    /// directory is not listed again but its subdirectories are.
+    fn continue_expand_all(&mut self, node_id: NodeId, depth: usize) {
+        let path_node = self.path_tree.get_path_node(node_id);
+        let expand_all = match &mut self.expand_all {
+            Some(expand_all) => expand_all,
+            None => return,
+        };
+
+        if !path_node.is_dir
+            || path_node.is_err
+            || depth > expand_all.max_depth
+            || expand_all.entry_count >= expand_all.max_entries
+        {
+            return;
+        }
+
+        if path_node.is_expanded {
+            for child_id in path_node.children.clone() {
+                self.continue_expand_all(child_id, depth + 1);
+            }
+        } else {
+            expand_all.depths.insert(path_node.path.clone(), depth);
+            self.expand_dir_async(node_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controller;
+    use std::path::PathBuf;
+
+    fn prepare_event_queue(
+        max_depth: i32,
+        max_entries: i32,
+    ) -> EventQueue<Vec<u8>> {
+        let mut event_queue = controller::prepare_event_queue(|config| {
+            config.behavior.expand_all_max_depth = max_depth;
+            config.behavior.expand_all_max_entries = max_entries;
+        });
+        event_queue.pager.cursor_row = 1;
+        event_queue
+    }
+
+    #[test]
+    fn unlimited() {
+        let mut event_queue = prepare_event_queue(100, 100);
+
+        event_queue.do_expand_all();
+        let dir1_id = event_queue.path_tree.get_node_id_at(&[1]);
+        assert!(event_queue.path_tree.get_path_node(dir1_id).is_loading);
+        event_queue.wait_for_dir_listings();
+
+        assert_eq!(
+            vec![
+                PathBuf::from("./tests/test_dirs/dir1"),
+                PathBuf::from("./tests/test_dirs/dir1/dir6"),
+                PathBuf::from("./tests/test_dirs/dir1/dir6/dir10"),
+                PathBuf::from("./tests/test_dirs/dir1/dir6/dir8"),
+                PathBuf::from("./tests/test_dirs/dir1/dir6/dir9"),
+                PathBuf::from("./tests/test_dirs/dir1/dir6/dir9/dir11"),
+                PathBuf::from("./tests/test_dirs/dir1/dir6/dir9/dir12"),
+                PathBuf::from("./tests/test_dirs/dir1/dir7"),
+            ],
+            event_queue.path_tree.get_expanded_paths()
+        );
+        assert_eq!(33, event_queue.text_entries.len());
+        assert!(event_queue.expand_all.is_none());
+    }
+
+    #[test]
+    fn max_depth() {
+        let mut event_queue = prepare_event_queue(2, 100);
+
+        event_queue.do_expand_all();
+        event_queue.wait_for_dir_listings();
+
+        assert_eq!(3, event_queue.path_tree.get_expanded_paths().len());
+    }
+
+    #[test]
+    fn max_entries() {
+        let mut event_queue = prepare_event_queue(100, 5);
+
+        event_queue.do_expand_all();
+        event_queue.wait_for_dir_listings();
+
+        // the subdirectories of a level are listed at the same time
+        assert_eq!(
+            vec![
+                PathBuf::from("./tests/test_dirs/dir1"),
+                PathBuf::from("./tests/test_dirs/dir1/dir6"),
+                PathBuf::from("./tests/test_dirs/dir1/dir7"),
+            ],
+            event_queue.path_tree.get_expanded_paths()
+        );
+    }
+
+    #[test]
+    fn file() {
+        let mut event_queue = prepare_event_queue(100, 100);
+        event_queue.pager.cursor_row = 3;
+
+        event_queue.do_expand_all();
+
+        assert!(event_queue.dir_listings.is_empty());
+        assert!(event_queue.path_tree.get_expanded_paths().is_empty());
+    }
+}
@@ -1,36 +1,95 @@
 use crate::controller::EventQueue;
+use crate::model::config::opener::get_matching_rules;
 use std::io::Write;
-use log::info;
 
 impl<W: Write> EventQueue<W> {
     pub fn do_file_action(&mut self) -> Option<()> {
-        let tree_index = self
-            .path_node_root
-            .flat_index_to_tree_index(self.pager.cursor_row as usize);
-
-        let child_node = self.path_node_root.get_child_path_node(&tree_index);
-
-        if !child_node.is_dir {
-            let file_path = &child_node.get_absolute_path();
-            let file_action_replaced =
-                self.config.behavior.file_action.replace("%s", file_path);
-
-            info!("executing file action:\n{}", file_action_replaced);
-
-
-            if self.config.behavior.quit_on_action {
-                self.command_to_run_on_exit = Some(file_action_replaced);
-                None
-            } else {
-                std::process::Command::new("bash")
-                    .arg("-c")
-                    .arg(file_action_replaced)
-                    .spawn()
-                    .unwrap();
-                Some(())
-            }
+        let node_id = self
+            .path_tree
+            .flat_index_to_node_id(self.pager.cursor_row as usize);
+
+        let child_node = self.path_tree.get_path_node(node_id);
+
+        if !child_node.is_dir && !child_node.is_err {
+            let (command, mode) = match get_matching_rules(
+                &self.config.borrow().opener,
+                &child_node.path,
+            )
+            .first()
+            {
+                Some(rule) => {
+                    (rule.command.clone(), self.get_command_mode(&rule.mode))
+                }
+                None => (
+                    self.config.borrow().behavior.file_action.clone(),
+                    self.get_command_mode(&None),
+                ),
+            };
+            self.run_command(&command, &mode)
+        } else {
+            Some(())
+        }
+    }
+
+    /// Runs a command of the keymap for the entry under the cursor, which may
+    /// also be a directory.
+    pub fn do_command(&mut self, command: &str, mode: &str) -> Option<()> {
+        let node_id = self
+            .path_tree
+            .flat_index_to_node_id(self.pager.cursor_row as usize);
+
+        if self.path_tree.get_path_node(node_id).is_err {
+            return Some(());
         }
-        else
-            {Some(())}
+
+        self.run_command(command, mode)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controller;
+    use crate::model::event::DirListing;
+    use crate::model::path_node::PathNode;
+    use std::path::PathBuf;
+
+    /// An event queue with the cursor on an unreadable entry in dir1.
+    fn prepare_event_queue() -> EventQueue<Vec<u8>> {
+        let mut event_queue = controller::prepare_event_queue(|config| {
+            config.behavior.quit_on_action = true;
+        });
+        event_queue
+            .expand_dir_async(event_queue.path_tree.get_node_id_at(&[1]));
+        event_queue.apply_dir_listing(DirListing {
+            id: event_queue.dir_listing_id,
+            path: PathBuf::from("./tests/test_dirs/dir1"),
+            children: Some(vec![PathNode::new_err(std::io::Error::from(
+                std::io::ErrorKind::PermissionDenied,
+            ))]),
+        });
+        event_queue.pager.cursor_row = 2;
+        event_queue
+    }
+
+    #[test]
+    fn error_entry() {
+        let mut event_queue = prepare_event_queue();
+        assert_eq!("  ⨯ <permission denied>", event_queue.text_entries[2]);
+
+        assert!(event_queue.do_file_action().is_some());
+        assert!(event_queue.do_command("echo %s", "background").is_some());
+        assert_eq!(None, event_queue.command_to_run_on_exit);
+
+        event_queue.set_bookmark('a');
+        assert_eq!(None, event_queue.bookmarks.get('a'));
+
+        assert_eq!(PathBuf::new(), event_queue.get_location().cursor);
+        assert_eq!(
+            Some(event_queue.path_tree.get_node_id_at(&[1])),
+            event_queue
+                .path_tree
+                .find_node_id(&PathBuf::from("./tests/test_dirs/dir1"))
+        );
     }
 }
@@ -0,0 +1,37 @@
+use crate::controller::EventQueue;
+use std::io::Write;
+
+impl<W: Write> EventQueue<W> {
+    pub fn do_history_back(&mut self) -> Option<()> {
+        let current_location = self.get_location();
+
+        if let Some(location) = self.history.back(current_location) {
+            self.jump_to_location(&location);
+        }
+
+        Some(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::controller::prepare_event_queue;
+
+    #[test]
+    fn back_and_forward_over_root_changes() {
+        let mut event_queue = prepare_event_queue(|_| {});
+        event_queue.pager.cursor_row = 1;
+        event_queue.do_enter_dir();
+        event_queue.pager.cursor_row = 2;
+
+        event_queue.do_history_back();
+
+        assert!(event_queue.path_tree.get_root().path.ends_with("test_dirs"));
+        assert_eq!(1, event_queue.pager.cursor_row);
+
+        event_queue.do_history_forward();
+
+        assert!(event_queue.path_tree.get_root().path.ends_with("dir1"));
+        assert_eq!(2, event_queue.pager.cursor_row);
+    }
+}
@@ -0,0 +1,14 @@
+use crate::controller::EventQueue;
+use std::io::Write;
+
+impl<W: Write> EventQueue<W> {
+    pub fn do_history_forward(&mut self) -> Option<()> {
+        let current_location = self.get_location();
+
+        if let Some(location) = self.history.forward(current_location) {
+            self.jump_to_location(&location);
+        }
+
+        Some(())
+    }
+}
@@ -0,0 +1,185 @@
+use crate::controller::EventQueue;
+use crate::model::config::opener::get_matching_rules;
+use crate::model::config::opener::OpenerRule;
+use crate::model::event::Key;
+use std::io::Write;
+
+/// Selects an entry of the open with menu.
+const MENU_KEYS: &str = "123456789abcdefghijklmnopqrstuvwxyz";
+
+impl<W: Write> EventQueue<W> {
+    /// Lists all opener rules matching the file under the cursor followed by
+    /// the default file action.
+    pub fn do_open_with(&mut self) -> Option<()> {
+        let node_id = self
+            .path_tree
+            .flat_index_to_node_id(self.pager.cursor_row as usize);
+
+        let child_node = self.path_tree.get_path_node(node_id);
+
+        if child_node.is_dir || child_node.is_err {
+            return Some(());
+        }
+
+        let mut rules: Vec<OpenerRule> =
+            get_matching_rules(&self.config.borrow().opener, &child_node.path)
+                .into_iter()
+                .cloned()
+                .collect();
+        let file_action = self.config.borrow().behavior.file_action.clone();
+        if !rules.iter().any(|rule| rule.command == file_action) {
+            rules.push(OpenerRule {
+                name: Some(format!("{} (default)", file_action)),
+                glob: None,
+                extension: None,
+                mime: None,
+                command: file_action,
+                mode: None,
+            });
+        }
+        rules.truncate(MENU_KEYS.len());
+
+        let menu_entries: Vec<(char, String)> = MENU_KEYS
+            .chars()
+            .zip(rules.iter().map(|rule| String::from(rule.get_name())))
+            .collect();
+        let overlay_entries = self.composer.compose_open_with(&menu_entries);
+        self.pager.set_overlay(overlay_entries);
+
+        self.pending_open_with = Some(rules);
+
+        self.update_pager(0);
+        Some(())
+    }
+
+    /// Runs the command of the selected rule, any other key closes the menu.
+    pub fn select_open_with(
+        &mut self,
+        rules: Vec<OpenerRule>,
+        key: Key,
+    ) -> Option<()> {
+        self.pager.clear_overlay();
+        self.update_pager(0);
+
+        let rule = key
+            .get_char()
+            .and_then(|c| MENU_KEYS.find(c))
+            .and_then(|index| rules.get(index));
+
+        match rule {
+            Some(rule) => {
+                let mode = self.get_command_mode(&rule.mode);
+                self.run_command(&rule.command, &mode)
+            }
+            None => Some(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controller;
+
+    fn prepare_event_queue() -> EventQueue<Vec<u8>> {
+        let mut event_queue = controller::prepare_event_queue(|config| {
+            config.behavior.quit_on_action = true;
+            config.opener = vec![
+                OpenerRule {
+                    name: Some(String::from("empty")),
+                    glob: None,
+                    extension: None,
+                    mime: Some(String::from("inode/x-empty")),
+                    command: String::from("echo empty %s"),
+                    mode: None,
+                },
+                OpenerRule {
+                    name: None,
+                    glob: Some(String::from("*.rs")),
+                    extension: None,
+                    mime: None,
+                    command: String::from("echo rust %s"),
+                    mode: None,
+                },
+                OpenerRule {
+                    name: None,
+                    glob: Some(String::from("file?")),
+                    extension: None,
+                    mime: None,
+                    command: String::from("echo file %s"),
+                    mode: None,
+                },
+            ];
+        });
+        event_queue.pager.cursor_row = event_queue
+            .text_entries
+            .iter()
+            .position(|text_entry| text_entry.ends_with("file0"))
+            .unwrap() as i32;
+        event_queue
+    }
+
+    #[test]
+    fn list_matching_rules() {
+        let mut event_queue = prepare_event_queue();
+
+        event_queue.do_open_with();
+
+        assert!(event_queue.pager.has_overlay());
+        let commands: Vec<String> = event_queue
+            .pending_open_with
+            .unwrap()
+            .into_iter()
+            .map(|rule| rule.command)
+            .collect();
+        assert_eq!(vec!["echo empty %s", "echo file %s", "true"], commands);
+    }
+
+    #[test]
+    fn select_entry() {
+        let mut event_queue = prepare_event_queue();
+        event_queue.do_open_with();
+        let rules = event_queue.pending_open_with.take().unwrap();
+
+        let result = event_queue.select_open_with(rules, Key::from("2"));
+
+        assert!(result.is_none(), "quit_on_action quits after the command");
+        assert!(!event_queue.pager.has_overlay());
+        let argv = event_queue.command_to_run_on_exit.unwrap();
+        assert!(argv[2].to_string_lossy().starts_with("echo file '/"));
+    }
+
+    #[test]
+    fn close_menu() {
+        let mut event_queue = prepare_event_queue();
+        event_queue.do_open_with();
+        let rules = event_queue.pending_open_with.take().unwrap();
+
+        let result = event_queue.select_open_with(rules, Key::from("q"));
+
+        assert!(result.is_some());
+        assert!(!event_queue.pager.has_overlay());
+        assert_eq!(None, event_queue.command_to_run_on_exit);
+    }
+
+    #[test]
+    fn file_action_uses_first_matching_rule() {
+        let mut event_queue = prepare_event_queue();
+
+        event_queue.do_file_action();
+
+        let argv = event_queue.command_to_run_on_exit.unwrap();
+        assert!(argv[2].to_string_lossy().starts_with("echo empty '/"));
+    }
+
+    #[test]
+    fn file_action_falls_back() {
+        let mut event_queue = prepare_event_queue();
+        event_queue.config.borrow_mut().opener.clear();
+
+        event_queue.do_file_action();
+
+        let argv = event_queue.command_to_run_on_exit.unwrap();
+        assert_eq!(std::ffi::OsString::from("true"), argv[2]);
+    }
+}
@@ -0,0 +1,91 @@
+use crate::controller::EventQueue;
+use crate::model::path_node::PathNode;
+use crate::model::path_tree::PathTree;
+use std::fs::canonicalize;
+use std::io::Write;
+
+impl<W: Write> EventQueue<W> {
+    pub fn do_parent_dir(&mut self) -> Option<()> {
+        let old_root_path = match canonicalize(&self.path_tree.get_root().path)
+        {
+            Ok(path) => path,
+            Err(_) => return Some(()),
+        };
+
+        let parent_path = match old_root_path.parent() {
+            Some(parent_path) => parent_path.to_path_buf(),
+            None => return Some(()),
+        };
+
+        self.record_location();
+
+        let mut new_path_tree = PathTree::from(PathNode::from(parent_path));
+        new_path_tree.expand_dir(PathTree::ROOT_ID, self.path_node_compare);
+
+        // put the old root with all its expansions in place of its new entry
+        let old_root_id = new_path_tree.find_node_id(&old_root_path);
+
+        if let Some(old_root_id) = old_root_id {
+            new_path_tree.graft(
+                old_root_id,
+                &self.path_tree,
+                PathTree::ROOT_ID,
+            );
+        }
+
+        self.set_path_tree(new_path_tree);
+
+        self.update_pager_to_node_id(old_root_id.unwrap_or(PathTree::ROOT_ID));
+        Some(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controller;
+
+    fn prepare_event_queue(working_dir: &str) -> EventQueue<Vec<u8>> {
+        controller::prepare_event_queue(|config| {
+            config.setup.working_dir = String::from(working_dir);
+        })
+    }
+
+    #[test]
+    fn parent_dir_keeps_old_root_expanded() {
+        let mut event_queue = prepare_event_queue("./tests/test_dirs/dir1");
+        event_queue.path_tree.expand_dir(
+            event_queue.path_tree.get_node_id_at(&[0]),
+            PathNode::compare_dirs_top_simple,
+        );
+
+        event_queue.do_parent_dir();
+
+        let path_tree = &event_queue.path_tree;
+        let dir1 = path_tree.get_path_node(path_tree.get_node_id_at(&[1]));
+        let dir6 = path_tree.get_path_node(path_tree.get_node_id_at(&[1, 0]));
+        assert!(path_tree.get_root().path.ends_with("tests/test_dirs"));
+        assert!(path_tree.get_root().path.is_absolute());
+        assert_eq!("dir1", dir1.display_text);
+        assert!(dir1.is_expanded);
+        assert!(dir6.is_expanded);
+        assert_eq!(23, event_queue.text_entries.len());
+        assert_eq!(1, event_queue.pager.cursor_row);
+    }
+
+    #[test]
+    fn parent_dir_twice() {
+        let mut event_queue = prepare_event_queue("./tests/test_dirs/dir1");
+
+        event_queue.do_parent_dir();
+        event_queue.do_parent_dir();
+
+        let path_tree = &event_queue.path_tree;
+        let test_dirs = path_tree.get_path_node(path_tree.get_node_id_at(&[0]));
+        let dir1 = path_tree.get_path_node(path_tree.get_node_id_at(&[0, 1]));
+        assert!(path_tree.get_root().path.ends_with("tests"));
+        assert_eq!("test_dirs", test_dirs.display_text);
+        assert!(dir1.is_expanded);
+        assert_eq!(0, event_queue.pager.cursor_row);
+    }
+}
@@ -1,110 +1,80 @@
 use crate::controller::EventQueue;
 use crate::model::path_node::PathNode;
-use crate::model::tree_index::TreeIndex;
+use crate::model::path_tree::PathTree;
 use std::io::Write;
 
 impl<W: Write> EventQueue<W> {
     pub fn do_reload(&mut self) -> Option<()> {
         self.reload_openend_dirs();
 
-        self.text_entries =
-            self.composer.compose_path_node(&self.path_node_root);
-
         self.update_pager(0);
 
         Some(())
     }
 
-    fn reload_openend_dirs(&mut self) {
+    pub fn reload_openend_dirs(&mut self) {
         // backup the old path node structure
-        let old_path_node_root = self.path_node_root.clone();
+        let root_path = self.path_tree.get_root().path.clone();
+        let expanded_paths = self.path_tree.get_expanded_paths();
 
         // reset the root path node
-        self.path_node_root =
-            PathNode::from(self.config.setup.working_dir.clone());
-        self.path_node_root
-            .expand_dir(&TreeIndex::from(Vec::new()), self.path_node_compare);
-
-        // restore the old path nodes structure for the root path node
-        self.restore_expansions(&old_path_node_root, &mut TreeIndex::new());
-    }
-
-    fn restore_expansions(
-        &mut self,
-        path_node: &PathNode,
-        tree_index: &mut TreeIndex,
-    ) {
-        for (c, child) in path_node.children.iter().enumerate() {
-            if child.is_expanded {
-                tree_index.index.push(c);
-
-                self.path_node_root
-                    .expand_dir(tree_index, self.path_node_compare);
-                self.restore_expansions(child, tree_index);
-
-                tree_index.index.pop();
+        let mut path_tree = PathTree::from(PathNode::from(root_path.clone()));
+        path_tree.expand_dir(PathTree::ROOT_ID, self.path_node_compare);
+
+        // restore the old expansions, parents come before their children
+        for expanded_path in expanded_paths {
+            let node_id = expanded_path.strip_prefix(&root_path).ok().and_then(
+                |relative_path| {
+                    path_tree.reveal_path(relative_path, self.path_node_compare)
+                },
+            );
+
+            if let Some(node_id) = node_id {
+                if !path_tree.get_path_node(node_id).is_expanded {
+                    path_tree.expand_dir(node_id, self.path_node_compare);
+                }
             }
         }
+
+        self.set_path_tree(path_tree);
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::model::config::Config;
-    use crate::model::path_node::PathNode;
-    use crate::model::tree_index::TreeIndex;
-    use crate::view::composer::Composer;
-    use crate::view::Pager;
-
-    fn get_expanded_path_node(working_dir: &str) -> PathNode {
-        let mut path_node = PathNode::from(working_dir);
-
-        path_node
-            .expand_dir(&TreeIndex::new(), PathNode::compare_dirs_top_simple);
-        path_node.expand_dir(
-            &TreeIndex::from(vec![0]),
-            PathNode::compare_dirs_top_simple,
-        );
-        path_node.expand_dir(
-            &TreeIndex::from(vec![0, 0]),
-            PathNode::compare_dirs_top_simple,
-        );
-        path_node.expand_dir(
-            &TreeIndex::from(vec![1]),
-            PathNode::compare_dirs_top_simple,
-        );
-        path_node.expand_dir(
-            &TreeIndex::from(vec![1, 0]),
-            PathNode::compare_dirs_top_simple,
-        );
-        path_node.expand_dir(
-            &TreeIndex::from(vec![1, 0, 2]),
-            PathNode::compare_dirs_top_simple,
-        );
-        path_node
+    use crate::controller;
+
+    fn get_expanded_path_tree(working_dir: &str) -> PathTree {
+        let mut path_tree = PathTree::from(PathNode::from(working_dir));
+        for child_indices in
+            &[&[][..], &[0], &[0, 0], &[1], &[1, 0], &[1, 0, 2]]
+        {
+            path_tree.expand_dir(
+                path_tree.get_node_id_at(child_indices),
+                PathNode::compare_dirs_top_simple,
+            );
+        }
+        path_tree
     }
 
     fn prepare_event_queue(working_dir: &str) -> EventQueue<Vec<u8>> {
-        let mut config = Config::default();
-        config.setup.working_dir = String::from(working_dir.clone());
-
-        let composer = Composer::from(config.clone());
-        let pager = Pager::new(config.clone(), Vec::new());
-        let path_node = PathNode::from(config.setup.working_dir.clone());
-
-        let mut event_queue =
-            EventQueue::new(config, composer, pager, path_node);
-
-        event_queue.path_node_root = get_expanded_path_node(working_dir);
-
+        let mut event_queue = controller::prepare_event_queue(|config| {
+            config.setup.working_dir = String::from(working_dir);
+        });
+        event_queue.set_path_tree(get_expanded_path_tree(working_dir));
         event_queue
     }
 
     #[test]
     fn do_reload() {
-        // TODO: implement proper test
         let mut event_queue = prepare_event_queue("./tests/test_dirs");
+        let expanded_paths = event_queue.path_tree.get_expanded_paths();
+        let text_entries = event_queue.text_entries.clone();
+
         event_queue.do_reload();
+
+        assert_eq!(expanded_paths, event_queue.path_tree.get_expanded_paths());
+        assert_eq!(text_entries, event_queue.text_entries);
     }
 }
@@ -0,0 +1,24 @@
+use crate::controller::EventQueue;
+use std::io::Write;
+
+impl<W: Write> EventQueue<W> {
+    /// Hands the terminal back to the shell and stops the process, like
+    /// ctrl+z outside of the raw mode. The tree is redrawn by the
+    /// `Event::Continue` of the SIGCONT, e.g. sent by `fg`.
+    pub fn do_suspend(&mut self) -> Option<()> {
+        self.pager.suspend();
+
+        unsafe {
+            libc::kill(libc::getpid(), libc::SIGSTOP);
+        }
+
+        Some(())
+    }
+
+    /// Takes the terminal back and prints the whole frame, as the terminal
+    /// may have been used by others meanwhile.
+    pub fn redraw(&mut self) {
+        self.pager.resume();
+        self.update_pager(0);
+    }
+}
@@ -0,0 +1,106 @@
+use crate::controller::EventQueue;
+use crate::model::config::Config;
+use crate::model::event::Key;
+use std::io::Write;
+
+type GetValue = fn(&Config) -> bool;
+
+/// The boolean options of the toggle menu, selected by their position
+/// starting with 1.
+const TOGGLE_OPTIONS: [(&str, GetValue); 6] = [
+    ("composition.show_indent", |config| {
+        config.composition.show_indent
+    }),
+    ("composition.use_utf8", |config| config.composition.use_utf8),
+    ("behavior.mouse", |config| config.behavior.mouse),
+    ("behavior.use_shell", |config| config.behavior.use_shell),
+    ("behavior.quit_on_action", |config| {
+        config.behavior.quit_on_action
+    }),
+    ("debug.enabled", |config| config.debug.enabled),
+];
+
+impl<W: Write> EventQueue<W> {
+    /// Lists the options of the toggle menu with their current values.
+    pub fn do_toggle_option(&mut self) -> Option<()> {
+        let menu_entries: Vec<(char, &str, bool)> = {
+            let config = self.config.borrow();
+            TOGGLE_OPTIONS
+                .iter()
+                .enumerate()
+                .map(|(index, (key, get_value))| {
+                    let menu_key =
+                        std::char::from_digit(index as u32 + 1, 10).unwrap();
+                    (menu_key, *key, get_value(&config))
+                })
+                .collect()
+        };
+        let overlay_entries =
+            self.composer.compose_toggle_options(&menu_entries);
+        self.pager.set_overlay(overlay_entries);
+
+        self.pending_toggle_option = true;
+
+        self.update_pager(0);
+        Some(())
+    }
+
+    /// Toggles the selected option, any other key closes the menu.
+    pub fn select_toggle_option(&mut self, key: Key) -> Option<()> {
+        self.pager.clear_overlay();
+
+        let toggle_option = key
+            .get_char()
+            .and_then(|c| c.to_digit(10))
+            .and_then(|digit| {
+                TOGGLE_OPTIONS.get((digit as usize).checked_sub(1)?)
+            });
+
+        match toggle_option {
+            Some((key, get_value)) => {
+                let value = (!get_value(&self.config.borrow())).to_string();
+                if self.set_option(key, &value).is_ok() {
+                    self.set_status(format!("{}={}", key, value));
+                }
+            }
+            None => self.update_pager(0),
+        }
+
+        Some(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controller::prepare_event_queue;
+
+    #[test]
+    fn toggle() {
+        let mut event_queue = prepare_event_queue(|_| {});
+
+        event_queue.match_key_event(Key::from("t"));
+        assert!(event_queue.pager.has_overlay());
+
+        event_queue.match_key_event(Key::from("2"));
+        assert!(!event_queue.pager.has_overlay());
+        assert!(!event_queue.config.borrow().composition.use_utf8);
+        assert_eq!("> dir0/", event_queue.text_entries[0]);
+        assert_eq!(
+            "composition.use_utf8=false",
+            event_queue.pager.get_status()
+        );
+    }
+
+    #[test]
+    fn close() {
+        let mut event_queue = prepare_event_queue(|_| {});
+
+        event_queue.match_key_event(Key::from("t"));
+        let result = event_queue.match_key_event(Key::from("q"));
+
+        assert!(result.is_some(), "closing the menu must not quit");
+        assert!(!event_queue.pager.has_overlay());
+        assert!(event_queue.config.borrow().composition.use_utf8);
+    }
+}
@@ -0,0 +1,153 @@
+use crate::controller::EventQueue;
+use crate::model::history::Location;
+use crate::model::path_node::PathNode;
+use crate::model::path_tree::NodeId;
+use crate::model::path_tree::PathTree;
+use crate::utils::canonicalize_or_keep;
+use std::io::Write;
+use std::path::PathBuf;
+
+impl<W: Write> EventQueue<W> {
+    pub fn get_location(&self) -> Location {
+        let node_id = self
+            .path_tree
+            .flat_index_to_node_id(self.pager.cursor_row as usize);
+
+        let cursor_path = &self.path_tree.get_path_node(node_id).path;
+
+        Location {
+            root: canonicalize_or_keep(&self.path_tree.get_root().path),
+            cursor: canonicalize_or_keep(cursor_path),
+        }
+    }
+
+    /// Remembers the current location in the history, call this before
+    /// jumping somewhere else.
+    pub fn record_location(&mut self) {
+        let location = self.get_location();
+        self.history.push(location);
+    }
+
+    pub fn jump_to_location(&mut self, location: &Location) {
+        if !location.root.is_dir() {
+            return;
+        }
+
+        if canonicalize_or_keep(&self.path_tree.get_root().path)
+            != location.root
+        {
+            let mut path_tree =
+                PathTree::from(PathNode::from(location.root.clone()));
+            path_tree.expand_dir(PathTree::ROOT_ID, self.path_node_compare);
+            self.set_path_tree(path_tree);
+        }
+
+        let node_id = location
+            .cursor
+            .strip_prefix(&location.root)
+            .ok()
+            .and_then(|relative_path| {
+                self.path_tree
+                    .reveal_path(relative_path, self.path_node_compare)
+            })
+            .unwrap_or(PathTree::ROOT_ID);
+
+        self.update_text_entries();
+
+        self.update_pager_to_node_id(node_id);
+    }
+
+    /// Reveals the path when it is inside the current tree, otherwise the
+    /// tree is re-rooted at the directory or at the parent of the file.
+    pub fn reveal(&mut self, path: PathBuf) {
+        let current_location = self.get_location();
+
+        let root = if path.starts_with(&current_location.root) {
+            current_location.root
+        } else if path.is_dir() {
+            path.clone()
+        } else {
+            match path.parent() {
+                Some(parent_path) => parent_path.to_path_buf(),
+                None => return,
+            }
+        };
+
+        self.record_location();
+        self.jump_to_location(&Location { root, cursor: path });
+    }
+
+    /// Moves the cursor to the entry with the given node id, the root's node
+    /// id moves it to the top.
+    pub fn update_pager_to_node_id(&mut self, node_id: NodeId) {
+        let flat_index =
+            self.path_tree.node_id_to_flat_index(node_id).unwrap_or(0) as i32;
+
+        self.update_pager(flat_index - self.pager.cursor_row);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controller::prepare_event_queue;
+    use std::fs::canonicalize;
+
+    #[test]
+    fn get_location() {
+        let mut event_queue = prepare_event_queue(|_| {});
+        event_queue.pager.cursor_row = 2;
+
+        let location = event_queue.get_location();
+
+        assert!(location.root.is_absolute());
+        assert!(location.root.ends_with("tests/test_dirs"));
+        assert_eq!(location.root.join("dir2"), location.cursor);
+    }
+
+    #[test]
+    fn jump_to_location_in_same_root() {
+        let mut event_queue = prepare_event_queue(|_| {});
+        let root = canonicalize("./tests/test_dirs").unwrap();
+
+        event_queue.jump_to_location(&Location {
+            cursor: root.join("dir1/dir6/file9"),
+            root,
+        });
+
+        assert_eq!(8, event_queue.pager.cursor_row);
+        assert_eq!(23, event_queue.text_entries.len());
+        assert_eq!(
+            "./tests/test_dirs",
+            event_queue.path_tree.get_root().display_text
+        );
+    }
+
+    #[test]
+    fn jump_to_location_in_other_root() {
+        let mut event_queue = prepare_event_queue(|_| {});
+        let root = canonicalize("./tests/test_dirs/dir1").unwrap();
+
+        event_queue.jump_to_location(&Location {
+            cursor: root.join("dir7/file19"),
+            root,
+        });
+
+        assert!(event_queue.path_tree.get_root().path.ends_with("dir1"));
+        assert_eq!(2, event_queue.pager.cursor_row);
+    }
+
+    #[test]
+    fn jump_to_missing_location() {
+        let mut event_queue = prepare_event_queue(|_| {});
+        event_queue.pager.cursor_row = 3;
+
+        event_queue.jump_to_location(&Location {
+            root: PathBuf::from("/nonsense"),
+            cursor: PathBuf::from("/nonsense/file"),
+        });
+
+        assert!(event_queue.path_tree.get_root().path.ends_with("test_dirs"));
+        assert_eq!(3, event_queue.pager.cursor_row);
+    }
+}
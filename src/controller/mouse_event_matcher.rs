@@ -0,0 +1,252 @@
+use crate::controller::EventQueue;
+use std::io::Write;
+use std::time::Duration;
+use std::time::Instant;
+use termion::event::MouseButton;
+use termion::event::MouseEvent;
+
+impl<W: Write> EventQueue<W> {
+    pub fn match_mouse_event(&mut self, mouse_event: MouseEvent) -> Option<()> {
+        if !self.config.borrow().behavior.mouse {
+            return Some(());
+        }
+
+        if self.pager.has_overlay() {
+            self.pager.clear_overlay();
+            self.update_pager(0);
+            return Some(());
+        }
+
+        self.pending_mark_action = None;
+
+        let scroll_lines = self.config.borrow().behavior.mouse_scroll_lines;
+
+        match mouse_event {
+            MouseEvent::Press(MouseButton::Left, column, row) => {
+                self.do_click(column as usize, i32::from(row))
+            }
+            MouseEvent::Press(MouseButton::WheelUp, _, _) => {
+                self.do_scroll(-scroll_lines)
+            }
+            MouseEvent::Press(MouseButton::WheelDown, _, _) => {
+                self.do_scroll(scroll_lines)
+            }
+            _ => Some(()),
+        }
+    }
+
+    /// Moves the cursor to the clicked entry. A click on the expand marker or
+    /// a double click on a directory toggles it, a double click on a file
+    /// performs the file action.
+    fn do_click(&mut self, column: usize, row: i32) -> Option<()> {
+        let flat_index = match self
+            .pager
+            .get_text_entry_index(row, self.text_entries.len() as i32)
+        {
+            Some(flat_index) => flat_index as usize,
+            None => return Some(()),
+        };
+
+        let is_double_click = self.is_double_click(flat_index);
+
+        self.update_pager(flat_index as i32 - self.pager.cursor_row);
+
+        let node_id = self.path_tree.flat_index_to_node_id(flat_index);
+        let path_node = self.path_tree.get_path_node(node_id);
+
+        if self.composer.is_expand_marker_column(path_node, column)
+            || (is_double_click && path_node.is_dir)
+        {
+            if path_node.is_expanded || path_node.is_loading {
+                self.do_collapse_dir()
+            } else {
+                self.do_expand_dir()
+            }
+        } else if is_double_click {
+            self.do_file_action()
+        } else {
+            Some(())
+        }
+    }
+
+    /// Remembers the click, a third click starts a new double click.
+    fn is_double_click(&mut self, flat_index: usize) -> bool {
+        let now = Instant::now();
+        let interval = Duration::from_millis(
+            self.config.borrow().behavior.mouse_double_click_ms.max(0) as u64,
+        );
+
+        let is_double_click = match self.last_click {
+            Some((last_instant, last_flat_index)) => {
+                last_flat_index == flat_index
+                    && now.duration_since(last_instant) <= interval
+            }
+            None => false,
+        };
+
+        self.last_click = if is_double_click {
+            None
+        } else {
+            Some((now, flat_index))
+        };
+
+        is_double_click
+    }
+
+    /// Moves the cursor without wrapping around at the top or bottom.
+    fn do_scroll(&mut self, lines: i32) -> Option<()> {
+        let cursor_delta = self.get_clamped_cursor_delta(lines);
+        self.update_pager(cursor_delta);
+        Some(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controller;
+    use crate::model::config::Config;
+    use crate::model::path_node::PathNode;
+    use crate::model::path_tree::PathTree;
+
+    fn prepare_event_queue<F>(configure: F) -> EventQueue<Vec<u8>>
+    where
+        F: FnOnce(&mut Config),
+    {
+        let mut event_queue = controller::prepare_event_queue(|config| {
+            config.behavior.mouse = true;
+            configure(config);
+        });
+        event_queue.pager.set_terminal_size(80, 24);
+
+        let mut path_tree = PathTree::from(PathNode::from("./tests/test_dirs"));
+        for child_indices in
+            &[&[][..], &[0], &[0, 0], &[1], &[1, 0], &[1, 0, 2]]
+        {
+            path_tree.expand_dir(
+                path_tree.get_node_id_at(child_indices),
+                PathNode::compare_dirs_top_simple,
+            );
+        }
+        event_queue.set_path_tree(path_tree);
+        event_queue.update_pager(0);
+
+        event_queue
+    }
+
+    fn is_dir1_expanded(event_queue: &EventQueue<Vec<u8>>) -> bool {
+        let node_id = event_queue.path_tree.get_node_id_at(&[1]);
+        event_queue.path_tree.get_path_node(node_id).is_expanded
+    }
+
+    #[test]
+    fn click_moves_cursor() {
+        let mut event_queue = prepare_event_queue(|_| {});
+
+        event_queue.match_mouse_event(MouseEvent::Press(
+            MouseButton::Left,
+            10,
+            9,
+        ));
+
+        assert_eq!(6, event_queue.pager.cursor_row);
+        assert!(is_dir1_expanded(&event_queue));
+    }
+
+    #[test]
+    fn click_outside_entries() {
+        let mut event_queue = prepare_event_queue(|_| {});
+
+        event_queue.match_mouse_event(MouseEvent::Press(
+            MouseButton::Left,
+            10,
+            1,
+        ));
+
+        assert_eq!(0, event_queue.pager.cursor_row);
+    }
+
+    #[test]
+    fn click_on_expand_marker() {
+        let mut event_queue = prepare_event_queue(|_| {});
+
+        event_queue.match_mouse_event(MouseEvent::Press(
+            MouseButton::Left,
+            1,
+            9,
+        ));
+
+        assert_eq!(6, event_queue.pager.cursor_row);
+        assert!(!is_dir1_expanded(&event_queue));
+        assert_eq!(
+            event_queue
+                .composer
+                .compose_path_tree(&event_queue.path_tree),
+            event_queue.text_entries
+        );
+    }
+
+    #[test]
+    fn double_click_on_dir() {
+        let mut event_queue = prepare_event_queue(|_| {});
+
+        for _ in 0..2 {
+            event_queue.match_mouse_event(MouseEvent::Press(
+                MouseButton::Left,
+                10,
+                9,
+            ));
+        }
+
+        assert!(!is_dir1_expanded(&event_queue));
+    }
+
+    #[test]
+    fn clicks_on_different_entries() {
+        let mut event_queue = prepare_event_queue(|_| {});
+
+        for row in &[8, 9] {
+            event_queue.match_mouse_event(MouseEvent::Press(
+                MouseButton::Left,
+                10,
+                *row,
+            ));
+        }
+
+        assert_eq!(6, event_queue.pager.cursor_row);
+        assert!(is_dir1_expanded(&event_queue));
+    }
+
+    #[test]
+    fn wheel() {
+        let mut event_queue = prepare_event_queue(|_| {});
+
+        event_queue.match_mouse_event(MouseEvent::Press(
+            MouseButton::WheelUp,
+            1,
+            1,
+        ));
+        assert_eq!(0, event_queue.pager.cursor_row);
+
+        event_queue.match_mouse_event(MouseEvent::Press(
+            MouseButton::WheelDown,
+            1,
+            1,
+        ));
+        assert_eq!(3, event_queue.pager.cursor_row);
+    }
+
+    #[test]
+    fn disabled() {
+        let mut event_queue =
+            prepare_event_queue(|config| config.behavior.mouse = false);
+
+        event_queue.match_mouse_event(MouseEvent::Press(
+            MouseButton::Left,
+            10,
+            9,
+        ));
+
+        assert_eq!(0, event_queue.pager.cursor_row);
+    }
+}
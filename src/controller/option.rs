@@ -0,0 +1,163 @@
+use crate::controller::EventQueue;
+use crate::model::config::Config;
+use crate::model::config::OptionError;
+use crate::model::path_node::PathNode;
+use std::io::Write;
+
+impl<W: Write> EventQueue<W> {
+    /// Sets an option by its command line key and applies it at once.
+    pub fn set_option(
+        &mut self,
+        key: &str,
+        value: &str,
+    ) -> Result<(), OptionError> {
+        self.config.borrow_mut().set_option(key, value)?;
+        self.apply_option(key);
+        Ok(())
+    }
+
+    /// Reads the config file again after it changed on disk. The tree stays
+    /// as it is, a broken file is reported and the running config is kept.
+    pub fn reload_config(&mut self) {
+        match Config::load() {
+            Ok((config, warnings)) => {
+                self.replace_config(config);
+                let status = match warnings.first() {
+                    Some(warning) => format!("config reloaded, {}", warning),
+                    None => String::from("config reloaded"),
+                };
+                self.set_status(status);
+            }
+            Err(errors) => self.set_status(errors.join(", ")),
+        }
+    }
+
+    fn replace_config(&mut self, config: Config) {
+        let old_config = self.config.replace(config);
+        let new_config = self.config.borrow().clone();
+
+        if old_config.behavior.path_node_sort
+            != new_config.behavior.path_node_sort
+        {
+            self.apply_sort();
+        } else {
+            self.recompose();
+        }
+        self.rebind();
+        if old_config.behavior.mouse != new_config.behavior.mouse {
+            self.pager.update_mouse();
+        }
+
+        self.update_pager(0);
+    }
+
+    /// Re-composes and redraws whatever depends on the changed option.
+    fn apply_option(&mut self, key: &str) {
+        if key == "behavior.path_node_sort" {
+            self.apply_sort();
+        } else if key.starts_with("composition.") {
+            self.recompose();
+        } else if key.starts_with("keybinding.") || key.starts_with("keymap.") {
+            self.rebind();
+        } else if key == "behavior.mouse" {
+            self.pager.update_mouse();
+        }
+
+        self.update_pager(0);
+    }
+
+    /// Sorts the tree again, keeping the entry under the cursor.
+    fn apply_sort(&mut self) {
+        let location = self.get_location();
+        self.path_node_compare =
+            PathNode::get_path_node_compare(&self.config.borrow());
+        self.reload_openend_dirs();
+        self.jump_to_location(&location);
+    }
+
+    fn recompose(&mut self) {
+        self.text_entries = self.composer.compose_path_tree(&self.path_tree);
+    }
+
+    fn rebind(&mut self) {
+        self.key_bindings = self.config.borrow().get_key_bindings();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controller::prepare_event_queue;
+    use crate::model::config::keybinding::Keys;
+    use crate::model::event::Key;
+
+    #[test]
+    fn composition() {
+        let mut event_queue = prepare_event_queue(|_| {});
+        event_queue.pager.cursor_row = 1;
+        event_queue.do_expand_all();
+        event_queue.wait_for_dir_listings();
+        assert_eq!("  ▼ dir6/", event_queue.text_entries[2]);
+
+        event_queue.set_option("composition.indent", "4").unwrap();
+        assert_eq!("    ▼ dir6/", event_queue.text_entries[2]);
+
+        event_queue
+            .set_option("composition.use_utf8", "false")
+            .unwrap();
+        assert_eq!("    v dir6/", event_queue.text_entries[2]);
+
+        assert_eq!(
+            event_queue
+                .composer
+                .compose_path_tree(&event_queue.path_tree),
+            event_queue.text_entries
+        );
+    }
+
+    #[test]
+    fn keybinding() {
+        let mut event_queue = prepare_event_queue(|_| {});
+
+        event_queue.set_option("keybinding.quit", "Q").unwrap();
+
+        assert!(event_queue.match_key_event(Key::from("q")).is_some());
+        assert!(event_queue.match_key_event(Key::from("Q")).is_none());
+    }
+
+    #[test]
+    fn replace_config() {
+        let mut event_queue = prepare_event_queue(|_| {});
+        event_queue.pager.cursor_row = 1;
+        event_queue.do_expand_all();
+        event_queue.wait_for_dir_listings();
+        let expanded_paths = event_queue.path_tree.get_expanded_paths();
+
+        let mut config = event_queue.config.borrow().clone();
+        config.behavior.path_node_sort = String::from("dirs_bot_simple");
+        config.composition.use_utf8 = false;
+        config.keybinding.quit = Keys::from("Q");
+        event_queue.replace_config(config);
+
+        assert!(event_queue.text_entries[0].ends_with("file0"));
+        assert!(event_queue.get_location().cursor.ends_with("dir1"));
+        assert_eq!(expanded_paths, event_queue.path_tree.get_expanded_paths());
+        assert_eq!(
+            event_queue
+                .composer
+                .compose_path_tree(&event_queue.path_tree),
+            event_queue.text_entries
+        );
+        assert!(event_queue.match_key_event(Key::from("Q")).is_none());
+    }
+
+    #[test]
+    fn unknown() {
+        let mut event_queue = prepare_event_queue(|_| {});
+
+        assert_eq!(
+            Err(OptionError::Unknown),
+            event_queue.set_option("composition.color", "1")
+        );
+    }
+}
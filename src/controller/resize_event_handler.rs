@@ -1,6 +1,6 @@
 use crate::model::event::Event;
-use std::sync::mpsc::SyncSender;
 use std::sync::mpsc;
+use std::sync::mpsc::SyncSender;
 
 pub struct ResizeEventHandler {}
 
@@ -0,0 +1,158 @@
+use crate::controller::EventQueue;
+use crate::model::path_tree::PathTree;
+use crate::model::session::Session;
+use crate::utils::canonicalize_or_keep;
+use log::{info, warn};
+use std::fs::canonicalize;
+use std::io::Write;
+
+impl<W: Write> EventQueue<W> {
+    pub fn get_session(&self) -> Session {
+        Session {
+            cursor: self.get_location().cursor,
+            expanded: self
+                .path_tree
+                .get_expanded_paths()
+                .iter()
+                .map(|expanded_path| canonicalize_or_keep(expanded_path))
+                .collect(),
+        }
+    }
+
+    pub fn save_session(&self) {
+        if !self.config.borrow().behavior.restore_session {
+            return;
+        }
+
+        let config_dir = match self.config.borrow().config_dir.clone() {
+            Some(config_dir) => config_dir,
+            None => return,
+        };
+
+        let working_dir =
+            match canonicalize(&self.config.borrow().setup.working_dir) {
+                Ok(working_dir) => working_dir,
+                Err(err) => {
+                    warn!("could not save the session: {}", err);
+                    return;
+                }
+            };
+
+        info!("saving session for {:?}", working_dir);
+
+        if let Err(err) = self.get_session().save(&config_dir, &working_dir) {
+            warn!("could not save the session: {}", err);
+        }
+    }
+
+    pub fn restore_session(&mut self) {
+        if !self.config.borrow().behavior.restore_session {
+            return;
+        }
+
+        let config_dir = match self.config.borrow().config_dir.clone() {
+            Some(config_dir) => config_dir,
+            None => return,
+        };
+
+        let working_dir =
+            match canonicalize(&self.config.borrow().setup.working_dir) {
+                Ok(working_dir) => working_dir,
+                Err(_) => return,
+            };
+
+        match Session::load(&config_dir, &working_dir) {
+            Ok(Some(session)) => {
+                info!("restoring session for {:?}", working_dir);
+                self.apply_session(&session);
+            }
+            Ok(None) => {}
+            Err(err) => warn!("could not restore the session: {}", err),
+        }
+    }
+
+    /// Expands the directories of the session and moves the cursor, paths
+    /// outside of the tree or which do not exist anymore are skipped.
+    pub fn apply_session(&mut self, session: &Session) {
+        let root = canonicalize_or_keep(&self.path_tree.get_root().path);
+
+        for expanded_path in &session.expanded {
+            let node_id = expanded_path.strip_prefix(&root).ok().and_then(
+                |relative_path| {
+                    self.path_tree
+                        .reveal_path(relative_path, self.path_node_compare)
+                },
+            );
+
+            if let Some(node_id) = node_id {
+                if !self.path_tree.get_path_node(node_id).is_expanded {
+                    self.path_tree.expand_dir(node_id, self.path_node_compare);
+                }
+            }
+        }
+
+        let node_id = session
+            .cursor
+            .strip_prefix(&root)
+            .ok()
+            .and_then(|relative_path| {
+                self.path_tree
+                    .reveal_path(relative_path, self.path_node_compare)
+            })
+            .unwrap_or(PathTree::ROOT_ID);
+
+        self.update_text_entries();
+
+        self.update_pager_to_node_id(node_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controller::prepare_event_queue;
+    use crate::model::path_node::PathNode;
+    use std::path::PathBuf;
+
+    #[test]
+    fn get_and_apply_session() {
+        let session = {
+            let mut event_queue = prepare_event_queue(|_| {});
+            event_queue.path_tree.reveal_path(
+                std::path::Path::new("dir1/dir6/dir9/file12"),
+                PathNode::compare_dirs_top_simple,
+            );
+            event_queue.update_text_entries();
+            event_queue.pager.cursor_row = 8;
+            event_queue.get_session()
+        };
+
+        assert_eq!(3, session.expanded.len());
+        assert!(session.cursor.ends_with("dir1/dir6/dir9/file12"));
+
+        let mut event_queue = prepare_event_queue(|_| {});
+        event_queue.apply_session(&session);
+
+        assert_eq!(session, event_queue.get_session());
+        assert_eq!(8, event_queue.pager.cursor_row);
+    }
+
+    #[test]
+    fn apply_session_skips_missing_paths() {
+        let mut event_queue = prepare_event_queue(|_| {});
+        let root = canonicalize("./tests/test_dirs").unwrap();
+
+        event_queue.apply_session(&Session {
+            cursor: root.join("nonsense/file"),
+            expanded: vec![
+                root.join("nonsense"),
+                root.join("dir2"),
+                root.join("file0"),
+                PathBuf::from("/"),
+            ],
+        });
+
+        assert_eq!(vec![root.join("dir2")], event_queue.get_session().expanded);
+        assert_eq!(0, event_queue.pager.cursor_row);
+    }
+}
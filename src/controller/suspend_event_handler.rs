@@ -0,0 +1,38 @@
+use crate::model::event::Event;
+use signal_hook::iterator::Signals;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::mpsc::SyncSender;
+use std::sync::Arc;
+
+pub struct SuspendEventHandler {}
+
+impl SuspendEventHandler {
+    /// Turns SIGTSTP into `Event::Suspend` and SIGCONT into
+    /// `Event::Continue`. SIGTSTP is ignored while a command runs in the
+    /// foreground, as it stops the command which is handled when waiting for
+    /// it.
+    pub fn handle(
+        sync_sender: SyncSender<Event>,
+        is_foreground: Arc<AtomicBool>,
+    ) {
+        let signals = match Signals::new([libc::SIGTSTP, signal_hook::SIGCONT])
+        {
+            Ok(signals) => signals,
+            Err(_) => return,
+        };
+
+        for signal in signals.forever() {
+            let event = match signal {
+                libc::SIGTSTP if !is_foreground.load(Ordering::SeqCst) => {
+                    Event::Suspend
+                }
+                signal_hook::SIGCONT => Event::Continue,
+                _ => continue,
+            };
+            if sync_sender.send(event).is_err() {
+                break;
+            }
+        }
+    }
+}
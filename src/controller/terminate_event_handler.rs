@@ -0,0 +1,37 @@
+use crate::model::event::Event;
+use signal_hook::iterator::Signals;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::mpsc::SyncSender;
+use std::sync::Arc;
+
+pub struct TerminateEventHandler {}
+
+impl TerminateEventHandler {
+    /// Waits for a terminating signal in this thread, as sending events from
+    /// within a signal handler is not safe. SIGINT is ignored while a command
+    /// runs in the foreground, as ctrl+c is meant for the command.
+    pub fn handle(
+        sync_sender: SyncSender<Event>,
+        is_foreground: Arc<AtomicBool>,
+    ) {
+        let signals = match Signals::new([
+            signal_hook::SIGHUP,
+            signal_hook::SIGINT,
+            signal_hook::SIGTERM,
+        ]) {
+            Ok(signals) => signals,
+            Err(_) => return,
+        };
+
+        for signal in signals.forever() {
+            if signal == signal_hook::SIGINT
+                && is_foreground.load(Ordering::SeqCst)
+            {
+                continue;
+            }
+            let _ = sync_sender.send(Event::Terminate);
+            break;
+        }
+    }
+}
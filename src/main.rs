@@ -2,15 +2,18 @@ extern crate chrono;
 extern crate termion;
 extern crate toml;
 use controller::EventQueue;
+use exec::execvp;
 use log::info;
 use model::config::Config;
-use model::path_node::PathNode;
+use model::path_tree::PathTree;
 use std::io::stdout;
 use termion::raw::IntoRawMode;
 use utils::setup_logger;
+use utils::setup_panic_hook;
 use view::composer::Composer;
+use view::terminal::save_raw_attributes;
+use view::terminal::save_terminal_attributes;
 use view::Pager;
-use exec::execvp;
 
 mod controller;
 mod model;
@@ -18,27 +21,30 @@ mod utils;
 mod view;
 
 fn main() {
-
     let command_to_run_on_exit = {
         let _ = setup_logger();
+        setup_panic_hook();
 
         let config = Config::new();
 
         let composer = Composer::from(config.clone());
 
-        let pager = Pager::new(config.clone(), stdout().into_raw_mode().unwrap());
+        save_terminal_attributes();
+        let raw_stdout = stdout().into_raw_mode().unwrap();
+        save_raw_attributes();
+        let pager = Pager::new(config.clone(), raw_stdout);
 
-        let path_node_root = PathNode::new_expanded(config.clone());
+        let path_tree = PathTree::new_expanded(config.clone());
 
         let mut event_queue =
-            EventQueue::new(config, composer, pager, path_node_root);
+            EventQueue::new(config, composer, pager, path_tree);
 
         event_queue.handle_messages()
     };
 
-    if let Some(cmd) = command_to_run_on_exit {
-        let _ = execvp("bash", &["bash", "-c", &cmd]);
+    if let Some(argv) = command_to_run_on_exit {
+        let _ = execvp(&argv[0], &argv);
     };
- 
+
     info!("clean exit");
 }
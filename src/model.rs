@@ -1,14 +1,20 @@
+pub mod bookmarks;
+pub mod command_template;
 pub mod compare_functions;
 pub mod config;
 pub mod event;
+pub mod history;
+pub mod mime_type;
 pub mod path_node;
-pub mod tree_index;
+pub mod path_tree;
+pub mod prompt;
+pub mod session;
 
 #[cfg(test)]
 mod tests {
     use crate::model::config::Config;
     use crate::model::path_node::PathNode;
-    use crate::model::tree_index::TreeIndex;
+    use crate::model::path_tree::PathTree;
     use crate::view::composer::Composer;
 
     #[test]
@@ -17,68 +23,81 @@ mod tests {
         config.setup.working_dir = String::from("./tests/test_dirs");
 
         let composer = Composer::from(config.clone());
-        let mut path_node = PathNode::from(config.setup.working_dir);
+        let mut path_tree =
+            PathTree::from(PathNode::from(config.setup.working_dir));
         let path_node_compare = PathNode::compare_dirs_top_simple;
-        assert_eq!(0, composer.compose_path_node(&path_node).len());
+        assert_eq!(0, composer.compose_path_tree(&path_tree).len());
 
         // expand_dir
-        path_node.expand_dir(&TreeIndex::from(Vec::new()), path_node_compare);
+        path_tree.expand_dir(PathTree::ROOT_ID, path_node_compare);
         assert_eq!(
             13,
-            composer.compose_path_node(&path_node).len(),
+            composer.compose_path_tree(&path_tree).len(),
             "expanding the root directory"
         );
 
-        path_node.expand_dir(&TreeIndex::from(vec![3]), path_node_compare);
+        path_tree.expand_dir(path_tree.get_node_id_at(&[3]), path_node_compare);
         assert_eq!(
             13,
-            composer.compose_path_node(&path_node).len(),
+            composer.compose_path_tree(&path_tree).len(),
             "expanding a file does nothing"
         );
 
-        path_node.expand_dir(&TreeIndex::from(vec![1]), path_node_compare);
-        assert_eq!(17, composer.compose_path_node(&path_node).len());
+        path_tree.expand_dir(path_tree.get_node_id_at(&[1]), path_node_compare);
+        assert_eq!(17, composer.compose_path_tree(&path_tree).len());
 
-        path_node.expand_dir(&TreeIndex::from(vec![1, 0]), path_node_compare);
-        assert_eq!(23, composer.compose_path_node(&path_node).len());
+        path_tree
+            .expand_dir(path_tree.get_node_id_at(&[1, 0]), path_node_compare);
+        assert_eq!(23, composer.compose_path_tree(&path_tree).len());
 
-        path_node
-            .expand_dir(&TreeIndex::from(vec![1, 0, 2]), path_node_compare);
-        assert_eq!(26, composer.compose_path_node(&path_node).len());
-
-        path_node
-            .expand_dir(&TreeIndex::from(vec![1, 0, 2, 1]), path_node_compare);
-        assert_eq!(29, composer.compose_path_node(&path_node).len());
-
-        // tree_index_to_flat_index
-        let flat_index = TreeIndex::from(vec![7, 2, 4, 0, 0]).to_flat_index();
-        assert_eq!(17, flat_index);
-
-        // flat_index_to_tree_index
-        let tree_index = path_node.flat_index_to_tree_index(9);
-        assert_eq!(vec![1, 0, 2, 1, 1], tree_index.index);
+        path_tree.expand_dir(
+            path_tree.get_node_id_at(&[1, 0, 2]),
+            path_node_compare,
+        );
+        assert_eq!(26, composer.compose_path_tree(&path_tree).len());
 
-        let tree_index = path_node.flat_index_to_tree_index(10);
-        assert_eq!(vec![1, 0, 2, 1, 2], tree_index.index);
+        path_tree.expand_dir(
+            path_tree.get_node_id_at(&[1, 0, 2, 1]),
+            path_node_compare,
+        );
+        assert_eq!(29, composer.compose_path_tree(&path_tree).len());
 
-        let tree_index = path_node.flat_index_to_tree_index(11);
-        assert_eq!(vec![1, 0, 2, 2], tree_index.index);
+        // flat_index_to_node_id
+        assert_eq!(
+            path_tree.get_node_id_at(&[1, 0, 2, 1, 1]),
+            path_tree.flat_index_to_node_id(9)
+        );
+        assert_eq!(
+            path_tree.get_node_id_at(&[1, 0, 2, 1, 2]),
+            path_tree.flat_index_to_node_id(10)
+        );
+        assert_eq!(
+            path_tree.get_node_id_at(&[1, 0, 2, 2]),
+            path_tree.flat_index_to_node_id(11)
+        );
+        assert_eq!(
+            path_tree.get_node_id_at(&[1, 1]),
+            path_tree.flat_index_to_node_id(15)
+        );
 
-        let tree_index = path_node.flat_index_to_tree_index(15);
-        assert_eq!(vec![1, 1], tree_index.index);
+        // node_id_to_flat_index
+        assert_eq!(
+            Some(18),
+            path_tree.node_id_to_flat_index(path_tree.get_node_id_at(&[2]))
+        );
 
         // collapse_dir
-        path_node.collapse_dir(&TreeIndex::from(vec![1, 0, 2, 1]));
+        path_tree.collapse_dir(path_tree.get_node_id_at(&[1, 0, 2, 1]));
         assert_eq!(
             26,
-            composer.compose_path_node(&path_node).len(),
+            composer.compose_path_tree(&path_tree).len(),
             "reducing the last opened dir"
         );
 
-        path_node.collapse_dir(&TreeIndex::from(vec![1, 0]));
+        path_tree.collapse_dir(path_tree.get_node_id_at(&[1, 0]));
         assert_eq!(
             17,
-            composer.compose_path_node(&path_node).len(),
+            composer.compose_path_tree(&path_tree).len(),
             "reducing lots of sub dirs"
         );
     }
@@ -0,0 +1,232 @@
+use crate::model::config::Config;
+use crate::utils::read_file;
+use crate::utils::write_file;
+use log::{info, warn};
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::path::PathBuf;
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+struct BookmarksFile {
+    #[serde(default)]
+    bookmarks: BTreeMap<String, PathBuf>,
+}
+
+/// Directories and files stored under a single character mark.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bookmarks {
+    marks: BTreeMap<char, PathBuf>,
+    /// None when the bookmarks are not persisted.
+    file_path: Option<PathBuf>,
+}
+
+impl Bookmarks {
+    pub fn new(config: &Config, config_dir: Option<&Path>) -> Self {
+        info!("initializing bookmarks");
+
+        let mut bookmarks = Self {
+            marks: BTreeMap::new(),
+            file_path: config_dir
+                .filter(|_| config.behavior.persist_bookmarks)
+                .map(|config_dir| config_dir.join("bookmarks.toml")),
+        };
+
+        if let Some(file_path) = &bookmarks.file_path {
+            match Self::read_bookmarks_file(file_path) {
+                Ok(bookmarks_file) => {
+                    bookmarks.marks = bookmarks_file
+                        .bookmarks
+                        .into_iter()
+                        .filter_map(|(mark, path)| {
+                            Self::parse_mark(&mark).map(|mark| (mark, path))
+                        })
+                        .collect();
+                }
+                Err(err) => {
+                    warn!("could not read the bookmarks file: {}", err)
+                }
+            }
+        }
+
+        bookmarks
+    }
+
+    pub fn is_valid_mark(mark: char) -> bool {
+        !mark.is_whitespace() && !mark.is_control()
+    }
+
+    fn parse_mark(mark: &str) -> Option<char> {
+        let mut chars = mark.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) if Self::is_valid_mark(c) => Some(c),
+            _ => None,
+        }
+    }
+
+    pub fn get(&self, mark: char) -> Option<&PathBuf> {
+        self.marks.get(&mark)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&char, &PathBuf)> {
+        self.marks.iter()
+    }
+
+    /// Stores the path under the given mark and writes all bookmarks to the
+    /// bookmarks file.
+    pub fn set(&mut self, mark: char, path: PathBuf) {
+        if !Self::is_valid_mark(mark) {
+            return;
+        }
+
+        self.marks.insert(mark, path);
+
+        if let Some(file_path) = &self.file_path {
+            if let Err(err) = self.write_bookmarks_file(file_path) {
+                warn!("could not write the bookmarks file: {}", err);
+            }
+        }
+    }
+
+    fn read_bookmarks_file(file_path: &Path) -> std::io::Result<BookmarksFile> {
+        let bookmarks_file_content = read_file(file_path)?;
+
+        toml::from_str(&bookmarks_file_content).map_err(|err| {
+            std::io::Error::new(std::io::ErrorKind::InvalidData, err)
+        })
+    }
+
+    /// The bookmarks as they are stored. toml strings cannot hold paths
+    /// which are not valid UTF-8, such bookmarks are only kept in memory.
+    fn get_bookmarks_file(&self) -> BookmarksFile {
+        let mut bookmarks_file = BookmarksFile::default();
+
+        for (mark, path) in &self.marks {
+            if path.to_str().is_some() {
+                bookmarks_file
+                    .bookmarks
+                    .insert(mark.to_string(), path.clone());
+            } else {
+                warn!("not storing the non UTF-8 bookmark {} {:?}", mark, path);
+            }
+        }
+
+        bookmarks_file
+    }
+
+    fn write_bookmarks_file(&self, file_path: &Path) -> std::io::Result<()> {
+        let bookmarks_file_content =
+            toml::to_string(&self.get_bookmarks_file()).map_err(|err| {
+                std::io::Error::new(std::io::ErrorKind::InvalidData, err)
+            })?;
+
+        write_file(file_path, &bookmarks_file_content)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::OsStr;
+    use std::fs::create_dir_all;
+    use std::fs::remove_dir_all;
+    use std::os::unix::ffi::OsStrExt;
+
+    fn prepare_bookmarks() -> Bookmarks {
+        let mut config = Config::default();
+        config.behavior.persist_bookmarks = false;
+        Bookmarks::new(&config, None)
+    }
+
+    #[test]
+    fn persist_bookmarks() {
+        let config_dir = std::env::temp_dir().join(format!(
+            "twilight-commander-bookmarks-{}",
+            std::process::id()
+        ));
+        create_dir_all(&config_dir).unwrap();
+        let mut config = Config::default();
+        config.behavior.persist_bookmarks = true;
+
+        let mut bookmarks = Bookmarks::new(&config, Some(&config_dir));
+        bookmarks.set('a', PathBuf::from("/a"));
+        let read_bookmarks = Bookmarks::new(&config, Some(&config_dir));
+        remove_dir_all(&config_dir).unwrap();
+
+        assert_eq!(bookmarks, read_bookmarks);
+        assert_eq!(Some(&PathBuf::from("/a")), read_bookmarks.get('a'));
+    }
+
+    #[test]
+    fn set_and_get() {
+        let mut bookmarks = prepare_bookmarks();
+
+        bookmarks.set('a', PathBuf::from("/a"));
+        bookmarks.set('a', PathBuf::from("/b"));
+        bookmarks.set('1', PathBuf::from("/1"));
+
+        assert_eq!(Some(&PathBuf::from("/b")), bookmarks.get('a'));
+        assert_eq!(Some(&PathBuf::from("/1")), bookmarks.get('1'));
+        assert_eq!(None, bookmarks.get('c'));
+        assert_eq!(
+            vec!['1', 'a'],
+            bookmarks.iter().map(|(m, _)| *m).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn invalid_marks() {
+        let mut bookmarks = prepare_bookmarks();
+
+        bookmarks.set(' ', PathBuf::from("/a"));
+        bookmarks.set('\n', PathBuf::from("/a"));
+
+        assert_eq!(0, bookmarks.iter().count());
+    }
+
+    #[test]
+    fn parse_mark() {
+        assert_eq!(Some('x'), Bookmarks::parse_mark("x"));
+        assert_eq!(None, Bookmarks::parse_mark("xy"));
+        assert_eq!(None, Bookmarks::parse_mark(""));
+        assert_eq!(None, Bookmarks::parse_mark(" "));
+    }
+
+    #[test]
+    fn skip_non_utf8_bookmarks() {
+        let mut bookmarks = prepare_bookmarks();
+        bookmarks.set('a', PathBuf::from("/a"));
+        bookmarks.set('b', PathBuf::from(OsStr::from_bytes(b"/b\xff")));
+
+        let bookmarks_file = bookmarks.get_bookmarks_file();
+
+        assert_eq!(2, bookmarks.iter().count());
+        assert_eq!(
+            vec![(&String::from("a"), &PathBuf::from("/a"))],
+            bookmarks_file.bookmarks.iter().collect::<Vec<_>>()
+        );
+        assert!(toml::to_string(&bookmarks_file).is_ok());
+    }
+
+    #[test]
+    fn deserialize_bookmarks_file() {
+        let bookmarks_file: BookmarksFile = toml::from_str(
+            r#"
+            [bookmarks]
+            a = "/home"
+            "'" = "/tmp/file"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            Some(&PathBuf::from("/home")),
+            bookmarks_file.bookmarks.get("a")
+        );
+        assert_eq!(
+            Some(&PathBuf::from("/tmp/file")),
+            bookmarks_file.bookmarks.get("'")
+        );
+    }
+}
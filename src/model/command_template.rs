@@ -0,0 +1,525 @@
+use std::ffi::OsStr;
+use std::ffi::OsString;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::ffi::OsStringExt;
+use std::path::Path;
+use std::path::PathBuf;
+use std::str::Chars;
+
+/// The placeholders written as `%{x}`, `%s` is also written without braces.
+const PLACEHOLDERS: &str = "spdbnerS%";
+
+/// The paths a command template refers to.
+pub struct TemplateContext<'a> {
+    pub path: &'a Path,
+    pub root: &'a Path,
+    pub selection: &'a [PathBuf],
+}
+
+impl<'a> TemplateContext<'a> {
+    /// The values of a placeholder, `None` for unknown placeholders.
+    fn get_values(&self, placeholder: char) -> Option<Vec<&'a OsStr>> {
+        let path = self.path;
+        let value = match placeholder {
+            's' | 'p' => path.as_os_str(),
+            'd' => path.parent().unwrap_or(path).as_os_str(),
+            'b' => path.file_name().unwrap_or(path.as_os_str()),
+            'n' => path.file_stem().unwrap_or_default(),
+            'e' => path.extension().unwrap_or_default(),
+            'r' => match path.strip_prefix(self.root) {
+                Ok(relative_path) if relative_path.as_os_str().is_empty() => {
+                    OsStr::new(".")
+                }
+                Ok(relative_path) => relative_path.as_os_str(),
+                Err(_) => path.as_os_str(),
+            },
+            'S' => {
+                return Some(
+                    self.selection
+                        .iter()
+                        .map(|path| path.as_os_str())
+                        .collect(),
+                )
+            }
+            _ => return None,
+        };
+        Some(vec![value])
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Quoting {
+    None,
+    Single,
+    Double,
+}
+
+/// Reads the placeholder after a `%`, which is `%s` or `%{x}`. Any other `%`
+/// is no placeholder and kept, so commands like `date +%d` work unchanged.
+fn read_placeholder(chars: &mut Chars) -> Option<char> {
+    let rest = chars.as_str();
+    let (placeholder, len) = match rest.strip_prefix('{') {
+        Some(name) => {
+            let mut name = name.chars();
+            match (name.next(), name.next()) {
+                (Some(placeholder), Some('}')) => {
+                    (placeholder, placeholder.len_utf8() + 2)
+                }
+                _ => return None,
+            }
+        }
+        None if rest.starts_with('s') => ('s', 1),
+        None => return None,
+    };
+
+    if !PLACEHOLDERS.contains(placeholder) {
+        return None;
+    }
+    *chars = rest[len..].chars();
+    Some(placeholder)
+}
+
+/// A `$(…)` or backtick command substitution, which starts a new command with
+/// its own quoting.
+struct Substitution {
+    /// the quoting and the open parentheses around the substitution
+    outer_quoting: Quoting,
+    outer_parens: usize,
+    end: char,
+}
+
+/// Renders a command for `bash -c`. Placeholders are quoted for the quoting
+/// they appear in, also within command substitutions, so that no file name
+/// can end the quoting, and `%{%}` is a `%`.
+pub fn render_shell(template: &str, context: &TemplateContext) -> OsString {
+    let mut bytes = Vec::new();
+    let mut quoting = Quoting::None;
+    // the open parentheses of the current command, `)` ends a `$(` after them
+    let mut parens = 0;
+    let mut substitutions: Vec<Substitution> = Vec::new();
+    let mut chars = template.chars();
+
+    while let Some(c) = chars.next() {
+        match (c, quoting) {
+            ('%', _) => match read_placeholder(&mut chars) {
+                Some('%') | None => bytes.push(b'%'),
+                Some(placeholder) => {
+                    let values =
+                        context.get_values(placeholder).unwrap_or_default();
+                    for (i, value) in values.iter().enumerate() {
+                        if i > 0 {
+                            bytes.push(b' ');
+                        }
+                        push_quoted(&mut bytes, value, quoting);
+                    }
+                }
+            },
+            ('\\', Quoting::None) | ('\\', Quoting::Double) => {
+                push_char(&mut bytes, c);
+                if let Some(escaped) = chars.next() {
+                    push_char(&mut bytes, escaped);
+                }
+            }
+            ('\'', Quoting::None) => {
+                quoting = Quoting::Single;
+                bytes.push(b'\'');
+            }
+            ('\'', Quoting::Single) => {
+                quoting = Quoting::None;
+                bytes.push(b'\'');
+            }
+            ('"', Quoting::None) => {
+                quoting = Quoting::Double;
+                bytes.push(b'"');
+            }
+            ('"', Quoting::Double) => {
+                quoting = Quoting::None;
+                bytes.push(b'"');
+            }
+            ('$', Quoting::None) | ('$', Quoting::Double)
+                if chars.as_str().starts_with('(') =>
+            {
+                chars.next();
+                bytes.extend_from_slice(b"$(");
+                substitutions.push(Substitution {
+                    outer_quoting: quoting,
+                    outer_parens: parens,
+                    end: ')',
+                });
+                quoting = Quoting::None;
+                parens = 0;
+            }
+            ('(', Quoting::None) => {
+                parens += 1;
+                bytes.push(b'(');
+            }
+            (')', Quoting::None) if parens > 0 => {
+                parens -= 1;
+                bytes.push(b')');
+            }
+            (')', Quoting::None)
+            | ('`', Quoting::None)
+            | ('`', Quoting::Double) => {
+                match substitutions.last() {
+                    Some(substitution) if substitution.end == c => {
+                        quoting = substitution.outer_quoting;
+                        parens = substitution.outer_parens;
+                        substitutions.pop();
+                    }
+                    _ if c == '`' => {
+                        substitutions.push(Substitution {
+                            outer_quoting: quoting,
+                            outer_parens: parens,
+                            end: '`',
+                        });
+                        quoting = Quoting::None;
+                        parens = 0;
+                    }
+                    _ => {}
+                }
+                push_char(&mut bytes, c);
+            }
+            _ => push_char(&mut bytes, c),
+        }
+    }
+
+    OsString::from_vec(bytes)
+}
+
+fn push_char(bytes: &mut Vec<u8>, c: char) {
+    bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+}
+
+/// Pushes a value single-quoted, a double quoting is ended around it.
+fn push_quoted(bytes: &mut Vec<u8>, value: &OsStr, quoting: Quoting) {
+    match quoting {
+        Quoting::None => {
+            bytes.push(b'\'');
+            push_single_quoted(bytes, value);
+            bytes.push(b'\'');
+        }
+        Quoting::Single => push_single_quoted(bytes, value),
+        Quoting::Double => {
+            bytes.extend_from_slice(b"\"'");
+            push_single_quoted(bytes, value);
+            bytes.extend_from_slice(b"'\"");
+        }
+    }
+}
+
+/// Ends the single quoting for every byte that is special in some quoting or
+/// in backticks, and writes it as `$'\xHH'` in between. So the value stays
+/// intact in any command substitution.
+fn push_single_quoted(bytes: &mut Vec<u8>, value: &OsStr) {
+    for byte in value.as_bytes() {
+        if b"'\"\\$`".contains(byte) {
+            bytes.extend_from_slice(format!("'$'\\x{:02x}''", byte).as_bytes());
+        } else {
+            bytes.push(*byte);
+        }
+    }
+}
+
+/// Splits a command into arguments to run without a shell. Words are
+/// separated by whitespace and may be quoted like in a shell. Placeholders
+/// are replaced by their exact value, a `%{S}` word by one argument per
+/// selected path.
+pub fn render_argv(
+    template: &str,
+    context: &TemplateContext,
+) -> Result<Vec<OsString>, String> {
+    let mut argv = Vec::new();
+    // the current word, `None` between words
+    let mut word: Option<Vec<u8>> = None;
+    let mut quoting = Quoting::None;
+    let mut chars = template.chars();
+
+    while let Some(c) = chars.next() {
+        match (c, quoting) {
+            ('%', Quoting::Single) => {
+                push_char(word.get_or_insert_with(Vec::new), c)
+            }
+            ('%', _) => {
+                let placeholder = read_placeholder(&mut chars);
+                if placeholder == Some('S')
+                    && quoting == Quoting::None
+                    && word.is_none()
+                    && chars
+                        .clone()
+                        .next()
+                        .filter(|c| !c.is_whitespace())
+                        .is_none()
+                {
+                    argv.extend(
+                        context
+                            .selection
+                            .iter()
+                            .map(|path| path.clone().into()),
+                    );
+                    continue;
+                }
+
+                let word = word.get_or_insert_with(Vec::new);
+                match placeholder {
+                    Some('%') | None => word.push(b'%'),
+                    Some(placeholder) => {
+                        let values =
+                            context.get_values(placeholder).unwrap_or_default();
+                        for (i, value) in values.iter().enumerate() {
+                            if i > 0 {
+                                word.push(b' ');
+                            }
+                            word.extend_from_slice(value.as_bytes());
+                        }
+                    }
+                }
+            }
+            ('\\', Quoting::None) | ('\\', Quoting::Double) => {
+                let escaped = chars
+                    .next()
+                    .ok_or_else(|| String::from("trailing backslash"))?;
+                push_char(word.get_or_insert_with(Vec::new), escaped);
+            }
+            ('\'', Quoting::None) => {
+                word.get_or_insert_with(Vec::new);
+                quoting = Quoting::Single;
+            }
+            ('"', Quoting::None) => {
+                word.get_or_insert_with(Vec::new);
+                quoting = Quoting::Double;
+            }
+            ('\'', Quoting::Single) | ('"', Quoting::Double) => {
+                quoting = Quoting::None;
+            }
+            (c, Quoting::None) if c.is_whitespace() => {
+                if let Some(word) = word.take() {
+                    argv.push(OsString::from_vec(word));
+                }
+            }
+            _ => push_char(word.get_or_insert_with(Vec::new), c),
+        }
+    }
+
+    if quoting != Quoting::None {
+        return Err(String::from("unterminated quote"));
+    }
+    if let Some(word) = word {
+        argv.push(OsString::from_vec(word));
+    }
+
+    Ok(argv)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    const HOSTILE_NAMES: [&[u8]; 13] = [
+        b"with space",
+        b"it's",
+        b"double\"quote",
+        b"$(touch pwned)",
+        b"`touch pwned`",
+        b"$HOME",
+        b"x;touch pwned",
+        b"\")$(touch pwned)",
+        b"'`touch pwned`'",
+        b"semi;colon && echo",
+        b"new\nline",
+        b"back\\slash\\",
+        b"bad\xffutf-8",
+    ];
+
+    fn context<'a>(
+        path: &'a Path,
+        selection: &'a [PathBuf],
+    ) -> TemplateContext<'a> {
+        TemplateContext {
+            path,
+            root: Path::new("/root dir"),
+            selection,
+        }
+    }
+
+    fn hostile_path(name: &[u8]) -> PathBuf {
+        let mut bytes = b"/root dir/sub dir/".to_vec();
+        bytes.extend_from_slice(name);
+        PathBuf::from(OsString::from_vec(bytes))
+    }
+
+    /// Runs the rendered command and returns what it printed.
+    fn run_shell(template: &str, path: &Path) -> Vec<u8> {
+        let selection = vec![path.to_path_buf(), PathBuf::from("/x y")];
+        let command = render_shell(template, &context(path, &selection));
+        let output = Command::new("bash")
+            .arg("-c")
+            .arg(command)
+            .current_dir(std::env::temp_dir())
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{:?}", output);
+        output.stdout
+    }
+
+    #[test]
+    fn placeholders() {
+        let path = Path::new("/root dir/sub/archive.tar.gz");
+        let selection = vec![PathBuf::from("/a"), PathBuf::from("/b c")];
+        let context = context(path, &selection);
+
+        assert_eq!(
+            OsStr::new(
+                "x '/root dir/sub/archive.tar.gz' '/root dir/sub' \
+                 'archive.tar.gz' 'archive.tar' 'gz' 'sub/archive.tar.gz' \
+                 '/a' '/b c' 100% %q %{q}"
+            ),
+            render_shell(
+                "x %s %{d} %{b} %{n} %{e} %{r} %{S} 100%{%} %q %{q}",
+                &context
+            )
+        );
+    }
+
+    #[test]
+    fn percent_signs_kept() {
+        let path = Path::new("/a/b");
+        let context = context(path, &[]);
+
+        assert_eq!(
+            OsStr::new("date +%d; printf '/a/b %d%%\\n' 5"),
+            render_shell("date +%d; printf '%s %d%%\\n' 5", &context)
+        );
+        assert_eq!(OsStr::new("%{s %"), render_shell("%{s %", &context));
+    }
+
+    #[test]
+    fn placeholders_outside_root() {
+        let path = Path::new("/elsewhere/Makefile");
+        let context = context(path, &[]);
+
+        assert_eq!(
+            OsStr::new("'/elsewhere/Makefile' '' 'Makefile'"),
+            render_shell("%{r} %{e} %{n}", &context)
+        );
+    }
+
+    #[test]
+    fn hostile_names_unquoted() {
+        for name in HOSTILE_NAMES.iter() {
+            let path = hostile_path(name);
+
+            let mut expected = b"[".to_vec();
+            expected.extend_from_slice(path.as_os_str().as_bytes());
+            expected.push(b']');
+            assert_eq!(expected, run_shell("printf '[%{%}s]' %s", &path));
+        }
+        assert!(!std::env::temp_dir().join("pwned").exists());
+    }
+
+    #[test]
+    fn hostile_names_quoted() {
+        for name in HOSTILE_NAMES.iter() {
+            let path = hostile_path(name);
+
+            let mut expected = b"<".to_vec();
+            expected.extend_from_slice(name);
+            expected.extend_from_slice(b"> <");
+            expected.extend_from_slice(name);
+            expected.push(b'>');
+            assert_eq!(
+                expected,
+                run_shell("printf '%{%}s' \"<%{b}>\" ' <%{b}>'", &path)
+            );
+        }
+        assert!(!std::env::temp_dir().join("pwned").exists());
+    }
+
+    #[test]
+    fn hostile_names_in_substitutions() {
+        let templates = [
+            "printf '%{%}s' \"$(printf '[%{%}s]' %s)\"",
+            "printf '%{%}s' \"$(printf '[%{%}s]' \"%s\")\"",
+            "printf '%{%}s' \"$( (printf '[%{%}s]' '%s') )\"",
+            "printf '%{%}s' \"$(: $((1)); printf '[%{%}s]' %s)\"",
+            "printf '%{%}s' \"`printf '[%{%}s]' %s`\"",
+            "printf '%{%}s' \"`printf '[%{%}s]' \"%s\"`\"",
+            "printf '%{%}s' \"`printf '[%{%}s]' '%s'`\"",
+        ];
+        for template in templates.iter() {
+            for name in HOSTILE_NAMES.iter() {
+                let path = hostile_path(name);
+
+                let mut expected = b"[".to_vec();
+                expected.extend_from_slice(path.as_os_str().as_bytes());
+                expected.push(b']');
+                assert_eq!(
+                    expected,
+                    run_shell(template, &path),
+                    "{}",
+                    template
+                );
+            }
+        }
+        assert!(!std::env::temp_dir().join("pwned").exists());
+    }
+
+    #[test]
+    fn hostile_selection() {
+        let path = hostile_path(b"it's $(x)");
+
+        let mut expected = path.as_os_str().as_bytes().to_vec();
+        expected.extend_from_slice(b"|/x y|");
+        assert_eq!(expected, run_shell("printf '%{%}s|' %{S}", &path));
+    }
+
+    #[test]
+    fn argv() {
+        let path = Path::new("/root dir/it's $(x).txt");
+        let selection = vec![PathBuf::from("/a"), PathBuf::from("/b c")];
+        let context = context(path, &selection);
+
+        let argv = render_argv(
+            r#"cmd --file=%s "quoted %{b}" 'single %{b}' esc\ aped %{S} "%{S}" 50% 5%{%}"#,
+            &context,
+        )
+        .unwrap();
+
+        assert_eq!(
+            vec![
+                OsString::from("cmd"),
+                OsString::from("--file=/root dir/it's $(x).txt"),
+                OsString::from("quoted it's $(x).txt"),
+                OsString::from("single %{b}"),
+                OsString::from("esc aped"),
+                OsString::from("/a"),
+                OsString::from("/b c"),
+                OsString::from("/a /b c"),
+                OsString::from("50%"),
+                OsString::from("5%"),
+            ],
+            argv
+        );
+    }
+
+    #[test]
+    fn argv_hostile_names() {
+        for name in HOSTILE_NAMES.iter() {
+            let path = hostile_path(name);
+
+            let argv = render_argv("printf %s", &context(&path, &[])).unwrap();
+
+            assert_eq!(vec![OsString::from("printf"), path.into()], argv);
+        }
+    }
+
+    #[test]
+    fn argv_errors() {
+        let context = context(Path::new("/a"), &[]);
+
+        assert!(render_argv("echo 'open", &context).is_err());
+        assert!(render_argv("echo \\", &context).is_err());
+        assert_eq!(Ok(Vec::new()), render_argv("  ", &context));
+        assert_eq!(Ok(vec![OsString::from("")]), render_argv("''", &context));
+    }
+}
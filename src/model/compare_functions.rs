@@ -4,6 +4,10 @@ use std::cmp::Ordering;
 
 pub type PathNodeCompare = fn(&PathNode, &PathNode) -> Ordering;
 
+/// The values of `behavior.path_node_sort`.
+pub const PATH_NODE_SORTS: [&str; 4] =
+    ["dirs_bot_simple", "dirs_top_simple", "natural", "none"];
+
 impl PathNode {
     pub fn compare_dirs_bot_simple(a: &PathNode, b: &PathNode) -> Ordering {
         if a.is_dir && !b.is_dir {
@@ -25,11 +29,25 @@ impl PathNode {
         a.display_text.cmp(&b.display_text)
     }
 
+    /// Directories first, then digits are compared by their numeric value
+    /// and letters case-insensitively, e.g. `file2` comes before `file10`.
+    pub fn compare_natural(a: &PathNode, b: &PathNode) -> Ordering {
+        if a.is_dir && !b.is_dir {
+            return std::cmp::Ordering::Less;
+        } else if !a.is_dir && b.is_dir {
+            return std::cmp::Ordering::Greater;
+        }
+
+        compare_natural_text(&a.display_text, &b.display_text)
+            .then_with(|| a.display_text.cmp(&b.display_text))
+    }
+
     pub fn get_path_node_compare(config: &Config) -> PathNodeCompare {
         let path_node_compare: fn(&PathNode, &PathNode) -> Ordering =
             match config.behavior.path_node_sort.as_str() {
                 "dirs_bot_simple" => PathNode::compare_dirs_bot_simple,
                 "dirs_top_simple" => PathNode::compare_dirs_top_simple,
+                "natural" => PathNode::compare_natural,
                 "none" => |_, _| Ordering::Equal,
                 _ => |_, _| Ordering::Equal,
             };
@@ -38,6 +56,52 @@ impl PathNode {
     }
 }
 
+fn compare_natural_text(a: &str, b: &str) -> Ordering {
+    let mut a_chars = a.chars().peekable();
+    let mut b_chars = b.chars().peekable();
+
+    loop {
+        match (a_chars.peek(), b_chars.peek()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(a_char), Some(b_char))
+                if a_char.is_ascii_digit() && b_char.is_ascii_digit() =>
+            {
+                let a_number = take_number(&mut a_chars);
+                let b_number = take_number(&mut b_chars);
+                let order = a_number
+                    .len()
+                    .cmp(&b_number.len())
+                    .then_with(|| a_number.cmp(&b_number));
+                if order != Ordering::Equal {
+                    return order;
+                }
+            }
+            (Some(a_char), Some(b_char)) => {
+                let order = a_char.to_lowercase().cmp(b_char.to_lowercase());
+                if order != Ordering::Equal {
+                    return order;
+                }
+                a_chars.next();
+                b_chars.next();
+            }
+        }
+    }
+}
+
+/// Takes the digits without leading zeros.
+fn take_number(chars: &mut std::iter::Peekable<std::str::Chars>) -> String {
+    let mut number = String::new();
+    while let Some(c) = chars.peek().filter(|c| c.is_ascii_digit()) {
+        if !(number.is_empty() && *c == '0') {
+            number.push(*c);
+        }
+        chars.next();
+    }
+    number
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -112,6 +176,29 @@ mod tests {
         }
     }
 
+    mod compare_natural_tests {
+        use super::*;
+
+        #[test]
+        fn numbers() {
+            let mut names = vec!["file10", "file2", "File3", "file02", "a"];
+
+            names.sort_by(|a, b| {
+                PathNode::compare_natural(&get_file(a), &get_file(b))
+            });
+
+            assert_eq!(vec!["a", "file02", "file2", "File3", "file10"], names);
+        }
+
+        #[test]
+        fn dir_to_file() {
+            let dir = get_dir("b");
+            let file = get_file("a");
+
+            assert_eq!(Less, PathNode::compare_natural(&dir, &file));
+        }
+    }
+
     fn get_dir(name: &str) -> PathNode {
         let mut path_node = PathNode::from(".");
         path_node.is_dir = true;
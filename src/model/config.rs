@@ -1,24 +1,87 @@
+use crate::model::compare_functions::PATH_NODE_SORTS;
 use crate::model::config::behavior::Behavior;
 use crate::model::config::color::Color;
 use crate::model::config::composition::Composition;
 use crate::model::config::debug::Debug;
 use crate::model::config::keybinding::Keybinding;
+use crate::model::config::keymap::get_conflicts;
+use crate::model::config::keymap::Action;
+use crate::model::config::keymap::Keymap;
+use crate::model::config::layers::find_project_config_file;
+use crate::model::config::layers::get_base_config_files;
+use crate::model::config::layers::get_explicit_config_file;
+use crate::model::config::layers::merge;
+use crate::model::config::layers::remove_commands;
+use crate::model::config::opener::OpenerRule;
+use crate::model::config::reference::get_default_config;
 use crate::model::config::setup::Setup;
+use crate::model::event::Key;
 use crate::utils::get_config_dir;
 use crate::utils::print_help;
 use crate::utils::read_file;
 use log::{info, warn};
 use serde::Deserialize;
+use std::cell::RefCell;
 use std::env::args;
+use std::path::Path;
+use std::path::PathBuf;
 use std::process::exit;
+use std::rc::Rc;
 
-mod behavior;
+pub mod behavior;
 mod color;
 mod composition;
 mod debug;
-mod keybinding;
+pub mod keybinding;
+pub mod keymap;
+mod layers;
+pub mod opener;
+pub mod reference;
 mod setup;
 
+/// The config the event queue, the composer and the pager read, so that
+/// changes at runtime apply to all of them.
+pub type SharedConfig = Rc<RefCell<Config>>;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum OptionError {
+    Unknown,
+    Unparsable,
+
+    /// The value is not one of the listed ones.
+    Invalid(&'static [&'static str]),
+}
+
+impl std::fmt::Display for OptionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            OptionError::Unknown => write!(f, "unknown option"),
+            OptionError::Unparsable => write!(f, "value not parsable"),
+            OptionError::Invalid(values) => {
+                write!(f, "expected one of {}", values.join(", "))
+            }
+        }
+    }
+}
+
+fn parse_value<F>(value: &str) -> Result<F, OptionError>
+where
+    F: std::str::FromStr,
+{
+    value.parse().map_err(|_| OptionError::Unparsable)
+}
+
+fn parse_enum(
+    value: &str,
+    values: &'static [&'static str],
+) -> Result<String, OptionError> {
+    if values.contains(&value) {
+        Ok(String::from(value))
+    } else {
+        Err(OptionError::Invalid(values))
+    }
+}
+
 #[derive(Clone, Debug, Default, Deserialize)]
 pub struct Config {
     #[serde(default)]
@@ -36,54 +99,270 @@ pub struct Config {
     #[serde(default)]
     pub keybinding: Keybinding,
 
+    #[serde(default)]
+    pub keymap: Keymap,
+
+    #[serde(default)]
+    pub opener: Vec<OpenerRule>,
+
     #[serde(default)]
     pub setup: Setup,
+
+    /// The files the config was read from, or would be if they existed,
+    /// watched for changes.
+    #[serde(skip)]
+    pub config_files: Vec<PathBuf>,
+
+    /// The directory of the bookmarks, the history and the sessions, none
+    /// when there is no home directory.
+    #[serde(skip)]
+    pub config_dir: Option<PathBuf>,
 }
 
 impl Config {
+    /// Exits on errors in the config, and after checking it with
+    /// `--check-config`.
     pub fn new() -> Self {
         info!("initializing config");
 
-        let config = Self::read_config_file().unwrap_or_default();
+        // help and the default config do not depend on the config files,
+        // which may be broken
+        for arg in args().skip(1).take_while(|arg| arg != "--") {
+            match arg.as_str() {
+                "--help" | "--version" => print_help(),
+                "--print-default-config" => {
+                    print!("{}", get_default_config());
+                    exit(0);
+                }
+                _ => {}
+            }
+        }
+
+        let is_check = args().any(|arg| arg == "--check-config");
+
+        let (config, warnings) = match Self::load() {
+            Ok(loaded) => loaded,
+            Err(errors) => {
+                for error in &errors {
+                    eprintln!("{}", error);
+                }
+                exit(1);
+            }
+        };
+
+        for warning in &warnings {
+            warn!("{}", warning);
+            eprintln!("{}", warning);
+        }
+
+        for conflict in get_conflicts(&config.get_key_bindings()) {
+            warn!("key binding conflict: {}", conflict);
+            eprintln!("key binding conflict: {}", conflict);
+        }
 
-        Self::parse_args(config, args().skip(1))
+        if is_check {
+            eprintln!("config ok");
+            exit(0);
+        }
+
+        config
     }
 
-    #[rustfmt::skip]
-    fn parse_args<T>(mut config: Self, args: T) -> Self
+    /// Reads the config layers, or only the explicit config file, and applies
+    /// the command line on top. Warnings are about keys which are ignored,
+    /// errors make the config unusable.
+    pub fn load() -> Result<(Self, Vec<String>), Vec<String>> {
+        let args: Vec<String> = args().skip(1).collect();
+
+        let mut value = toml::Value::Table(toml::value::Table::new());
+        let mut warnings = Vec::new();
+        let mut config_files = Vec::new();
+
+        let explicit_config_file =
+            get_explicit_config_file(&args).map_err(|err| vec![err])?;
+
+        if let Some(config_file) = explicit_config_file {
+            let layer = Self::read_layer(&config_file, &mut warnings)
+                .map_err(|err| vec![err])?;
+            merge(&mut value, layer);
+            config_files.push(config_file);
+        } else {
+            for config_file in get_base_config_files() {
+                if config_file.exists() {
+                    let layer = Self::read_layer(&config_file, &mut warnings)
+                        .map_err(|err| vec![err])?;
+                    merge(&mut value, layer);
+                }
+                config_files.push(config_file);
+            }
+
+            let working_dir = Self::get_working_dir(&args, &value);
+            if let Some(config_file) = find_project_config_file(&working_dir) {
+                let mut layer = Self::read_layer(&config_file, &mut warnings)
+                    .map_err(|err| vec![err])?;
+                warnings.extend(remove_commands(&mut layer).into_iter().map(
+                    |key| {
+                        format!(
+                            "{}: ignored {}, a project config file cannot set \
+                             commands",
+                            config_file.display(),
+                            key
+                        )
+                    },
+                ));
+                merge(&mut value, layer);
+                config_files.push(config_file);
+            }
+        }
+
+        let mut config: Self = value
+            .try_into()
+            .map_err(|err| vec![format!("merged config: {}", err)])?;
+        config.config_files = config_files;
+        config.config_dir = match get_config_dir() {
+            Ok(config_dir) => Some(PathBuf::from(config_dir)),
+            Err(err) => {
+                warn!(
+                    "bookmarks, history and sessions are not stored: {}",
+                    err
+                );
+                None
+            }
+        };
+
+        let config = Self::parse_args(config, args, &mut warnings);
+
+        let errors = config.get_errors();
+        if errors.is_empty() {
+            Ok((config, warnings))
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// The working directory the project config file is searched from, set
+    /// on the command line or by the layers read so far.
+    fn get_working_dir(args: &[String], value: &toml::Value) -> PathBuf {
+        let working_dir_arg = args
+            .iter()
+            .take_while(|arg| *arg != "--")
+            .filter_map(|arg| arg.strip_prefix("--setup.working_dir="))
+            .last();
+
+        let working_dir = working_dir_arg.or_else(|| {
+            value
+                .get("setup")
+                .and_then(|setup| setup.get("working_dir"))
+                .and_then(toml::Value::as_str)
+        });
+
+        PathBuf::from(working_dir.unwrap_or("."))
+    }
+
+    /// Describes every key sequence of the keybindings and the keymap which
+    /// cannot be parsed and every value which is not one of the valid ones.
+    fn get_errors(&self) -> Vec<String> {
+        let mut errors = self.keybinding.get_errors();
+        errors.extend(self.keymap.get_errors());
+
+        let enum_values: [(&str, &str, &'static [&'static str]); 3] = [
+            (
+                "behavior.file_action_mode",
+                &self.behavior.file_action_mode,
+                &Behavior::COMMAND_MODES,
+            ),
+            (
+                "behavior.path_node_sort",
+                &self.behavior.path_node_sort,
+                &PATH_NODE_SORTS,
+            ),
+            (
+                "behavior.scrolling",
+                &self.behavior.scrolling,
+                &Behavior::SCROLLINGS,
+            ),
+        ];
+        for (key, value, values) in enum_values.iter() {
+            if let Err(err) = parse_enum(value, values) {
+                errors.push(format!(
+                    "{}: invalid value '{}', {}",
+                    key, value, err
+                ));
+            }
+        }
+
+        for (index, rule) in self.opener.iter().enumerate() {
+            if let Some(mode) = &rule.mode {
+                if let Err(err) = parse_enum(mode, &Behavior::COMMAND_MODES) {
+                    errors.push(format!(
+                        "opener[{}].mode: invalid value '{}', {}",
+                        index, mode, err
+                    ));
+                }
+            }
+        }
+
+        errors
+    }
+
+    /// Lists the keymap entries followed by the keybindings. A keymap entry
+    /// replaces the keybindings with the same key sequence.
+    pub fn get_key_bindings(&self) -> Vec<(Action, Vec<Key>)> {
+        let mut key_bindings = self.keymap.get_bindings();
+
+        for (action, key_sequence) in self.keybinding.get_bindings() {
+            if !key_bindings.iter().any(|(_, keymap_key_sequence)| {
+                *keymap_key_sequence == key_sequence
+            }) {
+                key_bindings.push((action, key_sequence));
+            }
+        }
+
+        key_bindings
+    }
+
+    /// Applies the options of the command line. Unknown options are added to
+    /// the warnings, invalid values end the program.
+    fn parse_args<T>(
+        mut config: Self,
+        args: T,
+        warnings: &mut Vec<String>,
+    ) -> Self
     where
         T: IntoIterator<Item = String>,
     {
         for arg in args {
             let (key, value) = Self::split_arg(arg);
             match key.as_str() {
-                "--behavior.file_action" => config.behavior.file_action = Self::parse_value((key, value)),
-                "--behavior.quit_on_action" => config.behavior.quit_on_action = Self::parse_value((key, value)),
-                "--behavior.path_node_sort" => config.behavior.path_node_sort = Self::parse_value((key, value)),
-                "--behavior.scrolling" => config.behavior.scrolling = Self::parse_value((key, value)),
-                "--color.background" => config.color.background = Self::parse_value((key, value)),
-                "--color.foreground" => config.color.foreground = Self::parse_value((key, value)),
-                "--composition.indent" => config.composition.indent = Self::parse_value((key, value)),
-                "--composition.show_indent" => config.composition.show_indent = Self::parse_value((key, value)),
-                "--composition.use_utf8" => config.composition.use_utf8 = Self::parse_value((key, value)),
-                "--debug.enabled" => config.debug.enabled = Self::parse_value((key, value)),
-                "--debug.padding_bot" => config.debug.padding_bot = Self::parse_value((key, value)),
-                "--debug.padding_top" => config.debug.padding_top = Self::parse_value((key, value)),
-                "--debug.spacing_bot" => config.debug.spacing_bot = Self::parse_value((key, value)),
-                "--debug.spacing_top" => config.debug.spacing_top = Self::parse_value((key, value)),
-                "--keybinding.collapse_dir" => config.keybinding.collapse_dir = Self::parse_value((key, value)),
-                "--keybinding.entry_down" => config.keybinding.entry_down = Self::parse_value((key, value)),
-                "--keybinding.entry_up" => config.keybinding.entry_up = Self::parse_value((key, value)),
-                "--keybinding.expand_dir" => config.keybinding.expand_dir = Self::parse_value((key, value)),
-                "--keybinding.file_action" => config.keybinding.file_action = Self::parse_value((key, value)),
-                "--keybinding.quit" => config.keybinding.quit = Self::parse_value((key, value)),
-                "--keybinding.reload" => config.keybinding.reload = Self::parse_value((key, value)),
-                "--setup.working_dir" => config.setup.working_dir = Self::parse_value((key, value)),
-
-                "--help" | "--version" => print_help(),
+                "--help"
+                | "--version"
+                | "--check-config"
+                | "--config"
+                | "--print-default-config" => {}
                 "--" => break,
                 _ => {
-                    warn!("unknown option {}", key);
+                    let result = match key.strip_prefix("--") {
+                        Some(option) => config.set_option(option, &value),
+                        None => Err(OptionError::Unknown),
+                    };
+                    match result {
+                        Ok(()) => {}
+                        Err(OptionError::Unknown) => warnings.push(format!(
+                            "command line: unknown option {}",
+                            key
+                        )),
+                        Err(OptionError::Unparsable) => {
+                            eprintln!(
+                                "option '{}={}' was not parsable",
+                                key, value
+                            );
+                            exit(1);
+                        }
+                        Err(err) => {
+                            eprintln!("option '{}={}': {}", key, value, err);
+                            exit(1);
+                        }
+                    }
                 }
             }
         }
@@ -92,8 +371,68 @@ impl Config {
         config
     }
 
+    /// Sets an option by its key like `behavior.scrolling`, the key space of
+    /// the command line without the leading `--`.
+    #[rustfmt::skip]
+    pub fn set_option(&mut self, key: &str, value: &str) -> Result<(), OptionError> {
+        match key {
+            "behavior.expand_all_max_depth" => self.behavior.expand_all_max_depth = parse_value(value)?,
+            "behavior.expand_all_max_entries" => self.behavior.expand_all_max_entries = parse_value(value)?,
+            "behavior.file_action" => self.behavior.file_action = parse_value(value)?,
+            "behavior.file_action_mode" => self.behavior.file_action_mode = parse_enum(value, &Behavior::COMMAND_MODES)?,
+            "behavior.quit_on_action" => self.behavior.quit_on_action = parse_value(value)?,
+            "behavior.key_sequence_timeout_ms" => self.behavior.key_sequence_timeout_ms = parse_value(value)?,
+            "behavior.mouse" => self.behavior.mouse = parse_value(value)?,
+            "behavior.mouse_double_click_ms" => self.behavior.mouse_double_click_ms = parse_value(value)?,
+            "behavior.mouse_scroll_lines" => self.behavior.mouse_scroll_lines = parse_value(value)?,
+            "behavior.history_size" => self.behavior.history_size = parse_value(value)?,
+            "behavior.path_node_sort" => self.behavior.path_node_sort = parse_enum(value, &PATH_NODE_SORTS)?,
+            "behavior.persist_bookmarks" => self.behavior.persist_bookmarks = parse_value(value)?,
+            "behavior.persist_history" => self.behavior.persist_history = parse_value(value)?,
+            "behavior.restore_session" => self.behavior.restore_session = parse_value(value)?,
+            "behavior.use_shell" => self.behavior.use_shell = parse_value(value)?,
+            "behavior.scrolling" => self.behavior.scrolling = parse_enum(value, &Behavior::SCROLLINGS)?,
+            "color.background" => self.color.background = parse_value(value)?,
+            "color.foreground" => self.color.foreground = parse_value(value)?,
+            "composition.indent" => self.composition.indent = parse_value(value)?,
+            "composition.show_indent" => self.composition.show_indent = parse_value(value)?,
+            "composition.use_utf8" => self.composition.use_utf8 = parse_value(value)?,
+            "debug.enabled" => self.debug.enabled = parse_value(value)?,
+            "debug.padding_bot" => self.debug.padding_bot = parse_value(value)?,
+            "debug.padding_top" => self.debug.padding_top = parse_value(value)?,
+            "debug.spacing_bot" => self.debug.spacing_bot = parse_value(value)?,
+            "debug.spacing_top" => self.debug.spacing_top = parse_value(value)?,
+            "keybinding.bookmark_jump" => self.keybinding.bookmark_jump = parse_value(value)?,
+            "keybinding.bookmark_list" => self.keybinding.bookmark_list = parse_value(value)?,
+            "keybinding.bookmark_set" => self.keybinding.bookmark_set = parse_value(value)?,
+            "keybinding.collapse_all" => self.keybinding.collapse_all = parse_value(value)?,
+            "keybinding.collapse_dir" => self.keybinding.collapse_dir = parse_value(value)?,
+            "keybinding.collapse_siblings" => self.keybinding.collapse_siblings = parse_value(value)?,
+            "keybinding.command_prompt" => self.keybinding.command_prompt = parse_value(value)?,
+            "keybinding.enter_dir" => self.keybinding.enter_dir = parse_value(value)?,
+            "keybinding.entry_down" => self.keybinding.entry_down = parse_value(value)?,
+            "keybinding.entry_up" => self.keybinding.entry_up = parse_value(value)?,
+            "keybinding.expand_dir" => self.keybinding.expand_dir = parse_value(value)?,
+            "keybinding.expand_all" => self.keybinding.expand_all = parse_value(value)?,
+            "keybinding.file_action" => self.keybinding.file_action = parse_value(value)?,
+            "keybinding.history_back" => self.keybinding.history_back = parse_value(value)?,
+            "keybinding.history_forward" => self.keybinding.history_forward = parse_value(value)?,
+            "keybinding.open_with" => self.keybinding.open_with = parse_value(value)?,
+            "keybinding.parent_dir" => self.keybinding.parent_dir = parse_value(value)?,
+            "keybinding.quit" => self.keybinding.quit = parse_value(value)?,
+            "keybinding.reload" => self.keybinding.reload = parse_value(value)?,
+            "keybinding.suspend" => self.keybinding.suspend = parse_value(value)?,
+            "keybinding.toggle_option" => self.keybinding.toggle_option = parse_value(value)?,
+            "setup.working_dir" => self.setup.working_dir = parse_value(value)?,
+
+            _ if key.starts_with("keymap.") => { self.keymap.0.insert(String::from(&key["keymap.".len()..]), String::from(value).into()); }
+
+            _ => return Err(OptionError::Unknown),
+        }
+        Ok(())
+    }
+
     fn split_arg(arg: String) -> (String, String) {
-        println!("{}", arg);
         if let Some(equal_sign_index) = arg.find('=') {
             let before_split = arg.split_at(equal_sign_index);
             let after_split = arg.split_at(equal_sign_index + 1);
@@ -102,43 +441,194 @@ impl Config {
         (arg, String::from(""))
     }
 
-    fn parse_value<F>((key, value): (String, String)) -> F
-    where
-        F: std::str::FromStr,
-    {
-        value.parse().unwrap_or_else(|_| {
-            println!("option '{}={}' was not parsable", key, value);
-            exit(1);
-        })
+    /// Reads and parses a config file and lists its unknown keys. Errors and
+    /// warnings start with the file, and the line and column if known.
+    fn read_layer(
+        config_file: &Path,
+        warnings: &mut Vec<String>,
+    ) -> Result<toml::Value, String> {
+        let config_file_content = read_file(config_file)
+            .map_err(|err| format!("{}: {}", config_file.display(), err))?;
+        let config_file = config_file.to_string_lossy();
+
+        // the layer on its own has to be a valid config, so that errors
+        // point into the file they are in
+        toml::from_str::<Self>(&config_file_content)
+            .map_err(|err| format_toml_error(&config_file, &err))?;
+
+        let layer: toml::Value = toml::from_str(&config_file_content)
+            .map_err(|err| format_toml_error(&config_file, &err))?;
+
+        warnings.extend(
+            get_unknown_keys(&layer)
+                .into_iter()
+                .map(|key| format!("{}: unknown key {}", config_file, key)),
+        );
+
+        Ok(layer)
     }
 
-    fn read_config_file() -> std::io::Result<Self> {
-        let config_dir = get_config_dir()?;
+    /// Whether the key, like `behavior.scrolling`, names an option.
+    fn is_option(key: &str) -> bool {
+        Self::default().set_option(key, "") != Err(OptionError::Unknown)
+    }
+}
 
-        let config_file = format!("{}/twilight-commander.toml", config_dir);
+/// Moves the position of the error to the front, like a compiler does.
+fn format_toml_error(config_file: &str, err: &toml::de::Error) -> String {
+    let message = err.to_string();
+    match err.line_col() {
+        Some((line, col)) => {
+            let message = match message.rfind(" at line ") {
+                Some(index) => &message[..index],
+                None => &message,
+            };
+            format!("{}:{}:{}: {}", config_file, line + 1, col + 1, message)
+        }
+        None => format!("{}: {}", config_file, message),
+    }
+}
 
-        let config_file_content = read_file(&config_file)?;
+/// Lists the keys of the config file which are ignored, i.e. no options,
+/// sections or keys of opener rules. Any key is a keymap entry.
+fn get_unknown_keys(value: &toml::Value) -> Vec<String> {
+    let mut unknown_keys = Vec::new();
 
-        toml::from_str(&config_file_content).map_err(|_| {
-            std::io::Error::new(
-                std::io::ErrorKind::NotFound,
-                "could not read the config file",
-            )
-        })
+    let sections = match value.as_table() {
+        Some(sections) => sections,
+        None => return unknown_keys,
+    };
+
+    for (section, section_value) in sections {
+        match (section.as_str(), section_value) {
+            ("keymap", toml::Value::Table(_)) => {}
+            ("opener", toml::Value::Array(rules)) => {
+                for (index, rule) in rules.iter().enumerate() {
+                    for key in
+                        rule.as_table().into_iter().flat_map(|rule| rule.keys())
+                    {
+                        if !OpenerRule::KEYS.contains(&key.as_str()) {
+                            unknown_keys
+                                .push(format!("opener[{}].{}", index, key));
+                        }
+                    }
+                }
+            }
+            (_, toml::Value::Table(options)) => {
+                for key in options.keys() {
+                    let key = format!("{}.{}", section, key);
+                    if !Config::is_option(&key) {
+                        unknown_keys.push(key);
+                    }
+                }
+            }
+            _ => unknown_keys.push(section.clone()),
+        }
     }
+
+    unknown_keys
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::write_file;
+
+    #[test]
+    fn read_layer() {
+        let config_file = std::env::temp_dir()
+            .join(format!("twilight-commander-{}.toml", std::process::id()));
+        let config_file_name = config_file.to_str().unwrap();
+        let read_layer = |value: &mut toml::Value| {
+            let mut warnings = Vec::new();
+            Config::read_layer(&config_file, &mut warnings)
+                .map(|layer| merge(value, layer))
+                .map(|()| warnings)
+        };
+        let mut value = toml::Value::Table(toml::value::Table::new());
+
+        write_file(
+            config_file_name,
+            "skip = 1\n[composition]\nindent = 4\nindnet = 2\n\
+             [keymap]\nx = \"quit\"\n[[opener]]\ncommand = \"a\"\nmine = \"b\"\n",
+        )
+        .unwrap();
+        let warnings = read_layer(&mut value).unwrap();
+        assert_eq!(
+            vec![
+                format!("{}: unknown key composition.indnet", config_file_name),
+                format!("{}: unknown key opener[0].mine", config_file_name),
+                format!("{}: unknown key skip", config_file_name),
+            ],
+            warnings
+        );
+
+        write_file(config_file_name, "[composition]\nuse_utf8 = false\n")
+            .unwrap();
+        read_layer(&mut value).unwrap();
+        let config: Config = value.clone().try_into().unwrap();
+        assert_eq!(4, config.composition.indent);
+        assert!(!config.composition.use_utf8);
+
+        write_file(config_file_name, "[composition]\nindent = \"4\n").unwrap();
+        let err = read_layer(&mut value).unwrap_err();
+        assert!(
+            err.starts_with(&format!("{}:2:", config_file_name)),
+            "{}",
+            err
+        );
+        assert!(!err.contains(" at line "));
+
+        write_file(config_file_name, "[composition]\n\nuse_utf8 = 1\n")
+            .unwrap();
+        let err = read_layer(&mut value).unwrap_err();
+        assert!(
+            err.starts_with(&format!("{}:3:", config_file_name)),
+            "{}",
+            err
+        );
+        assert!(err.contains("composition.use_utf8"));
+
+        std::fs::remove_file(&config_file).unwrap();
+        assert!(read_layer(&mut value).is_err());
+    }
+
+    #[test]
+    fn enum_values() {
+        let mut config = Config::default();
+
+        assert_eq!(Ok(()), config.set_option("behavior.scrolling", "editor"));
+        assert_eq!(
+            Err(OptionError::Invalid(&Behavior::SCROLLINGS)),
+            config.set_option("behavior.scrolling", "sideways")
+        );
+        assert_eq!("editor", config.behavior.scrolling);
+        assert!(config.get_errors().is_empty());
+
+        config.behavior.path_node_sort = String::from("random");
+        config.opener = toml::from_str::<Config>(
+            "[[opener]]\ncommand = \"a\"\nmode = \"detached\"\n",
+        )
+        .unwrap()
+        .opener;
+        assert_eq!(
+            vec![
+                "behavior.path_node_sort: invalid value 'random', expected \
+                 one of dirs_bot_simple, dirs_top_simple, natural, none",
+                "opener[0].mode: invalid value 'detached', expected one of \
+                 background, capture, foreground",
+            ],
+            config.get_errors()
+        );
+    }
 
     #[test]
     fn test_parse_args() {
         let default_config = Config::default();
         let args_vec = vec![
             String::from("--behavior.file_action=file_action_test"),
-            String::from("--behavior.path_node_sort=path_node_sort_test"),
-            String::from("--behavior.scrolling=scrolling_test"),
+            String::from("--behavior.path_node_sort=natural"),
+            String::from("--behavior.scrolling=editor"),
             String::from("--color.background=background_test"),
             String::from("--color.foreground=foreground_test"),
             String::from("--debug.enabled=true"),
@@ -149,20 +639,18 @@ mod tests {
             String::from("--setup.working_dir=working_dir_test"),
         ];
 
-        let config = Config::parse_args(default_config, args_vec.into_iter());
+        let config =
+            Config::parse_args(default_config, args_vec, &mut Vec::new());
 
         assert_eq!(
             config.behavior.file_action,
             String::from("file_action_test")
         );
-        assert_eq!(
-            config.behavior.path_node_sort,
-            String::from("path_node_sort_test")
-        );
-        assert_eq!(config.behavior.scrolling, String::from("scrolling_test"));
+        assert_eq!(config.behavior.path_node_sort, String::from("natural"));
+        assert_eq!(config.behavior.scrolling, String::from("editor"));
         assert_eq!(config.color.background, String::from("background_test"));
         assert_eq!(config.color.foreground, String::from("foreground_test"));
-        assert_eq!(config.debug.enabled, true);
+        assert!(config.debug.enabled);
         assert_eq!(config.debug.padding_bot, 111);
         assert_eq!(config.debug.padding_top, 222);
         assert_eq!(config.debug.spacing_bot, 333);
@@ -175,8 +663,8 @@ mod tests {
         let default_config = Config::default();
         let args_vec = vec![
             String::from("--behavior.file_action=file_action_test"),
-            String::from("--behavior.path_node_sort=path_node_sort_test"),
-            String::from("--behavior.scrolling=scrolling_test"),
+            String::from("--behavior.path_node_sort=natural"),
+            String::from("--behavior.scrolling=editor"),
             String::from("--color.background=background_test"),
             String::from("--color.foreground=foreground_test"),
             String::from("--"),
@@ -188,18 +676,16 @@ mod tests {
             String::from("--setup.working_dir=working_dir_test"),
         ];
 
-        let config = Config::parse_args(default_config, args_vec.into_iter());
+        let config =
+            Config::parse_args(default_config, args_vec, &mut Vec::new());
         let def_conf = Config::default();
 
         assert_eq!(
             config.behavior.file_action,
             String::from("file_action_test")
         );
-        assert_eq!(
-            config.behavior.path_node_sort,
-            String::from("path_node_sort_test")
-        );
-        assert_eq!(config.behavior.scrolling, String::from("scrolling_test"));
+        assert_eq!(config.behavior.path_node_sort, String::from("natural"));
+        assert_eq!(config.behavior.scrolling, String::from("editor"));
         assert_eq!(config.color.background, String::from("background_test"));
         assert_eq!(config.color.foreground, String::from("foreground_test"));
         assert_eq!(config.debug.enabled, def_conf.debug.enabled);
@@ -210,13 +696,120 @@ mod tests {
         assert_eq!(config.setup.working_dir, def_conf.setup.working_dir);
     }
 
+    #[test]
+    fn test_parse_args_keymap() {
+        let args_vec = vec![
+            String::from("--keymap.o=xdg-open %s"),
+            String::from("--keymap.<space>q=quit"),
+        ];
+
+        let config =
+            Config::parse_args(Config::default(), args_vec, &mut Vec::new());
+
+        assert_eq!(
+            Some(&String::from("xdg-open %s").into()),
+            config.keymap.0.get("o")
+        );
+        assert_eq!(
+            Some(&String::from("quit").into()),
+            config.keymap.0.get("<space>q")
+        );
+    }
+
+    #[test]
+    fn get_key_bindings() {
+        let mut config = Config::default();
+        config
+            .keymap
+            .0
+            .insert(String::from("q"), String::from("reload").into());
+        config
+            .keymap
+            .0
+            .insert(String::from("o"), String::from("xdg-open %s").into());
+
+        let key_bindings = config.get_key_bindings();
+
+        assert_eq!(
+            (
+                Action::Command(String::from("xdg-open %s"), None),
+                vec![Key::from("o")]
+            ),
+            key_bindings[0]
+        );
+        assert_eq!(
+            (Action::Builtin("reload"), vec![Key::from("q")]),
+            key_bindings[1]
+        );
+        assert_eq!(
+            1,
+            key_bindings
+                .iter()
+                .filter(|(_, keys)| *keys == vec![Key::from("q")])
+                .count()
+        );
+        assert!(get_conflicts(&key_bindings).is_empty());
+    }
+
+    #[test]
+    fn no_default_conflicts() {
+        assert!(get_conflicts(&Config::default().get_key_bindings()).is_empty());
+    }
+
+    #[test]
+    fn opener_rules_from_toml() {
+        let config: Config = toml::from_str(
+            r#"
+            [[opener]]
+            mime = "image/*"
+            command = "feh %s"
+
+            [[opener]]
+            name = "vim"
+            extension = "rs"
+            command = "xterm -e vim %s"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(2, config.opener.len());
+        assert_eq!(Some(String::from("image/*")), config.opener[0].mime);
+        assert_eq!("vim", config.opener[1].get_name());
+        assert_eq!(None, config.opener[1].glob);
+    }
+
+    #[test]
+    fn test_parse_args_unknown_options() {
+        let args_vec = vec![
+            String::from("--behavior.no_such_option=1"),
+            String::from("stray"),
+            String::from("--debug.enabled=true"),
+        ];
+        let mut warnings = Vec::new();
+
+        let config =
+            Config::parse_args(Config::default(), args_vec, &mut warnings);
+
+        assert!(config.debug.enabled);
+        assert_eq!(
+            vec![
+                String::from(
+                    "command line: unknown option --behavior.no_such_option"
+                ),
+                String::from("command line: unknown option stray"),
+            ],
+            warnings
+        );
+    }
+
     #[test]
     fn test_parse_args_with_multiple_equals() {
         let default_config = Config::default();
         let args_vec =
             vec![String::from("--behavior.file_action=(x=1; y=2; echo $x$y)")];
 
-        let config = Config::parse_args(default_config, args_vec.into_iter());
+        let config =
+            Config::parse_args(default_config, args_vec, &mut Vec::new());
 
         assert_eq!(
             config.behavior.file_action,
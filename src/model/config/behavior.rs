@@ -1,10 +1,14 @@
 use serde::Deserialize;
+use serde::Serialize;
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Behavior {
     #[serde(default = "Behavior::default_file_action")]
     pub file_action: String,
 
+    #[serde(default = "Behavior::default_file_action_mode")]
+    pub file_action_mode: String,
+
     #[serde(default = "Behavior::default_path_node_sort")]
     pub path_node_sort: String,
 
@@ -13,24 +17,79 @@ pub struct Behavior {
 
     #[serde(default = "Behavior::default_quit_on_action")]
     pub quit_on_action: bool,
+
+    #[serde(default = "Behavior::default_use_shell")]
+    pub use_shell: bool,
+
+    #[serde(default = "Behavior::default_history_size")]
+    pub history_size: i32,
+
+    #[serde(default = "Behavior::default_persist_history")]
+    pub persist_history: bool,
+
+    #[serde(default = "Behavior::default_persist_bookmarks")]
+    pub persist_bookmarks: bool,
+
+    #[serde(default = "Behavior::default_restore_session")]
+    pub restore_session: bool,
+
+    #[serde(default = "Behavior::default_expand_all_max_depth")]
+    pub expand_all_max_depth: i32,
+
+    #[serde(default = "Behavior::default_expand_all_max_entries")]
+    pub expand_all_max_entries: i32,
+
+    #[serde(default = "Behavior::default_key_sequence_timeout_ms")]
+    pub key_sequence_timeout_ms: i32,
+
+    #[serde(default = "Behavior::default_mouse")]
+    pub mouse: bool,
+
+    #[serde(default = "Behavior::default_mouse_double_click_ms")]
+    pub mouse_double_click_ms: i32,
+
+    #[serde(default = "Behavior::default_mouse_scroll_lines")]
+    pub mouse_scroll_lines: i32,
 }
 
 impl Default for Behavior {
     fn default() -> Behavior {
         Behavior {
             file_action: Self::default_file_action(),
+            file_action_mode: Self::default_file_action_mode(),
             path_node_sort: Self::default_path_node_sort(),
             scrolling: Self::default_scrolling(),
             quit_on_action: Self::default_quit_on_action(),
+            use_shell: Self::default_use_shell(),
+            history_size: Self::default_history_size(),
+            persist_history: Self::default_persist_history(),
+            persist_bookmarks: Self::default_persist_bookmarks(),
+            restore_session: Self::default_restore_session(),
+            expand_all_max_depth: Self::default_expand_all_max_depth(),
+            expand_all_max_entries: Self::default_expand_all_max_entries(),
+            key_sequence_timeout_ms: Self::default_key_sequence_timeout_ms(),
+            mouse: Self::default_mouse(),
+            mouse_double_click_ms: Self::default_mouse_double_click_ms(),
+            mouse_scroll_lines: Self::default_mouse_scroll_lines(),
         }
     }
 }
 
 impl Behavior {
+    /// The modes of `file_action_mode`, keymap commands and opener rules.
+    pub const COMMAND_MODES: [&'static str; 3] =
+        ["background", "capture", "foreground"];
+
+    pub const SCROLLINGS: [&'static str; 2] = ["center", "editor"];
+
     fn default_file_action() -> String {
         String::from("true") // do nothing!
     }
 
+    fn default_file_action_mode() -> String {
+        String::from("background")
+    }
+
     fn default_path_node_sort() -> String {
         String::from("dirs_top_simple")
     }
@@ -42,4 +101,48 @@ impl Behavior {
     fn default_quit_on_action() -> bool {
         false
     }
+
+    fn default_history_size() -> i32 {
+        100
+    }
+
+    fn default_persist_history() -> bool {
+        false
+    }
+
+    fn default_persist_bookmarks() -> bool {
+        true
+    }
+
+    fn default_restore_session() -> bool {
+        false
+    }
+
+    fn default_expand_all_max_depth() -> i32 {
+        5
+    }
+
+    fn default_expand_all_max_entries() -> i32 {
+        1000
+    }
+
+    fn default_key_sequence_timeout_ms() -> i32 {
+        1000
+    }
+
+    fn default_mouse() -> bool {
+        false
+    }
+
+    fn default_mouse_double_click_ms() -> i32 {
+        400
+    }
+
+    fn default_mouse_scroll_lines() -> i32 {
+        3
+    }
+
+    fn default_use_shell() -> bool {
+        true
+    }
 }
@@ -1,27 +1,124 @@
+use crate::model::config::keymap::Action;
+use crate::model::event::parse_key_sequence;
+use crate::model::event::Key;
 use serde::Deserialize;
+use serde::Serialize;
+use std::str::FromStr;
 
-#[derive(Clone, Debug, Deserialize)]
+/// The key sequences bound to an action. In the config file either a single
+/// string or a list of strings, on the command line separated by spaces.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(from = "KeysDef", into = "KeysDef")]
+pub struct Keys(pub Vec<String>);
+
+#[derive(Deserialize, Serialize)]
+#[serde(untagged)]
+enum KeysDef {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl From<KeysDef> for Keys {
+    fn from(keys_def: KeysDef) -> Self {
+        match keys_def {
+            KeysDef::One(key) => Keys(vec![key]),
+            KeysDef::Many(keys) => Keys(keys),
+        }
+    }
+}
+
+impl From<Keys> for KeysDef {
+    fn from(keys: Keys) -> Self {
+        match keys.0.as_slice() {
+            [key] => KeysDef::One(key.clone()),
+            _ => KeysDef::Many(keys.0),
+        }
+    }
+}
+
+impl From<&str> for Keys {
+    fn from(key: &str) -> Self {
+        Keys(vec![String::from(key)])
+    }
+}
+
+impl FromStr for Keys {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // keep a lone space
+        if s.trim().is_empty() {
+            return Ok(Keys::from(s));
+        }
+
+        Ok(Keys(s.split_whitespace().map(String::from).collect()))
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Keybinding {
     #[serde(default = "Keybinding::default_quit")]
-    pub quit: String,
+    pub quit: Keys,
 
     #[serde(default = "Keybinding::default_entry_up")]
-    pub entry_up: String,
+    pub entry_up: Keys,
 
     #[serde(default = "Keybinding::default_entry_down")]
-    pub entry_down: String,
+    pub entry_down: Keys,
 
     #[serde(default = "Keybinding::default_expand_dir")]
-    pub expand_dir: String,
+    pub expand_dir: Keys,
 
     #[serde(default = "Keybinding::default_collapse_dir")]
-    pub collapse_dir: String,
+    pub collapse_dir: Keys,
 
     #[serde(default = "Keybinding::default_file_action")]
-    pub file_action: String,
+    pub file_action: Keys,
 
     #[serde(default = "Keybinding::default_reload")]
-    pub reload: String,
+    pub reload: Keys,
+
+    #[serde(default = "Keybinding::default_enter_dir")]
+    pub enter_dir: Keys,
+
+    #[serde(default = "Keybinding::default_parent_dir")]
+    pub parent_dir: Keys,
+
+    #[serde(default = "Keybinding::default_history_back")]
+    pub history_back: Keys,
+
+    #[serde(default = "Keybinding::default_history_forward")]
+    pub history_forward: Keys,
+
+    #[serde(default = "Keybinding::default_bookmark_set")]
+    pub bookmark_set: Keys,
+
+    #[serde(default = "Keybinding::default_bookmark_jump")]
+    pub bookmark_jump: Keys,
+
+    #[serde(default = "Keybinding::default_bookmark_list")]
+    pub bookmark_list: Keys,
+
+    #[serde(default = "Keybinding::default_expand_all")]
+    pub expand_all: Keys,
+
+    #[serde(default = "Keybinding::default_collapse_all")]
+    pub collapse_all: Keys,
+
+    #[serde(default = "Keybinding::default_collapse_siblings")]
+    pub collapse_siblings: Keys,
+
+    #[serde(default = "Keybinding::default_command_prompt")]
+    pub command_prompt: Keys,
+
+    #[serde(default = "Keybinding::default_open_with")]
+    pub open_with: Keys,
+
+    #[serde(default = "Keybinding::default_suspend")]
+    pub suspend: Keys,
+
+    #[serde(default = "Keybinding::default_toggle_option")]
+    pub toggle_option: Keys,
 }
 
 impl Default for Keybinding {
@@ -34,36 +131,259 @@ impl Default for Keybinding {
             collapse_dir: Self::default_collapse_dir(),
             file_action: Self::default_file_action(),
             reload: Self::default_reload(),
+            enter_dir: Self::default_enter_dir(),
+            parent_dir: Self::default_parent_dir(),
+            history_back: Self::default_history_back(),
+            history_forward: Self::default_history_forward(),
+            bookmark_set: Self::default_bookmark_set(),
+            bookmark_jump: Self::default_bookmark_jump(),
+            bookmark_list: Self::default_bookmark_list(),
+            expand_all: Self::default_expand_all(),
+            collapse_all: Self::default_collapse_all(),
+            collapse_siblings: Self::default_collapse_siblings(),
+            command_prompt: Self::default_command_prompt(),
+            open_with: Self::default_open_with(),
+            suspend: Self::default_suspend(),
+            toggle_option: Self::default_toggle_option(),
         }
     }
 }
 
 impl Keybinding {
-    fn default_quit() -> String {
-        String::from("q")
+    /// The names of the built-in actions.
+    pub const ACTIONS: [&'static str; 21] = [
+        "bookmark_jump",
+        "bookmark_list",
+        "bookmark_set",
+        "collapse_all",
+        "collapse_dir",
+        "collapse_siblings",
+        "command_prompt",
+        "enter_dir",
+        "entry_down",
+        "entry_up",
+        "expand_all",
+        "expand_dir",
+        "file_action",
+        "history_back",
+        "history_forward",
+        "open_with",
+        "parent_dir",
+        "quit",
+        "reload",
+        "suspend",
+        "toggle_option",
+    ];
+
+    fn get_actions(&self) -> Vec<(&'static str, &Keys)> {
+        vec![
+            ("bookmark_jump", &self.bookmark_jump),
+            ("bookmark_list", &self.bookmark_list),
+            ("bookmark_set", &self.bookmark_set),
+            ("collapse_all", &self.collapse_all),
+            ("collapse_dir", &self.collapse_dir),
+            ("collapse_siblings", &self.collapse_siblings),
+            ("command_prompt", &self.command_prompt),
+            ("enter_dir", &self.enter_dir),
+            ("entry_down", &self.entry_down),
+            ("entry_up", &self.entry_up),
+            ("expand_all", &self.expand_all),
+            ("expand_dir", &self.expand_dir),
+            ("file_action", &self.file_action),
+            ("history_back", &self.history_back),
+            ("history_forward", &self.history_forward),
+            ("open_with", &self.open_with),
+            ("parent_dir", &self.parent_dir),
+            ("quit", &self.quit),
+            ("reload", &self.reload),
+            ("suspend", &self.suspend),
+            ("toggle_option", &self.toggle_option),
+        ]
+    }
+
+    /// Lists the actions with their key sequences, the first action wins when
+    /// a key sequence is bound more than once. Invalid key sequences are
+    /// skipped.
+    pub fn get_bindings(&self) -> Vec<(Action, Vec<Key>)> {
+        let mut bindings = Vec::new();
+        for (action, keys) in self.get_actions() {
+            for key_sequence in &keys.0 {
+                if let Ok(keys) = parse_key_sequence(key_sequence) {
+                    bindings.push((Action::Builtin(action), keys));
+                }
+            }
+        }
+        bindings
+    }
+
+    /// Describes every key sequence which cannot be parsed.
+    pub fn get_errors(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        for (action, keys) in self.get_actions() {
+            for key_sequence in &keys.0 {
+                if let Err(err) = parse_key_sequence(key_sequence) {
+                    errors.push(format!("keybinding.{}: {}", action, err));
+                }
+            }
+        }
+        errors
+    }
+
+    fn default_quit() -> Keys {
+        Keys::from("q")
     }
 
-    fn default_entry_up() -> String {
-        String::from("up")
+    fn default_entry_up() -> Keys {
+        Keys::from("up")
     }
 
-    fn default_entry_down() -> String {
-        String::from("down")
+    fn default_entry_down() -> Keys {
+        Keys::from("down")
     }
 
-    fn default_expand_dir() -> String {
-        String::from("right")
+    fn default_expand_dir() -> Keys {
+        Keys::from("right")
     }
 
-    fn default_collapse_dir() -> String {
-        String::from("left")
+    fn default_collapse_dir() -> Keys {
+        Keys::from("left")
     }
 
-    fn default_file_action() -> String {
-        String::from("return")
+    fn default_file_action() -> Keys {
+        Keys::from("return")
+    }
+
+    fn default_reload() -> Keys {
+        Keys::from("r")
+    }
+
+    fn default_enter_dir() -> Keys {
+        Keys::from("C")
+    }
+
+    fn default_parent_dir() -> Keys {
+        Keys::from("u")
+    }
+
+    fn default_history_back() -> Keys {
+        Keys::from("H")
+    }
+
+    fn default_history_forward() -> Keys {
+        Keys::from("L")
+    }
+
+    fn default_bookmark_set() -> Keys {
+        Keys::from("m")
+    }
+
+    fn default_bookmark_jump() -> Keys {
+        Keys::from("'")
+    }
+
+    fn default_bookmark_list() -> Keys {
+        Keys::from("B")
+    }
+
+    fn default_expand_all() -> Keys {
+        Keys::from("O")
+    }
+
+    fn default_collapse_all() -> Keys {
+        Keys::from("X")
+    }
+
+    fn default_collapse_siblings() -> Keys {
+        Keys::from("x")
+    }
+
+    fn default_command_prompt() -> Keys {
+        Keys::from(":")
+    }
+
+    fn default_open_with() -> Keys {
+        Keys::from("o")
+    }
+
+    fn default_suspend() -> Keys {
+        Keys::from("ctrl+z")
+    }
+
+    fn default_toggle_option() -> Keys {
+        Keys::from("t")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_from_str() {
+        assert_eq!(Keys::from("q"), "q".parse().unwrap());
+        assert_eq!(
+            Keys(vec![String::from("q"), String::from("ctrl+c")]),
+            " q  ctrl+c ".parse().unwrap()
+        );
+        assert_eq!(Keys::from(" "), " ".parse().unwrap());
+    }
+
+    #[test]
+    fn keys_from_toml() {
+        let keybinding: Keybinding =
+            toml::from_str("quit = \"Q\"\nreload = [\"r\", \"<space>r\"]")
+                .unwrap();
+
+        assert_eq!(Keys::from("Q"), keybinding.quit);
+        assert_eq!(
+            Keys(vec![String::from("r"), String::from("<space>r")]),
+            keybinding.reload
+        );
+        assert_eq!(Keys::from("u"), keybinding.parent_dir);
+    }
+
+    #[test]
+    fn errors() {
+        let keybinding = Keybinding {
+            quit: Keys(vec![String::from("q"), String::from("ctrl+qq")]),
+            reload: Keys::from("<hyper+r>"),
+            ..Keybinding::default()
+        };
+
+        assert_eq!(
+            vec![
+                "keybinding.quit: unknown key name 'qq' in key 'ctrl+qq'",
+                "keybinding.reload: unknown modifier 'hyper' in key 'hyper+r'",
+            ],
+            keybinding.get_errors()
+        );
+        assert_eq!(
+            vec![(Action::Builtin("quit"), vec![Key::from("q")])],
+            keybinding
+                .get_bindings()
+                .into_iter()
+                .filter(|(action, _)| {
+                    *action == Action::Builtin("quit")
+                        || *action == Action::Builtin("reload")
+                })
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn actions() {
+        let keybinding = Keybinding::default();
+        let names: Vec<&str> = keybinding
+            .get_actions()
+            .iter()
+            .map(|(name, _)| *name)
+            .collect();
+
+        assert_eq!(Keybinding::ACTIONS.to_vec(), names);
     }
 
-    fn default_reload() -> String {
-        String::from("r")
+    #[test]
+    fn no_default_errors() {
+        assert!(Keybinding::default().get_errors().is_empty());
     }
 }
@@ -0,0 +1,291 @@
+use crate::model::config::behavior::Behavior;
+use crate::model::config::keybinding::Keybinding;
+use crate::model::event::parse_key_sequence;
+use crate::model::event::Key;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// What a key sequence does.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Action {
+    Builtin(&'static str),
+
+    /// A command template and the mode to run it in, `None` for
+    /// `behavior.file_action_mode`.
+    Command(String, Option<String>),
+}
+
+impl Action {
+    /// Takes the names of built-in actions as they are and other values as
+    /// shell commands. A leading `!` marks a shell command explicitly, which
+    /// is required for a single lowercase word so that a mistyped action is
+    /// not run as a command.
+    pub fn parse(value: &str) -> Result<Self, String> {
+        if let Some(command) = value.strip_prefix('!') {
+            return Ok(Action::Command(String::from(command), None));
+        }
+
+        if let Some(name) =
+            Keybinding::ACTIONS.iter().find(|name| **name == value)
+        {
+            return Ok(Action::Builtin(name));
+        }
+
+        if value.chars().all(|c| c.is_ascii_lowercase() || c == '_') {
+            return Err(format!(
+                "unknown action '{}', prefix shell commands with '!'",
+                value
+            ));
+        }
+
+        Ok(Action::Command(String::from(value), None))
+    }
+}
+
+impl std::fmt::Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Action::Builtin(name) => write!(f, "{}", name),
+            Action::Command(command, _) => write!(f, "!{}", command),
+        }
+    }
+}
+
+/// Either an action as parsed by `Action::parse` or a table with a command
+/// and the mode to run it in.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum KeymapEntry {
+    Action(String),
+    Command {
+        command: String,
+        mode: Option<String>,
+    },
+}
+
+impl KeymapEntry {
+    fn get_action(&self) -> Result<Action, String> {
+        match self {
+            KeymapEntry::Action(value) => Action::parse(value),
+            KeymapEntry::Command {
+                mode: Some(mode), ..
+            } if !Behavior::COMMAND_MODES.contains(&mode.as_str()) => {
+                Err(format!(
+                    "invalid mode '{}', expected one of {}",
+                    mode,
+                    Behavior::COMMAND_MODES.join(", ")
+                ))
+            }
+            KeymapEntry::Command { command, mode } => {
+                Ok(Action::Command(command.clone(), mode.clone()))
+            }
+        }
+    }
+}
+
+impl From<String> for KeymapEntry {
+    fn from(value: String) -> Self {
+        KeymapEntry::Action(value)
+    }
+}
+
+/// Maps key sequences to actions, e.g. `o = "xdg-open %s"`.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(transparent)]
+pub struct Keymap(pub BTreeMap<String, KeymapEntry>);
+
+impl Keymap {
+    /// Lists the valid entries.
+    pub fn get_bindings(&self) -> Vec<(Action, Vec<Key>)> {
+        self.0
+            .iter()
+            .filter_map(|(key_sequence, entry)| {
+                let keys = parse_key_sequence(key_sequence).ok()?;
+                let action = entry.get_action().ok()?;
+                Some((action, keys))
+            })
+            .collect()
+    }
+
+    /// Describes every entry whose key sequence or action cannot be parsed.
+    pub fn get_errors(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        for (key_sequence, entry) in &self.0 {
+            let result = parse_key_sequence(key_sequence)
+                .and_then(|_| entry.get_action());
+            if let Err(err) = result {
+                errors.push(format!("keymap.{}: {}", key_sequence, err));
+            }
+        }
+        errors
+    }
+}
+
+/// Finds key sequences which are bound more than once or which are the start
+/// of a longer sequence, as the longer one can never be completed.
+pub fn get_conflicts(bindings: &[(Action, Vec<Key>)]) -> Vec<String> {
+    let mut conflicts = Vec::new();
+
+    for (i, (action, key_sequence)) in bindings.iter().enumerate() {
+        for (other_action, other_key_sequence) in &bindings[i + 1..] {
+            if key_sequence == other_key_sequence {
+                conflicts.push(format!(
+                    "{} and {} are bound to the same keys",
+                    action, other_action
+                ));
+            } else if other_key_sequence.starts_with(key_sequence) {
+                conflicts.push(format!(
+                    "the keys of {} start the keys of {}",
+                    action, other_action
+                ));
+            } else if key_sequence.starts_with(other_key_sequence) {
+                conflicts.push(format!(
+                    "the keys of {} start the keys of {}",
+                    other_action, action
+                ));
+            }
+        }
+    }
+
+    conflicts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_keymap(entries: &[(&str, &str)]) -> Keymap {
+        Keymap(
+            entries
+                .iter()
+                .map(|(key, value)| {
+                    (String::from(*key), String::from(*value).into())
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn parse_action() {
+        assert_eq!(Ok(Action::Builtin("quit")), Action::parse("quit"));
+        assert_eq!(
+            Ok(Action::Command(String::from("xdg-open %s"), None)),
+            Action::parse("xdg-open %s")
+        );
+        assert_eq!(
+            Ok(Action::Command(String::from("$EDITOR %s"), None)),
+            Action::parse("$EDITOR %s")
+        );
+        assert_eq!(
+            Ok(Action::Command(String::from("quit"), None)),
+            Action::parse("!quit")
+        );
+        assert!(Action::parse("qiut").is_err());
+    }
+
+    #[test]
+    fn from_toml() {
+        let keymap: Keymap = toml::from_str(
+            "o = \"xdg-open %s\"\n\"<space>q\" = \"quit\"\n\"ctrl+e\" = \"!make\"",
+        )
+        .unwrap();
+
+        assert_eq!(
+            vec![
+                (
+                    Action::Builtin("quit"),
+                    vec![Key::from(" "), Key::from("q")]
+                ),
+                (
+                    Action::Command(String::from("make"), None),
+                    vec![Key::from("ctrl+e")]
+                ),
+                (
+                    Action::Command(String::from("xdg-open %s"), None),
+                    vec![Key::from("o")]
+                ),
+            ],
+            keymap.get_bindings()
+        );
+    }
+
+    #[test]
+    fn command_table_from_toml() {
+        let keymap: Keymap = toml::from_str(
+            "e = { command = \"$EDITOR %s\", mode = \"foreground\" }\n\
+             m = { command = \"make\" }",
+        )
+        .unwrap();
+
+        assert_eq!(
+            vec![
+                (
+                    Action::Command(
+                        String::from("$EDITOR %s"),
+                        Some(String::from("foreground"))
+                    ),
+                    vec![Key::from("e")]
+                ),
+                (
+                    Action::Command(String::from("make"), None),
+                    vec![Key::from("m")]
+                ),
+            ],
+            keymap.get_bindings()
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let keymap =
+            get_keymap(&[("ctrl+", "quit"), ("q", "qiut"), ("x", "quit")]);
+
+        assert_eq!(
+            vec![
+                "keymap.ctrl+: unknown key name 'ctrl+' in key 'ctrl+'",
+                "keymap.q: unknown action 'qiut', prefix shell commands with '!'",
+            ],
+            keymap.get_errors()
+        );
+        assert_eq!(1, keymap.get_bindings().len());
+    }
+
+    #[test]
+    fn invalid_mode() {
+        let keymap: Keymap =
+            toml::from_str("e = { command = \"vi\", mode = \"tmux\" }\n")
+                .unwrap();
+
+        assert_eq!(
+            vec![
+                "keymap.e: invalid mode 'tmux', expected one of background, \
+                  capture, foreground"
+            ],
+            keymap.get_errors()
+        );
+    }
+
+    #[test]
+    fn conflicts() {
+        let bindings = vec![
+            (Action::Builtin("enter_dir"), vec![Key::from("g")]),
+            (
+                Action::Builtin("parent_dir"),
+                vec![Key::from("g"), Key::from("g")],
+            ),
+            (Action::Builtin("quit"), vec![Key::from("q")]),
+            (
+                Action::Command(String::from("ls"), None),
+                vec![Key::from("q")],
+            ),
+        ];
+
+        assert_eq!(
+            vec![
+                "the keys of enter_dir start the keys of parent_dir",
+                "quit and !ls are bound to the same keys",
+            ],
+            get_conflicts(&bindings)
+        );
+    }
+}
@@ -0,0 +1,279 @@
+use crate::model::config::keymap::Action;
+use crate::utils::canonicalize_or_keep;
+use crate::utils::get_config_dir;
+use std::ffi::OsString;
+use std::path::Path;
+use std::path::PathBuf;
+
+/// Names a config file which is read instead of all layers.
+pub const CONFIG_ENV_VAR: &str = "TWILIGHT_COMMANDER_CONFIG";
+
+const CONFIG_FILE_NAME: &str = "twilight-commander.toml";
+
+/// Searched in the working directory and its parents.
+const PROJECT_CONFIG_FILE_NAME: &str = ".twilight-commander.toml";
+
+/// The config file given by `--config`, or else by the environment variable.
+/// Arguments after `--` are no options.
+pub fn get_explicit_config_file(
+    args: &[String],
+) -> Result<Option<PathBuf>, String> {
+    get_explicit_config_file_from(args, std::env::var_os(CONFIG_ENV_VAR))
+}
+
+fn get_explicit_config_file_from(
+    args: &[String],
+    env_config_file: Option<OsString>,
+) -> Result<Option<PathBuf>, String> {
+    let mut config_arg = None;
+    for arg in args.iter().take_while(|arg| *arg != "--") {
+        if arg == "--config" || arg == "--config=" {
+            return Err(String::from(
+                "command line: --config needs a path, as in --config=<path>",
+            ));
+        }
+        if let Some(config_file) = arg.strip_prefix("--config=") {
+            config_arg = Some(config_file);
+        }
+    }
+
+    Ok(match config_arg {
+        Some(config_file) => Some(PathBuf::from(config_file)),
+        None => env_config_file
+            .filter(|config_file| !config_file.is_empty())
+            .map(PathBuf::from),
+    })
+}
+
+/// The system config files followed by the user config file, later files
+/// override earlier ones. The system directories are `$XDG_CONFIG_DIRS`,
+/// most important first, or else `/etc/xdg`.
+pub fn get_base_config_files() -> Vec<PathBuf> {
+    let system_dirs = std::env::var("XDG_CONFIG_DIRS")
+        .ok()
+        .filter(|system_dirs| !system_dirs.is_empty())
+        .unwrap_or_else(|| String::from("/etc/xdg"));
+
+    let mut config_files: Vec<PathBuf> = system_dirs
+        .split(':')
+        .filter(|system_dir| !system_dir.is_empty())
+        .rev()
+        .map(|system_dir| {
+            Path::new(system_dir)
+                .join("twilight-commander")
+                .join(CONFIG_FILE_NAME)
+        })
+        .collect();
+
+    if let Ok(config_dir) = get_config_dir() {
+        config_files.push(Path::new(&config_dir).join(CONFIG_FILE_NAME));
+    }
+
+    config_files
+}
+
+/// Finds the nearest project config file in the working directory or one of
+/// its parents.
+pub fn find_project_config_file(working_dir: &Path) -> Option<PathBuf> {
+    canonicalize_or_keep(working_dir)
+        .ancestors()
+        .map(|dir| dir.join(PROJECT_CONFIG_FILE_NAME))
+        .find(|config_file| config_file.is_file())
+}
+
+/// Removes the options which run commands from a project config layer, as the
+/// file may come with any repository: `behavior.file_action`, the commands of
+/// the keymap and the opener rules. Returns the removed keys.
+pub fn remove_commands(layer: &mut toml::Value) -> Vec<String> {
+    let mut removed_keys = Vec::new();
+    let table = match layer.as_table_mut() {
+        Some(table) => table,
+        None => return removed_keys,
+    };
+
+    if let Some(behavior) = table
+        .get_mut("behavior")
+        .and_then(toml::Value::as_table_mut)
+    {
+        if behavior.remove("file_action").is_some() {
+            removed_keys.push(String::from("behavior.file_action"));
+        }
+    }
+
+    if let Some(keymap) =
+        table.get_mut("keymap").and_then(toml::Value::as_table_mut)
+    {
+        let command_keys: Vec<String> = keymap
+            .iter()
+            .filter(|(_, entry)| match entry {
+                toml::Value::String(value) => {
+                    matches!(Action::parse(value), Ok(Action::Command(..)))
+                }
+                _ => true,
+            })
+            .map(|(key_sequence, _)| key_sequence.clone())
+            .collect();
+        for key_sequence in command_keys {
+            keymap.remove(&key_sequence);
+            removed_keys.push(format!("keymap.{}", key_sequence));
+        }
+    }
+
+    if table.remove("opener").is_some() {
+        removed_keys.push(String::from("opener"));
+    }
+
+    removed_keys
+}
+
+/// Merges the tables of the layer into the base recursively, any other value
+/// of the layer replaces the one of the base, including arrays.
+pub fn merge(base: &mut toml::Value, layer: toml::Value) {
+    match (base, layer) {
+        (toml::Value::Table(base_table), toml::Value::Table(layer_table)) => {
+            for (key, layer_value) in layer_table {
+                match base_table.get_mut(&key) {
+                    Some(base_value) => merge(base_value, layer_value),
+                    None => {
+                        base_table.insert(key, layer_value);
+                    }
+                }
+            }
+        }
+        (base, layer) => *base = layer,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::write_file;
+    use std::fs::create_dir_all;
+    use std::fs::remove_dir_all;
+
+    fn to_args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| String::from(*arg)).collect()
+    }
+
+    #[test]
+    fn explicit_config_file() {
+        let env_config_file = Some(OsString::from("env.toml"));
+
+        let args = to_args(&["--config=a.toml", "--config=b.toml", "--"]);
+        assert_eq!(
+            Ok(Some(PathBuf::from("b.toml"))),
+            get_explicit_config_file_from(&args, env_config_file.clone())
+        );
+
+        let args = to_args(&["--", "--config=a.toml"]);
+        assert_eq!(
+            Ok(Some(PathBuf::from("env.toml"))),
+            get_explicit_config_file_from(&args, env_config_file)
+        );
+        assert_eq!(
+            Ok(None),
+            get_explicit_config_file_from(&args, Some(OsString::new()))
+        );
+        assert_eq!(Ok(None), get_explicit_config_file_from(&args, None));
+
+        let args = to_args(&["--config", "a.toml"]);
+        assert!(get_explicit_config_file_from(&args, None).is_err());
+
+        let args = to_args(&["--config="]);
+        assert!(get_explicit_config_file_from(&args, None).is_err());
+
+        let args = to_args(&["--", "--config"]);
+        assert_eq!(Ok(None), get_explicit_config_file_from(&args, None));
+    }
+
+    #[test]
+    fn project_config_file() {
+        let project_dir = std::env::temp_dir()
+            .join(format!("twilight-commander-project-{}", std::process::id()));
+        let working_dir = project_dir.join("src/model");
+        create_dir_all(&working_dir).unwrap();
+        let config_file = project_dir.join(".twilight-commander.toml");
+        write_file(config_file.to_str().unwrap(), "").unwrap();
+
+        let found_config_file = find_project_config_file(&working_dir);
+        remove_dir_all(&project_dir).unwrap();
+
+        assert_eq!(Some(canonicalize_or_keep(&config_file)), found_config_file);
+    }
+
+    #[test]
+    fn project_commands_removed() {
+        let mut layer: toml::Value = toml::from_str(
+            "[behavior]\n\
+             file_action = \"touch pwned\"\n\
+             mouse = true\n\
+             [keymap]\n\
+             a = \"quit\"\n\
+             b = \"!ls\"\n\
+             c = \"touch pwned\"\n\
+             d = { command = \"touch pwned\" }\n\
+             [[opener]]\n\
+             command = \"touch pwned\"\n",
+        )
+        .unwrap();
+
+        let removed_keys = remove_commands(&mut layer);
+
+        assert_eq!(
+            vec![
+                "behavior.file_action",
+                "keymap.b",
+                "keymap.c",
+                "keymap.d",
+                "opener"
+            ],
+            removed_keys
+        );
+        let expected: toml::Value = toml::from_str(
+            "[behavior]\n\
+             mouse = true\n\
+             [keymap]\n\
+             a = \"quit\"\n",
+        )
+        .unwrap();
+        assert_eq!(expected, layer);
+    }
+
+    #[test]
+    fn merge_layers() {
+        let mut base: toml::Value = toml::from_str(
+            "[behavior]\n\
+             mouse = true\n\
+             scrolling = \"editor\"\n\
+             [keymap]\n\
+             a = \"quit\"\n\
+             [[opener]]\n\
+             command = \"a\"\n",
+        )
+        .unwrap();
+        let layer: toml::Value = toml::from_str(
+            "[behavior]\n\
+             mouse = false\n\
+             [keymap]\n\
+             b = \"reload\"\n\
+             [[opener]]\n\
+             command = \"b\"\n",
+        )
+        .unwrap();
+
+        merge(&mut base, layer);
+
+        let expected: toml::Value = toml::from_str(
+            "[behavior]\n\
+             mouse = false\n\
+             scrolling = \"editor\"\n\
+             [keymap]\n\
+             a = \"quit\"\n\
+             b = \"reload\"\n\
+             [[opener]]\n\
+             command = \"b\"\n",
+        )
+        .unwrap();
+        assert_eq!(expected, base);
+    }
+}
@@ -0,0 +1,191 @@
+use crate::model::mime_type::detect_mime_type;
+use crate::model::mime_type::matches_mime_type;
+use serde::Deserialize;
+use std::path::Path;
+
+/// Opens files matching all given conditions with a command, a rule without
+/// conditions matches every file.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct OpenerRule {
+    /// Shown in the open with menu instead of the command.
+    pub name: Option<String>,
+
+    /// A pattern for the file name, `*` matches any characters and `?` a
+    /// single one.
+    pub glob: Option<String>,
+
+    /// Compared case-insensitively, without the leading dot.
+    pub extension: Option<String>,
+
+    /// Detected by the first bytes of the file, e.g. `image/png` or
+    /// `image/*`.
+    pub mime: Option<String>,
+
+    /// A command template, see `command_template`.
+    pub command: String,
+
+    /// How the command is run, `None` for `behavior.file_action_mode`.
+    pub mode: Option<String>,
+}
+
+impl OpenerRule {
+    /// The keys of a rule in the config file.
+    pub const KEYS: [&'static str; 6] =
+        ["name", "glob", "extension", "mime", "command", "mode"];
+
+    pub fn get_name(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.command)
+    }
+
+    /// The MIME type is only detected when the other conditions match.
+    pub fn matches(&self, path: &Path) -> bool {
+        let file_name = path
+            .file_name()
+            .map(|file_name| file_name.to_string_lossy())
+            .unwrap_or_default();
+
+        if let Some(glob) = &self.glob {
+            if !matches_glob(glob, &file_name) {
+                return false;
+            }
+        }
+
+        if let Some(extension) = &self.extension {
+            let file_extension = path
+                .extension()
+                .map(|extension| extension.to_string_lossy())
+                .unwrap_or_default();
+            if !file_extension
+                .eq_ignore_ascii_case(extension.trim_start_matches('.'))
+            {
+                return false;
+            }
+        }
+
+        if let Some(mime) = &self.mime {
+            match detect_mime_type(path) {
+                Some(mime_type) if matches_mime_type(mime, mime_type) => {}
+                _ => return false,
+            }
+        }
+
+        true
+    }
+}
+
+/// Lists the rules matching the file in the configured order.
+pub fn get_matching_rules<'a>(
+    rules: &'a [OpenerRule],
+    path: &Path,
+) -> Vec<&'a OpenerRule> {
+    rules.iter().filter(|rule| rule.matches(path)).collect()
+}
+
+/// Matches the whole text, `*` matches any characters including none and `?`
+/// exactly one.
+pub fn matches_glob(glob: &str, text: &str) -> bool {
+    let glob: Vec<char> = glob.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let mut glob_index = 0;
+    let mut text_index = 0;
+    // the last star and the text index it was tried with
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while text_index < text.len() {
+        match glob.get(glob_index) {
+            Some('*') => {
+                backtrack = Some((glob_index, text_index));
+                glob_index += 1;
+            }
+            Some(c) if *c == '?' || *c == text[text_index] => {
+                glob_index += 1;
+                text_index += 1;
+            }
+            _ => match backtrack {
+                Some((star_index, star_text_index)) => {
+                    backtrack = Some((star_index, star_text_index + 1));
+                    glob_index = star_index + 1;
+                    text_index = star_text_index + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+
+    glob[glob_index..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(
+        glob: Option<&str>,
+        extension: Option<&str>,
+        mime: Option<&str>,
+    ) -> OpenerRule {
+        OpenerRule {
+            name: None,
+            glob: glob.map(String::from),
+            extension: extension.map(String::from),
+            mime: mime.map(String::from),
+            command: String::from("true"),
+            mode: None,
+        }
+    }
+
+    #[test]
+    fn glob() {
+        assert!(matches_glob("*.rs", "main.rs"));
+        assert!(matches_glob("*", ""));
+        assert!(matches_glob("file?", "file1"));
+        assert!(matches_glob("*a*b*", "xxaxxbxx"));
+        assert!(matches_glob("Cargo.*", "Cargo.toml"));
+        assert!(!matches_glob("*.rs", "main.rs.bak"));
+        assert!(!matches_glob("file?", "file"));
+        assert!(!matches_glob("*a*b", "xxaxxbxx"));
+    }
+
+    #[test]
+    fn extension() {
+        let rule = rule(None, Some("toml"), None);
+
+        assert!(rule.matches(Path::new("Cargo.toml")));
+        assert!(rule.matches(Path::new("CARGO.TOML")));
+        assert!(!rule.matches(Path::new("Cargo.lock")));
+        assert!(!rule.matches(Path::new("toml")));
+    }
+
+    #[test]
+    fn all_conditions_must_match() {
+        let rule = rule(Some("Cargo*"), Some(".toml"), Some("text/*"));
+
+        assert!(rule.matches(Path::new("./Cargo.toml")));
+        assert!(!rule.matches(Path::new("./twilight-commander.toml")));
+        assert!(!rule.matches(Path::new("./tests/test_dirs/file0")));
+    }
+
+    #[test]
+    fn matching_rules() {
+        let rules = vec![
+            rule(None, None, Some("inode/x-empty")),
+            rule(Some("*.rs"), None, None),
+            rule(None, None, None),
+        ];
+
+        let matching_rules =
+            get_matching_rules(&rules, Path::new("./tests/test_dirs/file0"));
+
+        assert_eq!(vec![&rules[0], &rules[2]], matching_rules);
+    }
+
+    #[test]
+    fn name() {
+        let mut rule = rule(None, None, None);
+        assert_eq!("true", rule.get_name());
+
+        rule.name = Some(String::from("nothing"));
+        assert_eq!("nothing", rule.get_name());
+    }
+}
@@ -0,0 +1,444 @@
+use crate::model::config::Config;
+use crate::model::config::OptionError;
+
+/// Every option with its description, in the order of the default config.
+/// The values and types are taken from `Config::default()` and
+/// `Config::set_option`, so only the descriptions are maintained here.
+const OPTIONS: [(&str, &str); 48] = [
+    (
+        "behavior.file_action",
+        "command interpreted by bash when pressing the file_action key,\n\
+         placeholders like %s or %{d} are replaced by shell-quoted paths",
+    ),
+    (
+        "behavior.file_action_mode",
+        "how commands are run, the mode can be set per opener rule and\n\
+         keymap command",
+    ),
+    (
+        "behavior.use_shell",
+        "run commands by bash, when false they are split into arguments and\n\
+         run directly",
+    ),
+    (
+        "behavior.quit_on_action",
+        "when true quits after a command was started",
+    ),
+    (
+        "behavior.path_node_sort",
+        "determines the compare function used for sorting entries",
+    ),
+    ("behavior.scrolling", "the scrolling algorithm used"),
+    (
+        "behavior.history_size",
+        "the maximum number of locations stored in the navigation history",
+    ),
+    (
+        "behavior.persist_history",
+        "when true stores the navigation history in the config directory on\n\
+         quit",
+    ),
+    (
+        "behavior.persist_bookmarks",
+        "when true bookmarks are loaded from and stored in the config\n\
+         directory",
+    ),
+    (
+        "behavior.restore_session",
+        "when true expanded directories and the cursor are stored on quit and\n\
+         restored on the next start in the same working directory",
+    ),
+    (
+        "behavior.expand_all_max_depth",
+        "the maximum number of directory levels opened by the expand_all key",
+    ),
+    (
+        "behavior.expand_all_max_entries",
+        "the expand_all key stops opening directories after listing this\n\
+         many entries",
+    ),
+    (
+        "behavior.key_sequence_timeout_ms",
+        "the time in milliseconds an incomplete key sequence waits for the\n\
+         next key, 0 waits forever",
+    ),
+    (
+        "behavior.mouse",
+        "when true the mouse selects entries, toggles directories with a\n\
+         click on the expand marker, performs the file action with a double\n\
+         click and scrolls",
+    ),
+    (
+        "behavior.mouse_double_click_ms",
+        "the maximum time in milliseconds between the clicks of a double\n\
+         click",
+    ),
+    (
+        "behavior.mouse_scroll_lines",
+        "the number of entries the cursor moves per mouse wheel step",
+    ),
+    (
+        "color.background",
+        "the background color as hex rgb, not applied yet",
+    ),
+    (
+        "color.foreground",
+        "the foreground color as hex rgb, not applied yet",
+    ),
+    ("composition.indent", "indention used for subentries"),
+    (
+        "composition.show_indent",
+        "when true shows visual markers for indention whitespaces",
+    ),
+    ("composition.use_utf8", "when true uses utf8 characters"),
+    ("debug.enabled", "enables debug mode"),
+    (
+        "debug.padding_bot",
+        "the minimum distance of the highlighted entry to the bottom spacing",
+    ),
+    (
+        "debug.padding_top",
+        "the minimum distance of the highlighted entry to the top spacing",
+    ),
+    (
+        "debug.spacing_bot",
+        "the number of lines at the bottom not used for entries",
+    ),
+    (
+        "debug.spacing_top",
+        "the number of lines at the top not used for entries",
+    ),
+    (
+        "keybinding.bookmark_jump",
+        "followed by a character: jump to the entry bookmarked under that mark",
+    ),
+    ("keybinding.bookmark_list", "list all bookmarks"),
+    (
+        "keybinding.bookmark_set",
+        "followed by a character: bookmark the entry under that mark",
+    ),
+    ("keybinding.collapse_all", "collapse all directories"),
+    (
+        "keybinding.collapse_dir",
+        "collapse an entry directory or jump to its parent",
+    ),
+    (
+        "keybinding.collapse_siblings",
+        "collapse all directories except the ones containing the entry",
+    ),
+    ("keybinding.command_prompt", "open the command prompt in the footer"),
+    ("keybinding.entry_down", "move an entry down"),
+    ("keybinding.entry_up", "move an entry up"),
+    (
+        "keybinding.enter_dir",
+        "change the root directory to the directory entry",
+    ),
+    ("keybinding.expand_all", "recursively expand an entry directory"),
+    ("keybinding.expand_dir", "expand an entry directory"),
+    ("keybinding.file_action", "perform the file action"),
+    (
+        "keybinding.history_back",
+        "go back to the previous location in the navigation history",
+    ),
+    (
+        "keybinding.history_forward",
+        "go forward in the navigation history",
+    ),
+    (
+        "keybinding.open_with",
+        "list the commands of all opener rules matching the file",
+    ),
+    (
+        "keybinding.parent_dir",
+        "change the root directory to its parent directory",
+    ),
+    ("keybinding.quit", "quit"),
+    (
+        "keybinding.reload",
+        "collapse all directories and reload the root directory",
+    ),
+    ("keybinding.suspend", "suspend to the shell"),
+    (
+        "keybinding.toggle_option",
+        "list common boolean options to toggle",
+    ),
+    ("setup.working_dir", "the working directory used when starting"),
+];
+
+/// The sections in the order of the default config, with a comment below
+/// their header. Keymap and opener rules have no defaults, only examples.
+const SECTIONS: [(&str, &str); 8] = [
+    ("behavior", ""),
+    ("color", ""),
+    ("composition", ""),
+    ("debug", ""),
+    (
+        "keybinding",
+        "every action accepts a key, a key sequence like \"gg\" or\n\
+         \"<space>f\", or a list of them like [\"q\", \"ctrl+c\"]",
+    ),
+    (
+        "keymap",
+        "binds key sequences to built-in actions or to shell commands, %s is\n\
+         replaced by the path of the entry under the cursor, a leading !\n\
+         marks a command\n\
+         o = \"xdg-open %s\"\n\
+         \"<space>q\" = \"quit\"\n\
+         e = { command = \"$EDITOR %s\", mode = \"foreground\" }",
+    ),
+    (
+        "opener",
+        "opener rules choose the file action by glob, extension or detected\n\
+         MIME type, the first matching rule wins, otherwise\n\
+         behavior.file_action is used\n\
+         [[opener]]\n\
+         mime = \"image/*\"\n\
+         command = \"feh %s\"\n\
+         mode = \"background\"",
+    ),
+    ("setup", ""),
+];
+
+/// The command line flags which are no options.
+const FLAGS: [(&str, &str); 4] = [
+    (
+        "--config=<path>",
+        "read only this config file instead of the system, user and project\n\
+         config files, also set by TWILIGHT_COMMANDER_CONFIG",
+    ),
+    (
+        "--check-config",
+        "load the config, print its errors and warnings and exit",
+    ),
+    (
+        "--print-default-config",
+        "print a config file with every option set to its default",
+    ),
+    ("--help, --version", "print this help"),
+];
+
+/// The default value of every option, as it is written in a config file.
+fn get_default_values() -> toml::value::Table {
+    let config = Config::default();
+    let sections = [
+        ("behavior", toml::Value::try_from(&config.behavior)),
+        ("color", toml::Value::try_from(&config.color)),
+        ("composition", toml::Value::try_from(&config.composition)),
+        ("debug", toml::Value::try_from(&config.debug)),
+        ("keybinding", toml::Value::try_from(&config.keybinding)),
+        ("setup", toml::Value::try_from(&config.setup)),
+    ];
+
+    let mut default_values = toml::value::Table::new();
+    for (section, value) in sections.iter() {
+        let options = match value {
+            Ok(toml::Value::Table(options)) => options,
+            _ => continue,
+        };
+        for (key, value) in options {
+            default_values
+                .insert(format!("{}.{}", section, key), value.clone());
+        }
+    }
+
+    default_values
+}
+
+/// The values of an enum option, found by setting an invalid one.
+fn get_enum_values(key: &str) -> Option<&'static [&'static str]> {
+    match Config::default().set_option(key, "") {
+        Err(OptionError::Invalid(values)) => Some(values),
+        _ => None,
+    }
+}
+
+fn get_type(key: &str, value: &toml::Value) -> String {
+    if let Some(values) = get_enum_values(key) {
+        return values.join("|");
+    }
+
+    let type_name = if key.starts_with("keybinding.") {
+        "keys"
+    } else {
+        match value {
+            toml::Value::Boolean(_) => "bool",
+            toml::Value::Integer(_) => "integer",
+            _ => "string",
+        }
+    };
+    String::from(type_name)
+}
+
+/// The value like it is written on the command line.
+fn format_argument(value: &toml::Value) -> String {
+    match value {
+        toml::Value::String(value) => value.clone(),
+        toml::Value::Array(values) => values
+            .iter()
+            .map(format_argument)
+            .collect::<Vec<String>>()
+            .join(" "),
+        value => value.to_string(),
+    }
+}
+
+fn push_comment(text: &mut String, comment: &str, prefix: &str) {
+    for line in comment.lines() {
+        text.push_str(prefix);
+        text.push_str(line);
+        text.push('\n');
+    }
+}
+
+/// A config file with every option set to its default and described, as
+/// printed by `--print-default-config`.
+pub fn get_default_config() -> String {
+    let default_values = get_default_values();
+    let mut text = String::from(
+        "# the default config of twilight-commander, printed by\n\
+         # twilight-commander --print-default-config\n",
+    );
+
+    for (section, comment) in SECTIONS.iter() {
+        text.push('\n');
+        if *section != "opener" {
+            text.push_str(&format!("[{}]\n", section));
+        }
+        push_comment(&mut text, comment, "# ");
+
+        let prefix = format!("{}.", section);
+        for (key, description) in OPTIONS.iter() {
+            if !key.starts_with(&prefix) {
+                continue;
+            }
+
+            if !text.ends_with(&format!("[{}]\n", section)) {
+                text.push('\n');
+            }
+            push_comment(&mut text, description, "# ");
+            if let Some(values) = get_enum_values(key) {
+                text.push_str(&format!("# enum: {}\n", values.join(", ")));
+            }
+            text.push_str(&format!(
+                "{} = {}\n",
+                &key[prefix.len()..],
+                default_values[*key]
+            ));
+        }
+    }
+
+    text
+}
+
+/// The usage with every option, its type and default, as printed by
+/// `--help`.
+pub fn get_help() -> String {
+    let default_values = get_default_values();
+    let mut text = format!(
+        "{} {}\nusage: twilight-commander [--key1=value1 --key2=value2 ...]\n\n",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION"),
+    );
+
+    for (flag, description) in FLAGS.iter() {
+        text.push_str(&format!("  {}\n", flag));
+        push_comment(&mut text, description, "      ");
+    }
+
+    text.push_str(
+        "\noptions, also set in the config files by [section] and key:\n",
+    );
+    for (key, description) in OPTIONS.iter() {
+        let value = &default_values[*key];
+        text.push_str(&format!(
+            "  --{}=<{}> (default: {})\n",
+            key,
+            get_type(key, value),
+            format_argument(value)
+        ));
+        push_comment(&mut text, description, "      ");
+    }
+
+    text.push_str("  --keymap.<keys>=<action or command>\n");
+    push_comment(
+        &mut text,
+        "bind a key sequence to a built-in action or to a command",
+        "      ",
+    );
+
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::config::get_unknown_keys;
+    use crate::utils::read_file;
+
+    #[test]
+    fn every_option_described() {
+        let default_values = get_default_values();
+
+        for key in default_values.keys() {
+            assert!(
+                OPTIONS.iter().any(|(option, _)| option == key),
+                "{} has no description",
+                key
+            );
+        }
+        for (key, _) in OPTIONS.iter() {
+            assert!(default_values.contains_key(*key), "{} has no value", key);
+            assert!(Config::is_option(key), "{} is no option", key);
+        }
+    }
+
+    #[test]
+    fn default_config() {
+        let default_config = get_default_config();
+
+        let config: Config = toml::from_str(&default_config).unwrap();
+        assert!(config.get_errors().is_empty());
+
+        let value: toml::Value = toml::from_str(&default_config).unwrap();
+        assert_eq!(Vec::<String>::new(), get_unknown_keys(&value));
+
+        assert_eq!(
+            default_config,
+            read_file("./twilight-commander.toml").unwrap(),
+            "the shipped config differs from --print-default-config"
+        );
+    }
+
+    #[test]
+    fn vim_config() {
+        let vim_config = read_file("./twilight-commander-vim.toml").unwrap();
+
+        let config: Config = toml::from_str(&vim_config).unwrap();
+        assert!(config.get_errors().is_empty());
+
+        let value: toml::Value = toml::from_str(&vim_config).unwrap();
+        assert_eq!(Vec::<String>::new(), get_unknown_keys(&value));
+
+        for key in get_default_values().keys() {
+            let (section, option) = key.split_at(key.find('.').unwrap());
+            assert!(
+                value[section].get(&option[1..]).is_some(),
+                "{} is missing",
+                key
+            );
+        }
+    }
+
+    #[test]
+    fn help() {
+        let help = get_help();
+
+        assert!(help.contains(
+            "  --behavior.scrolling=<center|editor> (default: center)\n"
+        ));
+        assert!(
+            help.contains("  --composition.indent=<integer> (default: 2)\n")
+        );
+        assert!(help.contains("  --keybinding.quit=<keys> (default: q)\n"));
+    }
+}
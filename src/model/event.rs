@@ -1,3 +1,11 @@
+use crate::model::path_node::PathNode;
+use key_spec::format_key_spec;
+use key_spec::parse_key_spec;
+use std::path::PathBuf;
+use std::process::ExitStatus;
+
+mod key_spec;
+
 type TEvent = termion::event::Event;
 type TKey = termion::event::Key;
 
@@ -6,10 +14,72 @@ pub struct Key {
     inner: termion::event::Event,
 }
 
+/// The result of a directory listing done in a worker thread, `children` is
+/// `None` when the directory could not be read.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DirListing {
+    pub id: usize,
+    pub path: PathBuf,
+    pub children: Option<Vec<PathNode>>,
+}
+
+/// The end of a command run in the background, `stdout` is only captured in
+/// the capture mode.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CommandExit {
+    pub name: String,
+    pub status: ExitStatus,
+    pub stdout: Option<Vec<u8>>,
+    pub stderr: Vec<u8>,
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum Event {
+    CommandExit(CommandExit),
+    ConfigFileChange,
+    Continue,
+    DirListing(DirListing),
     Resize,
     Key(Key),
+    KeySequenceTimeout(usize),
+    Mouse(termion::event::MouseEvent),
+    Suspend,
+    Terminate,
+}
+
+impl Key {
+    pub fn get_char(&self) -> Option<char> {
+        match self.inner {
+            TEvent::Key(TKey::Char(c)) => Some(c),
+            _ => None,
+        }
+    }
+
+    /// The key for text editing, `None` for other events.
+    pub fn get_termion_key(&self) -> Option<TKey> {
+        match self.inner {
+            TEvent::Key(key) => Some(key),
+            _ => None,
+        }
+    }
+}
+
+impl std::str::FromStr for Key {
+    type Err = String;
+
+    fn from_str(key_spec: &str) -> Result<Self, Self::Err> {
+        parse_key_spec(key_spec).map(Key::from)
+    }
+}
+
+/// Shows the name of the key, e.g. `ctrl+up`.
+impl std::fmt::Display for Key {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match format_key_spec(&self.inner) {
+            Some(key_spec) => write!(f, "{}", key_spec),
+            None => write!(f, "unknown"),
+        }
+    }
 }
 
 impl From<TEvent> for Key {
@@ -18,85 +88,89 @@ impl From<TEvent> for Key {
     }
 }
 
+#[cfg(test)]
 impl From<&str> for Key {
-    fn from(s: &str) -> Key {
-        Key::from(convert_str_to_termion_event(s))
+    fn from(key_spec: &str) -> Key {
+        key_spec.parse().unwrap()
     }
 }
 
+#[cfg(test)]
 impl From<String> for Key {
-    fn from(s: String) -> Key {
-        Key::from(convert_str_to_termion_event(&s))
+    fn from(key_spec: String) -> Key {
+        key_spec.parse().unwrap()
     }
 }
 
-fn convert_str_to_termion_event(s: &str) -> TEvent {
-    if s.chars().count() == 1 {
-        return TEvent::Key(TKey::Char(s.chars().last().unwrap()));
-    }
-
-    if s.starts_with("alt+") && s.len() == 5 {
-        return TEvent::Key(TKey::Alt(s.chars().last().unwrap()));
-    }
-
-    if s.starts_with("ctrl+") && s.len() == 6 {
-        return TEvent::Key(TKey::Ctrl(s.chars().last().unwrap()));
-    }
-
-    match s {
-        // f keys
-        "f1" => TEvent::Key(TKey::F(1)),
-        "f2" => TEvent::Key(TKey::F(2)),
-        "f3" => TEvent::Key(TKey::F(3)),
-        "f4" => TEvent::Key(TKey::F(4)),
-        "f5" => TEvent::Key(TKey::F(5)),
-        "f6" => TEvent::Key(TKey::F(6)),
-        "f7" => TEvent::Key(TKey::F(7)),
-        "f8" => TEvent::Key(TKey::F(8)),
-        "f9" => TEvent::Key(TKey::F(9)),
-        "f10" => TEvent::Key(TKey::F(10)),
-        "f11" => TEvent::Key(TKey::F(11)),
-        "f12" => TEvent::Key(TKey::F(12)),
-
-        // special keys
-        "backspace" => TEvent::Key(TKey::Backspace),
-        "left" => TEvent::Key(TKey::Left),
-        "right" => TEvent::Key(TKey::Right),
-        "up" => TEvent::Key(TKey::Up),
-        "down" => TEvent::Key(TKey::Down),
-        "home" => TEvent::Key(TKey::Home),
-        "end" => TEvent::Key(TKey::End),
-        "page_up" => TEvent::Key(TKey::PageUp),
-        "page_down" => TEvent::Key(TKey::PageDown),
-        "delete" => TEvent::Key(TKey::Delete),
-        "insert" => TEvent::Key(TKey::Insert),
-        "esc" => TEvent::Key(TKey::Esc),
-        "return" => TEvent::Key(TKey::Char('\n')),
-        "tab" => TEvent::Key(TKey::Char('\t')),
-
-        // special key combinations
-
-        // arrow keys
-        "ctrl+left" => TEvent::Unsupported(vec![27, 91, 49, 59, 53, 68]),
-        "ctrl+right" => TEvent::Unsupported(vec![27, 91, 49, 59, 53, 67]),
-        "ctrl+up" => TEvent::Unsupported(vec![27, 91, 49, 59, 53, 65]),
-        "ctrl+down" => TEvent::Unsupported(vec![27, 91, 49, 59, 53, 66]),
-        "shift+left" => TEvent::Unsupported(vec![27, 91, 49, 59, 50, 68]),
-        "shift+right" => TEvent::Unsupported(vec![27, 91, 49, 59, 50, 67]),
-        "shift+up" => TEvent::Unsupported(vec![27, 91, 49, 59, 50, 65]),
-        "shift+down" => TEvent::Unsupported(vec![27, 91, 49, 59, 50, 66]),
-        "alt+shift+left" => TEvent::Unsupported(vec![27, 91, 49, 59, 52, 68]),
-        "alt+shift+right" => TEvent::Unsupported(vec![27, 91, 49, 59, 52, 67]),
-        "alt+shift+up" => TEvent::Unsupported(vec![27, 91, 49, 59, 52, 65]),
-        "alt+shift+down" => TEvent::Unsupported(vec![27, 91, 49, 59, 52, 66]),
-        "shift+alt+left" => TEvent::Unsupported(vec![27, 91, 49, 59, 52, 68]),
-        "shift+alt+right" => TEvent::Unsupported(vec![27, 91, 49, 59, 52, 67]),
-        "shift+alt+up" => TEvent::Unsupported(vec![27, 91, 49, 59, 52, 65]),
-        "shift+alt+down" => TEvent::Unsupported(vec![27, 91, 49, 59, 52, 66]),
-
-        // default
-        _ => TEvent::Unsupported(Vec::new()),
+/// Parses a key sequence like `gg` or `<space>f`. A string which names a
+/// single key, e.g. `up` or `ctrl+x`, is that key, otherwise every character
+/// is a key and `<...>` names a key within the sequence. A word of three or
+/// more lowercase letters is always a key name, as a sequence it needs a key
+/// in brackets, e.g. `<d>dd`.
+pub fn parse_key_sequence(s: &str) -> Result<Vec<Key>, String> {
+    if !s.contains('<') {
+        match s.parse() {
+            Ok(key) => return Ok(vec![key]),
+            Err(err) if looks_like_key_spec(s) => return Err(err),
+            Err(_) => {}
+        }
+    }
+
+    let mut keys = Vec::new();
+    let mut rest = s;
+
+    while let Some(c) = rest.chars().next() {
+        let key_spec = if c == '<' {
+            rest[1..].find('>').map(|end| &rest[1..=end])
+        } else {
+            None
+        };
+
+        match key_spec {
+            Some(key_spec) if !key_spec.is_empty() => {
+                let key = match key_spec {
+                    "lt" => Key::from(TEvent::Key(TKey::Char('<'))),
+                    _ => key_spec.parse()?,
+                };
+                keys.push(key);
+                rest = &rest[key_spec.len() + 2..];
+            }
+            _ => {
+                keys.push(Key::from(TEvent::Key(TKey::Char(c))));
+                rest = &rest[c.len_utf8()..];
+            }
+        }
     }
+
+    Ok(keys)
+}
+
+/// Formats keys like `parse_key_sequence` expects them. The first key is put
+/// in brackets when the sequence would be read as a key name otherwise.
+pub fn format_key_sequence(keys: &[Key]) -> String {
+    let key_specs: Vec<String> = keys
+        .iter()
+        .map(|key| match key.to_string() {
+            key_spec if key_spec == "<" => String::from("<lt>"),
+            key_spec if key_spec.chars().count() == 1 => key_spec,
+            key_spec => format!("<{}>", key_spec),
+        })
+        .collect();
+
+    let text = key_specs.concat();
+    if keys.len() > 1 && parse_key_sequence(&text).as_deref() != Ok(keys) {
+        return format!("<{}>{}", keys[0], key_specs[1..].concat());
+    }
+    text
+}
+
+/// Tells if a string that is no valid key was meant as one, e.g. `ctrl+upp`,
+/// `page_dwn` or `retrun`, rather than as a sequence of characters.
+fn looks_like_key_spec(s: &str) -> bool {
+    s.contains('+')
+        || s.contains('_')
+        || (s.len() > 2 && s.chars().all(|c| c.is_ascii_lowercase()))
+        || (s.starts_with('f') && s.len() > 1 && s[1..].parse::<u8>().is_ok())
 }
 
 #[cfg(test)]
@@ -116,82 +190,101 @@ mod tests {
     #[test]
     fn key_from_str_test() {
         assert_eq!(
-            Key::from("shift+alt+down"),
-            Key {
+            Ok(Key {
                 inner: TEvent::Unsupported(vec![27, 91, 49, 59, 52, 66])
-            }
+            }),
+            "shift+alt+down".parse()
         );
+        assert!("nonsense".parse::<Key>().is_err());
     }
 
     #[test]
-    fn key_from_string_test() {
+    fn key_get_char_test() {
+        assert_eq!(Some('x'), Key::from("x").get_char());
+        assert_eq!(None, Key::from("alt+x").get_char());
+        assert_eq!(None, Key::from("up").get_char());
+    }
+
+    #[test]
+    fn key_display_test() {
+        let display_texts: Vec<String> = ["x", "space", "alt+x", "f5", "up"]
+            .iter()
+            .map(|s| Key::from(*s).to_string())
+            .collect();
+
+        assert_eq!(vec!["x", "space", "alt+x", "f5", "up"], display_texts);
         assert_eq!(
-            Key::from(String::from("shift+alt+up")),
-            Key {
-                inner: TEvent::Unsupported(vec![27, 91, 49, 59, 52, 65])
-            }
+            "unknown",
+            Key::from(TEvent::Unsupported(Vec::new())).to_string()
         );
     }
 
-    mod convert_str_to_termion_event_tests {
+    mod parse_key_sequence_tests {
         use super::super::*;
-        #[test]
-        fn nonsense() {
-            assert_eq!(
-                TEvent::Unsupported(Vec::new()),
-                convert_str_to_termion_event("x1")
-            );
-            assert_eq!(
-                TEvent::Unsupported(Vec::new()),
-                convert_str_to_termion_event("alt+x1")
-            );
-            assert_eq!(
-                TEvent::Unsupported(Vec::new()),
-                convert_str_to_termion_event("ctrl+x1")
-            );
+
+        fn parse(s: &str) -> Vec<Key> {
+            parse_key_sequence(s).unwrap()
         }
 
         #[test]
-        fn single_digit() {
-            assert_eq!(
-                TEvent::Key(TKey::Char('x')),
-                convert_str_to_termion_event("x")
-            );
+        fn single_key() {
+            assert_eq!(vec![Key::from("g")], parse("g"));
+            assert_eq!(vec![Key::from("up")], parse("up"));
+            assert_eq!(vec![Key::from("ctrl+x")], parse("ctrl+x"));
+            assert_eq!(vec![Key::from("<")], parse("<"));
         }
+
         #[test]
-        fn alt_digit() {
+        fn sequence() {
+            assert_eq!(vec![Key::from("g"), Key::from("g")], parse("gg"));
+            assert_eq!(vec![Key::from(" "), Key::from("f")], parse("<space>f"));
             assert_eq!(
-                TEvent::Key(TKey::Alt('x')),
-                convert_str_to_termion_event("alt+x")
+                vec![Key::from("alt+x"), Key::from("<"), Key::from("d")],
+                parse("<alt+x><lt>d")
             );
         }
+
         #[test]
-        fn ctrl_digit() {
+        fn unclosed_brackets() {
             assert_eq!(
-                TEvent::Key(TKey::Ctrl('x')),
-                convert_str_to_termion_event("ctrl+x")
+                vec![Key::from("<"), Key::from(">"), Key::from("a")],
+                parse("<>a")
             );
-        }
-        #[test]
-        fn f_key() {
             assert_eq!(
-                TEvent::Key(TKey::F(5)),
-                convert_str_to_termion_event("f5")
+                vec![Key::from("<"), Key::from("a"), Key::from("b")],
+                parse("<ab")
             );
         }
+
         #[test]
-        fn special_key() {
-            assert_eq!(
-                TEvent::Key(TKey::PageDown),
-                convert_str_to_termion_event("page_down")
-            );
+        fn invalid() {
+            assert!(parse_key_sequence("ctrl+upp").is_err());
+            assert!(parse_key_sequence("page_dwn").is_err());
+            assert!(parse_key_sequence("f99").is_err());
+            assert!(parse_key_sequence("g<spce>").is_err());
+            for typo in &["retrun", "pageup", "escpae", "spcae", "tba"] {
+                assert!(parse_key_sequence(typo).is_err(), "{}", typo);
+            }
         }
+
         #[test]
-        fn special_key_comination() {
+        fn letters_in_brackets() {
             assert_eq!(
-                TEvent::Unsupported(vec![27, 91, 49, 59, 52, 65]),
-                convert_str_to_termion_event("alt+shift+up")
+                vec![Key::from("d"), Key::from("d"), Key::from("d")],
+                parse("<d>dd")
             );
         }
     }
+
+    #[test]
+    fn format_key_sequence_test() {
+        let keys = parse_key_sequence("<space>g<lt><ctrl+up>").unwrap();
+
+        assert_eq!("<space>g<lt><ctrl+up>", format_key_sequence(&keys));
+
+        for key_sequence in &["gg", "<d>dd", "<u>p", "<e>nd"] {
+            let keys = parse_key_sequence(key_sequence).unwrap();
+            assert_eq!(*key_sequence, format_key_sequence(&keys));
+        }
+    }
 }
@@ -0,0 +1,418 @@
+//! Translates key names like `ctrl+alt+up` or `f13` into the events termion
+//! reports for them and back. Modified keys without a termion event are
+//! matched by the escape sequences xterm sends for them.
+
+type TEvent = termion::event::Event;
+type TKey = termion::event::Key;
+
+const SHIFT: u8 = 1;
+const ALT: u8 = 2;
+const CTRL: u8 = 4;
+
+/// Keys which are sent as `CSI number;modifiers final` when modified.
+const CSI_KEYS: [(&str, TKey, u8, char); 22] = [
+    ("up", TKey::Up, 1, 'A'),
+    ("down", TKey::Down, 1, 'B'),
+    ("right", TKey::Right, 1, 'C'),
+    ("left", TKey::Left, 1, 'D'),
+    ("home", TKey::Home, 1, 'H'),
+    ("end", TKey::End, 1, 'F'),
+    ("insert", TKey::Insert, 2, '~'),
+    ("delete", TKey::Delete, 3, '~'),
+    ("page_up", TKey::PageUp, 5, '~'),
+    ("page_down", TKey::PageDown, 6, '~'),
+    ("f1", TKey::F(1), 1, 'P'),
+    ("f2", TKey::F(2), 1, 'Q'),
+    ("f3", TKey::F(3), 1, 'R'),
+    ("f4", TKey::F(4), 1, 'S'),
+    ("f5", TKey::F(5), 15, '~'),
+    ("f6", TKey::F(6), 17, '~'),
+    ("f7", TKey::F(7), 18, '~'),
+    ("f8", TKey::F(8), 19, '~'),
+    ("f9", TKey::F(9), 20, '~'),
+    ("f10", TKey::F(10), 21, '~'),
+    ("f11", TKey::F(11), 23, '~'),
+    ("f12", TKey::F(12), 24, '~'),
+];
+
+/// Keys which only accept alt as modifier, with the character sent after the
+/// escape character in that case.
+const PLAIN_KEYS: [(&str, TKey, char); 4] = [
+    ("backspace", TKey::Backspace, '\x7f'),
+    ("esc", TKey::Esc, '\x1b'),
+    ("return", TKey::Char('\n'), '\r'),
+    ("tab", TKey::Char('\t'), '\t'),
+];
+
+const SHIFT_TAB: &[u8] = b"\x1b[Z";
+
+/// Parses a key name with optional modifiers, e.g. `x`, `ctrl+x`,
+/// `alt+shift+page_up` or `f13`. The modifiers may come in any order.
+pub fn parse_key_spec(key_spec: &str) -> Result<TEvent, String> {
+    let (modifier_names, name) = split_key_spec(key_spec);
+
+    let mut modifiers = 0;
+    for modifier_name in modifier_names {
+        modifiers |= match modifier_name.to_lowercase().as_str() {
+            "shift" => SHIFT,
+            "alt" => ALT,
+            "ctrl" => CTRL,
+            _ => {
+                return Err(format!(
+                    "unknown modifier '{}' in key '{}'",
+                    modifier_name, key_spec
+                ))
+            }
+        };
+    }
+
+    let mut chars = name.chars();
+    let event = match (chars.next(), chars.next()) {
+        (Some(c), None) => parse_char_key(c, modifiers),
+        _ => parse_named_key(&name.to_lowercase(), modifiers),
+    };
+
+    event.map_err(|reason| format!("{} in key '{}'", reason, key_spec))
+}
+
+/// Formats an event like `parse_key_spec` expects it, `None` when the event
+/// has no name.
+pub fn format_key_spec(event: &TEvent) -> Option<String> {
+    match event {
+        TEvent::Key(key) => format_termion_key(*key),
+        TEvent::Unsupported(bytes) => format_escape_sequence(bytes),
+        TEvent::Mouse(_) => None,
+    }
+}
+
+fn split_key_spec(key_spec: &str) -> (Vec<&str>, &str) {
+    let (prefix, name) = if key_spec.len() > 1 && key_spec.ends_with("++") {
+        (&key_spec[..key_spec.len() - 2], "+")
+    } else {
+        match key_spec.rfind('+') {
+            Some(i) if i > 0 && i < key_spec.len() - 1 => {
+                (&key_spec[..i], &key_spec[i + 1..])
+            }
+            _ => ("", key_spec),
+        }
+    };
+
+    if prefix.is_empty() {
+        (Vec::new(), name)
+    } else {
+        (prefix.split('+').collect(), name)
+    }
+}
+
+fn parse_char_key(c: char, modifiers: u8) -> Result<TEvent, String> {
+    let c = if modifiers & SHIFT != 0 {
+        if !c.is_alphabetic() {
+            return Err(format!("shift cannot modify '{}'", c));
+        }
+        c.to_uppercase().next().unwrap_or(c)
+    } else {
+        c
+    };
+
+    let c = if modifiers & CTRL != 0 {
+        match c.to_ascii_lowercase() {
+            l @ 'a'..='z' => (l as u8 - b'a' + 1) as char,
+            d @ '4'..='7' => (d as u8 - b'4' + 0x1c) as char,
+            ' ' | '2' | '@' => '\0',
+            _ => return Err(format!("ctrl cannot modify '{}'", c)),
+        }
+    } else {
+        c
+    };
+
+    if modifiers & ALT != 0 {
+        return Ok(TEvent::Key(TKey::Alt(c)));
+    }
+
+    // the same translation termion does for single bytes
+    let key = match c {
+        '\t' => TKey::Char('\t'),
+        '\n' | '\r' => TKey::Char('\n'),
+        '\0' => TKey::Null,
+        '\x01'..='\x1a' => TKey::Ctrl((c as u8 - 1 + b'a') as char),
+        '\x1c'..='\x1f' => TKey::Ctrl((c as u8 - 0x1c + b'4') as char),
+        _ => TKey::Char(c),
+    };
+
+    Ok(TEvent::Key(key))
+}
+
+fn parse_named_key(name: &str, modifiers: u8) -> Result<TEvent, String> {
+    let name = match name {
+        "enter" => "return",
+        "escape" => "esc",
+        _ => name,
+    };
+
+    if name == "space" {
+        return parse_char_key(' ', modifiers);
+    }
+
+    if let Some((_, key, alt_char)) =
+        PLAIN_KEYS.iter().find(|(key_name, _, _)| *key_name == name)
+    {
+        return match modifiers {
+            0 => Ok(TEvent::Key(*key)),
+            ALT => Ok(TEvent::Key(TKey::Alt(*alt_char))),
+            SHIFT if name == "tab" => {
+                Ok(TEvent::Unsupported(SHIFT_TAB.to_vec()))
+            }
+            _ => Err(format!("'{}' only accepts alt as modifier", name)),
+        };
+    }
+
+    // terminals send f13 to f24 as shift+f1 to shift+f12
+    let (name, modifiers) = match get_function_key_number(name) {
+        Some(n) if (13..=24).contains(&n) => {
+            (format!("f{}", n - 12), modifiers | SHIFT)
+        }
+        _ => (String::from(name), modifiers),
+    };
+
+    let (_, key, number, final_char) = CSI_KEYS
+        .iter()
+        .find(|(key_name, _, _, _)| *key_name == name)
+        .ok_or_else(|| format!("unknown key name '{}'", name))?;
+
+    if modifiers == 0 {
+        Ok(TEvent::Key(*key))
+    } else {
+        let escape_sequence =
+            format!("\x1b[{};{}{}", number, 1 + modifiers, final_char);
+        Ok(TEvent::Unsupported(escape_sequence.into_bytes()))
+    }
+}
+
+fn get_function_key_number(name: &str) -> Option<u8> {
+    name.strip_prefix('f')?.parse().ok()
+}
+
+fn format_char(c: char) -> Option<String> {
+    match c {
+        ' ' => Some(String::from("space")),
+        '\n' => Some(String::from("return")),
+        '\t' => Some(String::from("tab")),
+        _ if c.is_control() => None,
+        _ => Some(c.to_string()),
+    }
+}
+
+fn format_termion_key(key: TKey) -> Option<String> {
+    let key_spec = match key {
+        TKey::Char(c) => return format_char(c),
+        TKey::Alt('\r') => String::from("alt+return"),
+        TKey::Alt('\t') => String::from("alt+tab"),
+        TKey::Alt('\x7f') => String::from("alt+backspace"),
+        TKey::Alt('\x1b') => String::from("alt+esc"),
+        TKey::Alt('\0') => String::from("ctrl+alt+space"),
+        TKey::Alt(c @ '\x01'..='\x1a') => {
+            format!("ctrl+alt+{}", (c as u8 - 1 + b'a') as char)
+        }
+        TKey::Alt(c @ '\x1c'..='\x1f') => {
+            format!("ctrl+alt+{}", (c as u8 - 0x1c + b'4') as char)
+        }
+        TKey::Alt(c) => format!("alt+{}", format_char(c)?),
+        TKey::Ctrl(c) => format!("ctrl+{}", c),
+        TKey::Null => String::from("ctrl+space"),
+        TKey::Backspace => String::from("backspace"),
+        TKey::Esc => String::from("esc"),
+        _ => {
+            let (name, _, _, _) =
+                CSI_KEYS.iter().find(|(_, csi_key, _, _)| *csi_key == key)?;
+            String::from(*name)
+        }
+    };
+
+    Some(key_spec)
+}
+
+fn format_escape_sequence(bytes: &[u8]) -> Option<String> {
+    if bytes == SHIFT_TAB {
+        return Some(String::from("shift+tab"));
+    }
+
+    let text = std::str::from_utf8(bytes).ok()?;
+    if !text.starts_with("\x1b[") || text.len() < 4 {
+        return None;
+    }
+
+    let final_char = text.chars().last()?;
+    let parameters = &text[2..text.len() - 1];
+    let mut parameters = parameters.split(';');
+    let number: u8 = parameters.next()?.parse().ok()?;
+    let modifiers = parameters.next()?.parse::<u8>().ok()?.checked_sub(1)?;
+    if parameters.next().is_some() || modifiers == 0 || modifiers > 7 {
+        return None;
+    }
+
+    let (name, _, _, _) =
+        CSI_KEYS.iter().find(|(_, _, csi_number, csi_char)| {
+            *csi_number == number && *csi_char == final_char
+        })?;
+
+    let (name, modifiers) = match get_function_key_number(name) {
+        Some(n) if modifiers & SHIFT != 0 => {
+            (format!("f{}", n + 12), modifiers & !SHIFT)
+        }
+        _ => (String::from(*name), modifiers),
+    };
+
+    let mut key_spec = String::new();
+    if modifiers & CTRL != 0 {
+        key_spec.push_str("ctrl+");
+    }
+    if modifiers & ALT != 0 {
+        key_spec.push_str("alt+");
+    }
+    if modifiers & SHIFT != 0 {
+        key_spec.push_str("shift+");
+    }
+    key_spec.push_str(&name);
+
+    Some(key_spec)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(key_spec: &str) -> TEvent {
+        parse_key_spec(key_spec).unwrap()
+    }
+
+    #[test]
+    fn chars() {
+        assert_eq!(TEvent::Key(TKey::Char('x')), parse("x"));
+        assert_eq!(TEvent::Key(TKey::Char('+')), parse("+"));
+        assert_eq!(TEvent::Key(TKey::Char('X')), parse("shift+x"));
+        assert_eq!(TEvent::Key(TKey::Char(' ')), parse("space"));
+    }
+
+    #[test]
+    fn modified_chars() {
+        assert_eq!(TEvent::Key(TKey::Ctrl('x')), parse("ctrl+x"));
+        assert_eq!(TEvent::Key(TKey::Ctrl('x')), parse("ctrl+X"));
+        assert_eq!(TEvent::Key(TKey::Alt('x')), parse("alt+x"));
+        assert_eq!(TEvent::Key(TKey::Alt('X')), parse("shift+alt+x"));
+        assert_eq!(TEvent::Key(TKey::Alt('\x18')), parse("ctrl+alt+x"));
+        assert_eq!(TEvent::Key(TKey::Alt('+')), parse("alt++"));
+        assert_eq!(TEvent::Key(TKey::Null), parse("ctrl+space"));
+        assert_eq!(TEvent::Key(TKey::Char('\t')), parse("ctrl+i"));
+    }
+
+    #[test]
+    fn named_keys() {
+        assert_eq!(TEvent::Key(TKey::PageDown), parse("page_down"));
+        assert_eq!(TEvent::Key(TKey::Char('\n')), parse("Enter"));
+        assert_eq!(TEvent::Key(TKey::F(12)), parse("f12"));
+        assert_eq!(TEvent::Key(TKey::Alt('\r')), parse("alt+return"));
+        assert_eq!(TEvent::Unsupported(b"\x1b[Z".to_vec()), parse("shift+tab"));
+    }
+
+    #[test]
+    fn modified_named_keys() {
+        assert_eq!(
+            TEvent::Unsupported(vec![27, 91, 49, 59, 53, 68]),
+            parse("ctrl+left")
+        );
+        assert_eq!(
+            TEvent::Unsupported(vec![27, 91, 49, 59, 52, 65]),
+            parse("alt+shift+up")
+        );
+        assert_eq!(parse("alt+shift+up"), parse("shift+alt+up"));
+        assert_eq!(
+            TEvent::Unsupported(b"\x1b[5;7~".to_vec()),
+            parse("ctrl+alt+page_up")
+        );
+        assert_eq!(TEvent::Unsupported(b"\x1b[1;3P".to_vec()), parse("alt+f1"));
+        assert_eq!(
+            TEvent::Unsupported(b"\x1b[24;6~".to_vec()),
+            parse("ctrl+shift+f12")
+        );
+    }
+
+    #[test]
+    fn high_function_keys() {
+        assert_eq!(TEvent::Unsupported(b"\x1b[1;2P".to_vec()), parse("f13"));
+        assert_eq!(
+            TEvent::Unsupported(b"\x1b[24;6~".to_vec()),
+            parse("ctrl+f24")
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            Err(String::from(
+                "unknown key name 'nonsense' in key 'nonsense'"
+            )),
+            parse_key_spec("nonsense")
+        );
+        assert_eq!(
+            Err(String::from("unknown modifier 'hyper' in key 'hyper+x'")),
+            parse_key_spec("hyper+x")
+        );
+        assert_eq!(
+            Err(String::from("unknown key name 'f25' in key 'f25'")),
+            parse_key_spec("f25")
+        );
+        assert!(parse_key_spec("ctrl+").is_err());
+        assert!(parse_key_spec("ctrl+%").is_err());
+        assert!(parse_key_spec("shift+1").is_err());
+        assert!(parse_key_spec("ctrl+return").is_err());
+    }
+
+    #[test]
+    fn format_round_trip() {
+        let key_specs = [
+            "x",
+            "+",
+            "X",
+            "space",
+            "return",
+            "tab",
+            "shift+tab",
+            "ctrl+x",
+            "ctrl+space",
+            "alt+x",
+            "alt+X",
+            "alt+space",
+            "alt+return",
+            "ctrl+alt+x",
+            "backspace",
+            "esc",
+            "up",
+            "ctrl+up",
+            "ctrl+alt+shift+home",
+            "page_down",
+            "alt+delete",
+            "f1",
+            "f12",
+            "alt+f4",
+            "f13",
+            "ctrl+f24",
+        ];
+
+        for key_spec in &key_specs {
+            assert_eq!(
+                Some(String::from(*key_spec)),
+                format_key_spec(&parse(key_spec)),
+            );
+        }
+    }
+
+    #[test]
+    fn format_unknown() {
+        assert_eq!(None, format_key_spec(&TEvent::Unsupported(Vec::new())));
+        assert_eq!(
+            None,
+            format_key_spec(&TEvent::Unsupported(b"\x1b[99;5~".to_vec()))
+        );
+        assert_eq!(None, format_key_spec(&TEvent::Key(TKey::Char('\x07'))));
+    }
+}
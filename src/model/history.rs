@@ -0,0 +1,312 @@
+use crate::model::config::Config;
+use crate::utils::read_file;
+use crate::utils::write_file;
+use log::{info, warn};
+use serde::Deserialize;
+use serde::Serialize;
+use std::path::Path;
+use std::path::PathBuf;
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Location {
+    pub root: PathBuf,
+    pub cursor: PathBuf,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+struct HistoryFile {
+    position: usize,
+    locations: Vec<Location>,
+}
+
+/// A bounded back/forward stack of visited locations, like in a browser.
+/// `position == locations.len()` means that the current location has not
+/// been stored yet.
+#[derive(Clone, Debug, PartialEq)]
+pub struct History {
+    locations: Vec<Location>,
+    max_size: usize,
+    /// None when the history is not persisted.
+    file_path: Option<PathBuf>,
+    position: usize,
+}
+
+impl History {
+    pub fn new(config: &Config, config_dir: Option<&Path>) -> Self {
+        info!("initializing history");
+
+        let mut history = Self {
+            locations: Vec::new(),
+            max_size: config.behavior.history_size.max(1) as usize,
+            file_path: config_dir
+                .filter(|_| config.behavior.persist_history)
+                .map(|config_dir| config_dir.join("history.toml")),
+            position: 0,
+        };
+
+        if let Some(file_path) = &history.file_path {
+            match Self::read_history_file(file_path) {
+                Ok(history_file) => {
+                    history.locations = history_file.locations;
+                    history.position =
+                        history_file.position.min(history.locations.len());
+                    history.truncate_front();
+                }
+                Err(err) => warn!("could not read the history file: {}", err),
+            }
+        }
+
+        history
+    }
+
+    /// Stores the location which is left by a jump and drops all forward
+    /// locations.
+    pub fn push(&mut self, location: Location) {
+        self.locations.truncate(self.position);
+        self.locations.push(location);
+        self.position = self.locations.len();
+        self.truncate_front();
+    }
+
+    /// Stores the current location for going forward again, which may drop
+    /// the oldest location.
+    pub fn back(&mut self, current: Location) -> Option<Location> {
+        if self.position == 0 {
+            return None;
+        }
+
+        if self.position == self.locations.len() {
+            self.locations.push(current);
+        } else {
+            self.locations[self.position] = current;
+        }
+
+        self.position -= 1;
+        let location = self.locations[self.position].clone();
+        self.truncate_front();
+        Some(location)
+    }
+
+    pub fn forward(&mut self, current: Location) -> Option<Location> {
+        if self.position + 1 >= self.locations.len() {
+            return None;
+        }
+
+        self.locations[self.position] = current;
+
+        self.position += 1;
+        Some(self.locations[self.position].clone())
+    }
+
+    pub fn save(&self) {
+        let file_path = match &self.file_path {
+            Some(file_path) => file_path,
+            None => return,
+        };
+
+        if let Err(err) =
+            Self::write_history_file(file_path, &self.get_history_file())
+        {
+            warn!("could not write the history file: {}", err);
+        }
+    }
+
+    /// The locations as they are stored. toml strings cannot hold paths which
+    /// are not valid UTF-8, such locations are skipped.
+    fn get_history_file(&self) -> HistoryFile {
+        let mut history_file = HistoryFile {
+            position: self.position,
+            locations: Vec::new(),
+        };
+
+        for (index, location) in self.locations.iter().enumerate() {
+            if location.root.to_str().is_some()
+                && location.cursor.to_str().is_some()
+            {
+                history_file.locations.push(location.clone());
+            } else {
+                warn!("not storing the non UTF-8 location {:?}", location);
+                if index < self.position {
+                    history_file.position -= 1;
+                }
+            }
+        }
+
+        history_file
+    }
+
+    fn truncate_front(&mut self) {
+        if self.locations.len() > self.max_size {
+            let overflow = self.locations.len() - self.max_size;
+            self.locations.drain(0..overflow);
+            self.position = self.position.saturating_sub(overflow);
+        }
+    }
+
+    fn read_history_file(file_path: &Path) -> std::io::Result<HistoryFile> {
+        let history_file_content = read_file(file_path)?;
+
+        toml::from_str(&history_file_content).map_err(|err| {
+            std::io::Error::new(std::io::ErrorKind::InvalidData, err)
+        })
+    }
+
+    fn write_history_file(
+        file_path: &Path,
+        history_file: &HistoryFile,
+    ) -> std::io::Result<()> {
+        let history_file_content =
+            toml::to_string(history_file).map_err(|err| {
+                std::io::Error::new(std::io::ErrorKind::InvalidData, err)
+            })?;
+
+        write_file(file_path, &history_file_content)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::OsStr;
+    use std::fs::create_dir_all;
+    use std::fs::remove_dir_all;
+    use std::os::unix::ffi::OsStrExt;
+
+    fn location(name: &str) -> Location {
+        Location {
+            root: PathBuf::from("/"),
+            cursor: PathBuf::from(format!("/{}", name)),
+        }
+    }
+
+    fn prepare_history(max_size: i32) -> History {
+        let mut config = Config::default();
+        config.behavior.history_size = max_size;
+        config.behavior.persist_history = false;
+        History::new(&config, None)
+    }
+
+    #[test]
+    fn persist_history() {
+        let config_dir = std::env::temp_dir()
+            .join(format!("twilight-commander-history-{}", std::process::id()));
+        create_dir_all(&config_dir).unwrap();
+        let mut config = Config::default();
+        config.behavior.persist_history = true;
+
+        let mut history = History::new(&config, Some(&config_dir));
+        history.push(location("a"));
+        history.push(location("b"));
+        history.back(location("c"));
+        history.save();
+        let read_history = History::new(&config, Some(&config_dir));
+        remove_dir_all(&config_dir).unwrap();
+
+        assert_eq!(history, read_history);
+    }
+
+    #[test]
+    fn empty() {
+        let mut history = prepare_history(10);
+
+        assert_eq!(None, history.back(location("a")));
+        assert_eq!(None, history.forward(location("a")));
+    }
+
+    #[test]
+    fn back_and_forward() {
+        let mut history = prepare_history(10);
+        history.push(location("a"));
+        history.push(location("b"));
+
+        assert_eq!(Some(location("b")), history.back(location("c")));
+        assert_eq!(Some(location("a")), history.back(location("b")));
+        assert_eq!(None, history.back(location("a")));
+        assert_eq!(Some(location("b")), history.forward(location("a")));
+        assert_eq!(Some(location("c")), history.forward(location("b")));
+        assert_eq!(None, history.forward(location("c")));
+    }
+
+    #[test]
+    fn back_stores_moved_cursor() {
+        let mut history = prepare_history(10);
+        history.push(location("a"));
+
+        history.back(location("b"));
+        assert_eq!(Some(location("b")), history.forward(location("a2")));
+        assert_eq!(Some(location("a2")), history.back(location("b")));
+    }
+
+    #[test]
+    fn push_drops_forward_locations() {
+        let mut history = prepare_history(10);
+        history.push(location("a"));
+        history.push(location("b"));
+        history.back(location("c"));
+        history.back(location("b"));
+
+        history.push(location("a"));
+
+        assert_eq!(None, history.forward(location("d")));
+        assert_eq!(Some(location("a")), history.back(location("d")));
+    }
+
+    #[test]
+    fn bounded() {
+        let mut history = prepare_history(2);
+        history.push(location("a"));
+        history.push(location("b"));
+        history.push(location("c"));
+
+        assert_eq!(Some(location("c")), history.back(location("d")));
+        assert_eq!(None, history.back(location("c")));
+        assert_eq!(Some(location("d")), history.forward(location("c")));
+    }
+
+    #[test]
+    fn bounded_back() {
+        let mut history = prepare_history(2);
+        history.push(location("a"));
+        history.push(location("b"));
+
+        assert_eq!(Some(location("b")), history.back(location("c")));
+        assert_eq!(vec![location("b"), location("c")], history.locations);
+        assert_eq!(0, history.position);
+        assert_eq!(None, history.back(location("b")));
+        assert_eq!(Some(location("c")), history.forward(location("b")));
+    }
+
+    #[test]
+    fn serialize_history_file() {
+        let history_file = HistoryFile {
+            position: 1,
+            locations: vec![location("a"), location("b")],
+        };
+
+        let content = toml::to_string(&history_file).unwrap();
+
+        assert_eq!(history_file, toml::from_str(&content).unwrap());
+    }
+
+    #[test]
+    fn skip_non_utf8_locations() {
+        let non_utf8_location = Location {
+            root: PathBuf::from("/"),
+            cursor: PathBuf::from(OsStr::from_bytes(b"/dir\xff")),
+        };
+        let mut history = prepare_history(10);
+        history.push(location("a"));
+        history.push(non_utf8_location);
+        history.push(location("b"));
+        history.back(location("c"));
+
+        let history_file = history.get_history_file();
+
+        assert_eq!(
+            vec![location("a"), location("b"), location("c")],
+            history_file.locations
+        );
+        assert_eq!(1, history_file.position);
+        assert!(toml::to_string(&history_file).is_ok());
+    }
+}
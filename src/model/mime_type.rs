@@ -0,0 +1,195 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// Magic bytes at the given offset.
+type MagicBytes = (usize, &'static [u8]);
+
+/// The magic bytes, which all have to match, and the MIME type they identify.
+const SIGNATURES: [(&[MagicBytes], &str); 21] = [
+    (&[(0, b"%PDF-")], "application/pdf"),
+    (&[(0, b"\x89PNG\r\n\x1a\n")], "image/png"),
+    (&[(0, b"\xff\xd8\xff")], "image/jpeg"),
+    (&[(0, b"GIF87a")], "image/gif"),
+    (&[(0, b"GIF89a")], "image/gif"),
+    (&[(0, b"RIFF"), (8, b"WEBP")], "image/webp"),
+    (&[(0, b"BM"), (6, b"\0\0\0\0")], "image/bmp"),
+    (&[(0, b"ID3")], "audio/mpeg"),
+    (&[(0, b"OggS")], "audio/ogg"),
+    (&[(0, b"fLaC")], "audio/flac"),
+    (&[(0, b"RIFF"), (8, b"WAVE")], "audio/wav"),
+    (&[(4, b"ftyp")], "video/mp4"),
+    (&[(0, b"\x1a\x45\xdf\xa3")], "video/x-matroska"),
+    (&[(0, b"PK\x03\x04")], "application/zip"),
+    (&[(0, b"\x1f\x8b")], "application/gzip"),
+    (&[(0, b"BZh")], "application/x-bzip2"),
+    (&[(0, b"\xfd7zXZ\x00")], "application/x-xz"),
+    (&[(0, b"7z\xbc\xaf\x27\x1c")], "application/x-7z-compressed"),
+    (&[(257, b"ustar")], "application/x-tar"),
+    (&[(0, b"\x7fELF")], "application/x-executable"),
+    (&[(0, b"SQLite format 3\x00")], "application/vnd.sqlite3"),
+];
+
+/// Detects the MIME type of a file by its first bytes, `None` when the file
+/// cannot be read or is no regular file, as reading a FIFO or a device could
+/// block.
+pub fn detect_mime_type(path: &Path) -> Option<&'static str> {
+    if !std::fs::metadata(path).ok()?.is_file() {
+        return None;
+    }
+
+    let mut head = Vec::new();
+    File::open(path)
+        .ok()?
+        .take(512)
+        .read_to_end(&mut head)
+        .ok()?;
+
+    Some(detect_mime_type_of_bytes(&head))
+}
+
+fn detect_mime_type_of_bytes(head: &[u8]) -> &'static str {
+    if head.is_empty() {
+        return "inode/x-empty";
+    }
+
+    for (magic_bytes, mime_type) in SIGNATURES.iter() {
+        if magic_bytes.iter().all(|(offset, magic_bytes)| {
+            head.get(*offset..offset + magic_bytes.len()) == Some(magic_bytes)
+        }) {
+            return mime_type;
+        }
+    }
+
+    if is_text(head) {
+        "text/plain"
+    } else {
+        "application/octet-stream"
+    }
+}
+
+/// Text is valid utf-8 without null bytes, a character may be cut off at the
+/// end of the read bytes.
+fn is_text(head: &[u8]) -> bool {
+    if head.contains(&0) {
+        return false;
+    }
+
+    match std::str::from_utf8(head) {
+        Ok(_) => true,
+        Err(err) => err.error_len().is_none(),
+    }
+}
+
+/// Matches a MIME type against a pattern like `image/png` or `image/*`.
+pub fn matches_mime_type(pattern: &str, mime_type: &str) -> bool {
+    match pattern.strip_suffix("/*") {
+        Some(media_type) => mime_type.split('/').next() == Some(media_type),
+        None => pattern == mime_type,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CString;
+    use std::fs::create_dir_all;
+    use std::fs::remove_dir_all;
+    use std::os::unix::ffi::OsStrExt;
+
+    #[test]
+    fn signatures() {
+        assert_eq!(
+            "application/pdf",
+            detect_mime_type_of_bytes(b"%PDF-1.4\n...")
+        );
+        assert_eq!(
+            "image/png",
+            detect_mime_type_of_bytes(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR")
+        );
+        assert_eq!(
+            "image/webp",
+            detect_mime_type_of_bytes(b"RIFF\x10\0\0\0WEBPVP8 ")
+        );
+        assert_eq!(
+            "video/mp4",
+            detect_mime_type_of_bytes(b"\0\0\0\x18ftypmp42")
+        );
+        assert_eq!(
+            "image/bmp",
+            detect_mime_type_of_bytes(b"BM\x36\0\x0c\0\0\0\0\0\x36\0")
+        );
+    }
+
+    #[test]
+    fn partial_signatures() {
+        assert_eq!("text/plain", detect_mime_type_of_bytes(b"BMW drives\n"));
+        assert_eq!(
+            "text/plain",
+            detect_mime_type_of_bytes(b"xxxxxxxxWEBP and WAVE\n")
+        );
+        assert_eq!("text/plain", detect_mime_type_of_bytes(b"xxxxxxxxWAVE\n"));
+    }
+
+    #[test]
+    fn tar() {
+        let mut head = vec![b'a'; 300];
+        head[257..262].copy_from_slice(b"ustar");
+
+        assert_eq!("application/x-tar", detect_mime_type_of_bytes(&head));
+    }
+
+    #[test]
+    fn text_and_binary() {
+        assert_eq!("text/plain", detect_mime_type_of_bytes(b"fn main() {}\n"));
+        assert_eq!(
+            "text/plain",
+            detect_mime_type_of_bytes(b"cut off \xe2\x96")
+        );
+        assert_eq!(
+            "application/octet-stream",
+            detect_mime_type_of_bytes(b"a\0b")
+        );
+        assert_eq!(
+            "application/octet-stream",
+            detect_mime_type_of_bytes(b"bad \xff utf-8")
+        );
+        assert_eq!("inode/x-empty", detect_mime_type_of_bytes(b""));
+    }
+
+    #[test]
+    fn detect_from_file() {
+        assert_eq!(
+            Some("inode/x-empty"),
+            detect_mime_type(Path::new("./tests/test_dirs/file0"))
+        );
+        assert_eq!(None, detect_mime_type(Path::new("./tests/nonsense")));
+        assert_eq!(
+            Some("text/plain"),
+            detect_mime_type(Path::new("./Cargo.toml"))
+        );
+    }
+
+    #[test]
+    fn fifo() {
+        let dir = std::env::temp_dir()
+            .join(format!("twilight-commander-fifo-{}", std::process::id()));
+        create_dir_all(&dir).unwrap();
+        let fifo = dir.join("fifo");
+        let fifo_path = CString::new(fifo.as_os_str().as_bytes()).unwrap();
+        assert_eq!(0, unsafe { libc::mkfifo(fifo_path.as_ptr(), 0o600) });
+
+        let mime_type = detect_mime_type(&fifo);
+        remove_dir_all(&dir).unwrap();
+
+        assert_eq!(None, mime_type);
+    }
+
+    #[test]
+    fn mime_type_patterns() {
+        assert!(matches_mime_type("image/png", "image/png"));
+        assert!(matches_mime_type("image/*", "image/png"));
+        assert!(!matches_mime_type("image/*", "imagex/png"));
+        assert!(!matches_mime_type("image/jpeg", "image/png"));
+    }
+}
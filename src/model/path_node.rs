@@ -1,19 +1,25 @@
 use crate::model::compare_functions::PathNodeCompare;
-use crate::model::config::Config;
-use crate::model::tree_index::TreeIndex;
-use log::info;
+use crate::model::path_tree::NodeId;
+use std::ffi::OsStr;
+use std::ffi::OsString;
 use std::fs::canonicalize;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
 use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
 
-mod debug;
-
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct PathNode {
-    pub children: Vec<PathNode>,
+    pub children: Vec<NodeId>,
+    pub depth: usize,
     pub display_text: String,
+    pub file_name: OsString,
     pub is_dir: bool,
     pub is_err: bool,
     pub is_expanded: bool,
+    pub is_loading: bool,
+    pub parent: Option<NodeId>,
     pub path: PathBuf,
 }
 
@@ -21,10 +27,14 @@ impl From<&str> for PathNode {
     fn from(working_dir: &str) -> Self {
         Self {
             children: Vec::new(),
+            depth: 0,
             display_text: String::from(working_dir),
+            file_name: OsString::from(working_dir),
             is_dir: true,
             is_err: false,
             is_expanded: false,
+            is_loading: false,
+            parent: None,
             path: PathBuf::from(working_dir),
         }
     }
@@ -34,166 +44,147 @@ impl From<String> for PathNode {
     fn from(working_dir: String) -> Self {
         Self {
             children: Vec::new(),
+            depth: 0,
             display_text: working_dir.clone(),
+            file_name: OsString::from(working_dir.clone()),
             is_dir: true,
             is_err: false,
             is_expanded: false,
+            is_loading: false,
+            parent: None,
             path: PathBuf::from(working_dir),
         }
     }
 }
 
-impl PathNode {
-    pub fn new_expanded(config: Config) -> Self {
-        info!("initializing path node");
-
-        let mut path_node = Self::from(config.setup.working_dir.clone());
-        let path_node_compare = Self::get_path_node_compare(&config);
-        path_node.expand_dir(&TreeIndex::new(), path_node_compare);
-
-        path_node
+impl From<PathBuf> for PathNode {
+    fn from(path: PathBuf) -> Self {
+        Self {
+            children: Vec::new(),
+            depth: 0,
+            display_text: Self::escape_file_name(path.as_os_str()),
+            file_name: path.clone().into_os_string(),
+            is_dir: true,
+            is_err: false,
+            is_expanded: false,
+            is_loading: false,
+            parent: None,
+            path,
+        }
     }
+}
 
+impl PathNode {
     pub fn get_absolute_path(&self) -> String {
-        let canonicalized_path = canonicalize(self.path.as_path()).unwrap();
-        canonicalized_path.to_str().unwrap().to_string()
-    }
-
-    fn list_path_node_children(
-        &mut self,
-        compare: PathNodeCompare,
-    ) -> Vec<PathNode> {
-        let dirs = self.path.read_dir();
-
-        if dirs.is_err() {
-            self.is_err = true;
-            return Vec::new();
+        match canonicalize(self.path.as_path()) {
+            Ok(canonicalized_path) => {
+                Self::escape_file_name(canonicalized_path.as_os_str())
+            }
+            Err(_) => Self::escape_file_name(self.path.as_os_str()),
         }
+    }
 
-        let mut path_nodes = dirs
-            .unwrap()
-            .map(|dir_entry| {
-                let dir_entry = dir_entry.unwrap();
-
-                PathNode {
-                    children: Vec::new(),
-                    display_text: dir_entry.file_name().into_string().unwrap(),
-                    is_dir: dir_entry.path().is_dir(),
-                    is_err: false,
-                    is_expanded: false,
-                    path: dir_entry.path(),
+    /// Converts a file name to a printable text. Invalid utf-8 bytes and
+    /// control characters are escaped, e.g. as `\xff` or `\n`.
+    pub fn escape_file_name(file_name: &OsStr) -> String {
+        let mut display_text = String::new();
+        let mut bytes = file_name.as_bytes();
+
+        while !bytes.is_empty() {
+            let (valid, invalid) = match std::str::from_utf8(bytes) {
+                Ok(valid) => (valid, &[][..]),
+                Err(err) => {
+                    let (valid, rest) = bytes.split_at(err.valid_up_to());
+                    let invalid_len = err.error_len().unwrap_or(rest.len());
+                    (
+                        std::str::from_utf8(valid).unwrap_or_default(),
+                        &rest[..invalid_len],
+                    )
                 }
-            })
-            .collect::<Vec<PathNode>>();
-
-        path_nodes.sort_unstable_by(compare);
+            };
 
-        path_nodes
-    }
+            for c in valid.chars() {
+                if c.is_control() {
+                    display_text.extend(c.escape_default());
+                } else {
+                    display_text.push(c);
+                }
+            }
 
-    pub fn expand_dir(
-        &mut self,
-        tree_index: &TreeIndex,
-        compare: PathNodeCompare,
-    ) {
-        let mut path_node = self;
-        for i in &tree_index.index {
-            if path_node.children.len() > *i {
-                path_node = &mut path_node.children[*i];
+            for byte in invalid {
+                display_text.push_str(&format!("\\x{:02x}", byte));
             }
-        }
 
-        if !path_node.path.is_dir() {
-            return;
+            bytes = &bytes[valid.len() + invalid.len()..];
         }
 
-        path_node.is_expanded = true;
-        path_node.children = path_node.list_path_node_children(compare);
+        display_text
     }
 
-    pub fn collapse_dir(&mut self, tree_index: &TreeIndex) {
-        let mut path_node = self;
-        for i in &tree_index.index {
-            path_node = &mut path_node.children[*i];
+    /// An entry which could not be read. Its name is unknown, so its path is
+    /// empty and resolves to nothing, instead of pointing to the directory
+    /// containing it.
+    pub fn new_err(err: std::io::Error) -> Self {
+        Self {
+            children: Vec::new(),
+            depth: 0,
+            display_text: format!("<{}>", err),
+            file_name: OsString::new(),
+            is_dir: false,
+            is_err: true,
+            is_expanded: false,
+            is_loading: false,
+            parent: None,
+            path: PathBuf::new(),
         }
-
-        path_node.is_expanded = false;
-        path_node.children = Vec::new();
     }
 
-    fn flat_index_to_tree_index_rec(
-        &self,
-        flat_index: &mut usize,
-        tree_index: &mut TreeIndex,
-    ) -> bool {
-        if *flat_index == 0 {
-            return true;
-        }
-
-        for (c, child) in self.children.iter().enumerate() {
-            *flat_index -= 1;
-
-            tree_index.index.push(c);
-            if child.flat_index_to_tree_index_rec(flat_index, tree_index) {
-                return true;
+    /// Lists and sorts the entries of a directory. Checks `is_cancelled`
+    /// for every entry and fails with `Interrupted` if it is set, this allows
+    /// to abort listings running in another thread.
+    pub fn list_children(
+        path: &Path,
+        compare: PathNodeCompare,
+        is_cancelled: &AtomicBool,
+    ) -> std::io::Result<Vec<PathNode>> {
+        let mut path_nodes = Vec::new();
+
+        for dir_entry in path.read_dir()? {
+            if is_cancelled.load(Ordering::Relaxed) {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::Interrupted,
+                    "listing was cancelled",
+                ));
             }
-            tree_index.index.pop();
-        }
-
-        false
-    }
-
-    pub fn flat_index_to_tree_index(&self, flat_index: usize) -> TreeIndex {
-        let mut tree_index = TreeIndex::from(Vec::new());
-        self.flat_index_to_tree_index_rec(
-            &mut (flat_index + 1),
-            &mut tree_index,
-        );
-
-        tree_index
-    }
 
-    pub fn tree_index_to_flat_index_rec(
-        &self,
-        target_tree_index: &TreeIndex,
-        current_tree_index: &TreeIndex,
-    ) -> usize {
-        if current_tree_index >= target_tree_index {
-            return 0;
-        }
-
-        if self.children.is_empty() {
-            return 1;
-        }
-
-        let mut sum = 1;
-
-        for (index, child) in self.children.iter().enumerate() {
-            let mut new_current_tree_index = current_tree_index.clone();
-            new_current_tree_index.index.push(index);
+            // e.g. the entry was deleted while listing
+            let dir_entry = match dir_entry {
+                Ok(dir_entry) => dir_entry,
+                Err(err) => {
+                    path_nodes.push(Self::new_err(err));
+                    continue;
+                }
+            };
 
-            sum += child.tree_index_to_flat_index_rec(
-                target_tree_index,
-                &new_current_tree_index,
-            );
+            let file_name = dir_entry.file_name();
+
+            path_nodes.push(PathNode {
+                children: Vec::new(),
+                depth: 0,
+                display_text: Self::escape_file_name(&file_name),
+                file_name,
+                is_dir: dir_entry.path().is_dir(),
+                is_err: false,
+                is_expanded: false,
+                is_loading: false,
+                parent: None,
+                path: dir_entry.path(),
+            });
         }
 
-        sum
-    }
-
-    pub fn tree_index_to_flat_index(&self, tree_index: &TreeIndex) -> usize {
-        // We count the root directory, hence we have to subtract 1 to get the
-        // proper index.
-        self.tree_index_to_flat_index_rec(tree_index, &TreeIndex::new()) - 1
-    }
-
-    pub fn get_child_path_node(&self, tree_index: &TreeIndex) -> &Self {
-        let mut child_node = self;
-        for i in &tree_index.index {
-            child_node = &child_node.children[*i];
-        }
+        path_nodes.sort_unstable_by(compare);
 
-        child_node
+        Ok(path_nodes)
     }
 }
 
@@ -201,123 +192,107 @@ impl PathNode {
 mod tests {
     use super::*;
 
-    fn get_expanded_path_node() -> PathNode {
-        let mut path_node = PathNode::from("./tests/test_dirs");
-        path_node
-            .expand_dir(&TreeIndex::new(), PathNode::compare_dirs_top_simple);
-        path_node.expand_dir(
-            &TreeIndex::from(vec![0]),
-            PathNode::compare_dirs_top_simple,
-        );
-        path_node.expand_dir(
-            &TreeIndex::from(vec![0, 0]),
-            PathNode::compare_dirs_top_simple,
-        );
-        path_node.expand_dir(
-            &TreeIndex::from(vec![1]),
-            PathNode::compare_dirs_top_simple,
-        );
-        path_node.expand_dir(
-            &TreeIndex::from(vec![1, 0]),
-            PathNode::compare_dirs_top_simple,
-        );
-        path_node.expand_dir(
-            &TreeIndex::from(vec![1, 0, 2]),
-            PathNode::compare_dirs_top_simple,
-        );
-        path_node
-    }
-
-    mod get_child_path_node_tests {
+    mod list_children_tests {
         use super::*;
 
         #[test]
-        fn first_dirs() {
-            let path_node = {
-                let mut path_node = PathNode::from("./tests/test_dirs");
-                path_node.expand_dir(
-                    &TreeIndex::new(),
-                    PathNode::compare_dirs_top_simple,
-                );
-                path_node.expand_dir(
-                    &TreeIndex::from(vec![0]),
-                    PathNode::compare_dirs_top_simple,
-                );
-                path_node.expand_dir(
-                    &TreeIndex::from(vec![0, 0]),
-                    PathNode::compare_dirs_top_simple,
-                );
-                path_node
-            };
+        fn sorted() {
+            let children = PathNode::list_children(
+                Path::new("./tests/test_dirs/dir1"),
+                PathNode::compare_dirs_top_simple,
+                &AtomicBool::new(false),
+            )
+            .unwrap();
+
+            let display_texts: Vec<&str> = children
+                .iter()
+                .map(|child| child.display_text.as_str())
+                .collect();
+            assert_eq!(vec!["dir6", "dir7", "file7", "file8"], display_texts);
+        }
 
-            let child_path_node =
-                path_node.get_child_path_node(&TreeIndex::from(vec![0, 0, 0]));
+        #[test]
+        fn cancelled() {
+            let result = PathNode::list_children(
+                Path::new("./tests/test_dirs/dir1"),
+                PathNode::compare_dirs_top_simple,
+                &AtomicBool::new(true),
+            );
 
-            assert_eq!("file4", child_path_node.display_text);
+            assert_eq!(
+                std::io::ErrorKind::Interrupted,
+                result.err().unwrap().kind()
+            );
         }
 
         #[test]
-        fn complex_dirs() {
-            let path_node = get_expanded_path_node();
-
-            let child_path_node = path_node
-                .get_child_path_node(&TreeIndex::from(vec![1, 0, 2, 2]));
+        fn missing_dir() {
+            let result = PathNode::list_children(
+                Path::new("./tests/test_dirs/nonsense"),
+                PathNode::compare_dirs_top_simple,
+                &AtomicBool::new(false),
+            );
 
-            assert_eq!("file12", child_path_node.display_text);
+            assert!(result.is_err());
         }
     }
 
-    mod tree_index_to_flat_index_tests {
+    mod escape_file_name_tests {
         use super::*;
 
         #[test]
-        fn complex_dirs() {
-            let path_node = get_expanded_path_node();
-
-            let flat_index =
-                path_node.tree_index_to_flat_index(&TreeIndex::from(vec![4]));
-
-            assert_eq!(22, flat_index);
+        fn valid() {
+            assert_eq!(
+                "file ▶ 1",
+                PathNode::escape_file_name(OsStr::new("file ▶ 1"))
+            );
         }
 
         #[test]
-        fn complex_dirs2() {
-            let path_node = get_expanded_path_node();
-
-            let flat_index =
-                path_node.tree_index_to_flat_index(&TreeIndex::from(vec![5]));
-
-            assert_eq!(23, flat_index);
+        fn control_characters() {
+            assert_eq!(
+                "a\\nb\\u{1b}c",
+                PathNode::escape_file_name(OsStr::new("a\nb\u{1b}c"))
+            );
         }
 
         #[test]
-        fn complex_dirs3() {
-            let path_node = get_expanded_path_node();
-
-            let flat_index = path_node
-                .tree_index_to_flat_index(&TreeIndex::from(vec![1, 0, 4]));
-
-            assert_eq!(15, flat_index);
+        fn invalid_utf8() {
+            assert_eq!(
+                "bad\\xffname\\xe2\\x96",
+                PathNode::escape_file_name(OsStr::from_bytes(
+                    b"bad\xffname\xe2\x96"
+                ))
+            );
         }
+    }
 
-        #[test]
-        fn total_count() {
-            let path_node = get_expanded_path_node();
-
-            let flat_index = path_node
-                .tree_index_to_flat_index(&TreeIndex::from(vec![100_000]));
-
-            assert_eq!(31, flat_index);
-        }
+    mod non_utf8_tests {
+        use super::*;
+        use std::fs::create_dir_all;
+        use std::fs::remove_dir_all;
+        use std::fs::File;
 
         #[test]
-        fn zero() {
-            let path_node = get_expanded_path_node();
-
-            let flat_index =
-                path_node.tree_index_to_flat_index(&TreeIndex::from(vec![0]));
+        fn list_children() {
+            let dir = std::env::temp_dir()
+                .join(format!("twilight-commander-{}", std::process::id()));
+            create_dir_all(&dir).unwrap();
+            let file_name = OsStr::from_bytes(b"bad\xffname");
+            File::create(dir.join(file_name)).unwrap();
+
+            let children = PathNode::list_children(
+                &dir,
+                PathNode::compare_dirs_top_simple,
+                &AtomicBool::new(false),
+            );
+            remove_dir_all(&dir).unwrap();
 
-            assert_eq!(0, flat_index);
+            let children = children.unwrap();
+            assert_eq!(1, children.len());
+            assert_eq!("bad\\xffname", children[0].display_text);
+            assert_eq!(file_name, children[0].file_name);
+            assert_eq!(dir.join(file_name), children[0].path);
         }
     }
 }
@@ -0,0 +1,919 @@
+use crate::model::compare_functions::PathNodeCompare;
+use crate::model::config::Config;
+use crate::model::path_node::PathNode;
+use log::info;
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+
+#[cfg(test)]
+mod bench;
+mod debug;
+
+pub type NodeId = usize;
+
+/// Describes how the flat list of visible entries changed: `removed` entries
+/// starting at `start` were replaced by `inserted` entries.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Splice {
+    pub start: usize,
+    pub removed: usize,
+    pub inserted: usize,
+}
+
+/// The expanded part of the file system. All path nodes live in one arena
+/// and refer to each other by node id. The visible path nodes are kept in a
+/// flat list, so that converting between the cursor row and the node id does
+/// not need to walk the tree, and the node ids by path, so that a path is
+/// found without a search.
+#[derive(Clone)]
+pub struct PathTree {
+    flat_indices: Vec<Option<usize>>,
+    free_node_ids: Vec<NodeId>,
+    node_ids_by_path: HashMap<PathBuf, NodeId>,
+    nodes: Vec<PathNode>,
+    splice: Option<Splice>,
+    visible_node_ids: Vec<NodeId>,
+}
+
+impl From<PathNode> for PathTree {
+    fn from(mut root: PathNode) -> Self {
+        root.children = Vec::new();
+        root.depth = 0;
+        root.parent = None;
+
+        let mut node_ids_by_path = HashMap::new();
+        node_ids_by_path.insert(root.path.clone(), Self::ROOT_ID);
+
+        Self {
+            flat_indices: vec![None],
+            free_node_ids: Vec::new(),
+            node_ids_by_path,
+            nodes: vec![root],
+            splice: None,
+            visible_node_ids: Vec::new(),
+        }
+    }
+}
+
+impl PathTree {
+    pub const ROOT_ID: NodeId = 0;
+
+    pub fn new_expanded(config: Config) -> Self {
+        info!("initializing path tree");
+
+        let mut path_tree =
+            Self::from(PathNode::from(config.setup.working_dir.clone()));
+        let path_node_compare = PathNode::get_path_node_compare(&config);
+        path_tree.expand_dir(Self::ROOT_ID, path_node_compare);
+
+        path_tree
+    }
+
+    pub fn get_root(&self) -> &PathNode {
+        &self.nodes[Self::ROOT_ID]
+    }
+
+    pub fn get_path_node(&self, node_id: NodeId) -> &PathNode {
+        &self.nodes[node_id]
+    }
+
+    /// Returns the number of visible entries, the root is not counted.
+    pub fn len(&self) -> usize {
+        self.visible_node_ids.len()
+    }
+
+    /// Returns the node id of the visible entry in the given row, or the
+    /// root's node id if there is no such entry.
+    pub fn flat_index_to_node_id(&self, flat_index: usize) -> NodeId {
+        self.visible_node_ids
+            .get(flat_index)
+            .copied()
+            .unwrap_or(Self::ROOT_ID)
+    }
+
+    /// Returns the row of the entry, `None` for the root which has no row.
+    pub fn node_id_to_flat_index(&self, node_id: NodeId) -> Option<usize> {
+        self.flat_indices[node_id]
+    }
+
+    /// Returns the accumulated change of the visible entries since the last
+    /// call, so that only the changed entries need to be composed again.
+    pub fn take_splice(&mut self) -> Option<Splice> {
+        self.splice.take()
+    }
+
+    pub fn expand_dir(&mut self, node_id: NodeId, compare: PathNodeCompare) {
+        if !self.nodes[node_id].path.is_dir() {
+            return;
+        }
+
+        let range = self.get_visible_range(node_id);
+        self.list_children(node_id, compare);
+        self.update_visible(node_id, range);
+    }
+
+    /// Sets the result of a listing for the directory with the given node
+    /// id, `None` marks the directory as not readable.
+    pub fn set_children(
+        &mut self,
+        node_id: NodeId,
+        children: Option<Vec<PathNode>>,
+    ) {
+        let range = self.get_visible_range(node_id);
+        self.replace_children(node_id, children);
+        self.update_visible(node_id, range);
+    }
+
+    pub fn set_loading(&mut self, node_id: NodeId, is_loading: bool) {
+        self.nodes[node_id].is_loading = is_loading;
+
+        if let Some(flat_index) = self.flat_indices[node_id] {
+            self.record_splice(flat_index, 1, 1);
+        }
+    }
+
+    pub fn collapse_dir(&mut self, node_id: NodeId) {
+        let range = self.get_visible_range(node_id);
+        self.remove_children(node_id);
+        self.update_visible(node_id, range);
+    }
+
+    /// Collapses all directories except the root directory.
+    pub fn collapse_all(&mut self) {
+        let range = self.get_visible_range(Self::ROOT_ID);
+
+        for child_id in self.nodes[Self::ROOT_ID].children.clone() {
+            if self.nodes[child_id].is_expanded {
+                self.remove_children(child_id);
+            }
+        }
+
+        self.update_visible(Self::ROOT_ID, range);
+    }
+
+    /// Collapses all directories which are not on the way to the entry with
+    /// the given node id. The entry itself stays untouched.
+    pub fn collapse_siblings(&mut self, node_id: NodeId) {
+        let range = self.get_visible_range(Self::ROOT_ID);
+
+        let mut ancestor_id = node_id;
+        while let Some(parent_id) = self.nodes[ancestor_id].parent {
+            for child_id in self.nodes[parent_id].children.clone() {
+                if child_id != ancestor_id && self.nodes[child_id].is_expanded {
+                    self.remove_children(child_id);
+                }
+            }
+
+            ancestor_id = parent_id;
+        }
+
+        self.update_visible(Self::ROOT_ID, range);
+    }
+
+    /// Looks up the path node with exactly the given path in the expanded part
+    /// of the tree.
+    pub fn find_node_id(&self, path: &Path) -> Option<NodeId> {
+        self.node_ids_by_path.get(path).copied()
+    }
+
+    /// Expands all directories on the way to the given path, which is relative
+    /// to the root, and returns the node id of the path's node.
+    pub fn reveal_path(
+        &mut self,
+        relative_path: &Path,
+        compare: PathNodeCompare,
+    ) -> Option<NodeId> {
+        let mut node_id = Self::ROOT_ID;
+        let mut is_found = true;
+        let mut top_expanded = None;
+
+        for component in relative_path.components() {
+            if !self.nodes[node_id].is_expanded {
+                if top_expanded.is_none() {
+                    top_expanded =
+                        Some((node_id, self.get_visible_range(node_id)));
+                }
+                self.list_children(node_id, compare);
+            }
+
+            let child_id = self.nodes[node_id].children.iter().find(|c| {
+                self.nodes[**c].path.file_name() == Some(component.as_os_str())
+            });
+
+            match child_id {
+                Some(child_id) => node_id = *child_id,
+                None => {
+                    is_found = false;
+                    break;
+                }
+            }
+        }
+
+        if let Some((top_node_id, range)) = top_expanded {
+            self.update_visible(top_node_id, range);
+        }
+
+        if is_found {
+            Some(node_id)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the paths of all expanded directories below the root, parents
+    /// come before their children.
+    pub fn get_expanded_paths(&self) -> Vec<PathBuf> {
+        self.visible_node_ids
+            .iter()
+            .map(|node_id| &self.nodes[*node_id])
+            .filter(|path_node| path_node.is_expanded)
+            .map(|path_node| path_node.path.clone())
+            .collect()
+    }
+
+    /// Returns a new tree whose root is a copy of the given path node
+    /// including its expanded subtree.
+    pub fn get_subtree(&self, node_id: NodeId) -> Self {
+        let mut path_tree = Self::from(self.nodes[node_id].clone());
+        path_tree.graft(Self::ROOT_ID, self, node_id);
+        path_tree.splice = None;
+
+        path_tree
+    }
+
+    /// Replaces the subtree below the given path node with a copy of the
+    /// subtree below `other_node_id` in `other`. The path node keeps its path
+    /// but takes over the state of the other path node.
+    pub fn graft(
+        &mut self,
+        node_id: NodeId,
+        other: &Self,
+        other_node_id: NodeId,
+    ) {
+        let range = self.get_visible_range(node_id);
+        self.remove_children(node_id);
+
+        let other_path_node = &other.nodes[other_node_id];
+        self.nodes[node_id].is_err = other_path_node.is_err;
+        self.nodes[node_id].is_expanded = other_path_node.is_expanded;
+        self.nodes[node_id].is_loading = other_path_node.is_loading;
+
+        let mut stack = vec![(node_id, other_node_id)];
+        while let Some((node_id, other_node_id)) = stack.pop() {
+            let children = other.nodes[other_node_id]
+                .children
+                .iter()
+                .map(|other_child_id| other.nodes[*other_child_id].clone())
+                .collect();
+            self.add_children(node_id, children);
+
+            let child_ids = self.nodes[node_id].children.clone();
+            let other_child_ids = &other.nodes[other_node_id].children;
+            stack.extend(
+                child_ids.into_iter().zip(other_child_ids.iter().copied()),
+            );
+        }
+
+        self.update_visible(node_id, range);
+    }
+
+    /// Returns the node id of a descendant of the root, given by the child
+    /// indices on the way to it.
+    #[cfg(test)]
+    pub fn get_node_id_at(&self, child_indices: &[usize]) -> NodeId {
+        let mut node_id = Self::ROOT_ID;
+        for i in child_indices {
+            node_id = self.nodes[node_id].children[*i];
+        }
+
+        node_id
+    }
+
+    fn list_children(&mut self, node_id: NodeId, compare: PathNodeCompare) {
+        let children = PathNode::list_children(
+            &self.nodes[node_id].path,
+            compare,
+            &AtomicBool::new(false),
+        );
+
+        self.replace_children(node_id, children.ok());
+    }
+
+    fn replace_children(
+        &mut self,
+        node_id: NodeId,
+        children: Option<Vec<PathNode>>,
+    ) {
+        self.remove_children(node_id);
+
+        let path_node = &mut self.nodes[node_id];
+        path_node.is_loading = false;
+        path_node.is_expanded = true;
+        path_node.is_err = children.is_none();
+
+        self.add_children(node_id, children.unwrap_or_default());
+    }
+
+    fn add_children(&mut self, node_id: NodeId, children: Vec<PathNode>) {
+        let depth = self.nodes[node_id].depth + 1;
+
+        let child_ids = children
+            .into_iter()
+            .map(|mut child| {
+                child.children = Vec::new();
+                child.depth = depth;
+                child.parent = Some(node_id);
+                self.allocate(child)
+            })
+            .collect();
+
+        self.nodes[node_id].children = child_ids;
+    }
+
+    /// Frees all descendants of the path node and marks it as collapsed.
+    fn remove_children(&mut self, node_id: NodeId) {
+        let mut stack = std::mem::take(&mut self.nodes[node_id].children);
+        while let Some(child_id) = stack.pop() {
+            stack.append(&mut self.nodes[child_id].children);
+            self.free_node_ids.push(child_id);
+
+            // unreadable entries share the empty path
+            let path = &self.nodes[child_id].path;
+            if self.node_ids_by_path.get(path) == Some(&child_id) {
+                self.node_ids_by_path.remove(path);
+            }
+        }
+
+        let path_node = &mut self.nodes[node_id];
+        path_node.is_expanded = false;
+        path_node.is_loading = false;
+    }
+
+    fn allocate(&mut self, path_node: PathNode) -> NodeId {
+        let path = path_node.path.clone();
+        let node_id = match self.free_node_ids.pop() {
+            Some(node_id) => {
+                self.nodes[node_id] = path_node;
+                node_id
+            }
+            None => {
+                self.nodes.push(path_node);
+                self.flat_indices.push(None);
+                self.nodes.len() - 1
+            }
+        };
+        self.node_ids_by_path.insert(path, node_id);
+
+        node_id
+    }
+
+    /// Returns the rows of the entry and its visible descendants. Has to be
+    /// called before the subtree changes.
+    fn get_visible_range(&self, node_id: NodeId) -> (usize, usize) {
+        let start = match self.flat_indices[node_id] {
+            Some(flat_index) => flat_index,
+            None => return (0, self.visible_node_ids.len()),
+        };
+
+        let depth = self.nodes[node_id].depth;
+        let end = self.visible_node_ids[start + 1..]
+            .iter()
+            .position(|visible_id| self.nodes[*visible_id].depth <= depth)
+            .map_or(self.visible_node_ids.len(), |len| start + 1 + len);
+
+        (start, end)
+    }
+
+    /// Replaces the rows of the old subtree with the rows of the new
+    /// subtree. Only the row numbers of the following entries are updated,
+    /// the rest of the tree is not visited.
+    fn update_visible(
+        &mut self,
+        node_id: NodeId,
+        (start, end): (usize, usize),
+    ) {
+        let mut new_visible_ids = Vec::new();
+        if node_id != Self::ROOT_ID {
+            new_visible_ids.push(node_id);
+        }
+
+        let mut stack: Vec<NodeId> =
+            self.nodes[node_id].children.iter().rev().copied().collect();
+        while let Some(visible_id) = stack.pop() {
+            new_visible_ids.push(visible_id);
+            stack.extend(self.nodes[visible_id].children.iter().rev());
+        }
+
+        for visible_id in &self.visible_node_ids[start..end] {
+            self.flat_indices[*visible_id] = None;
+        }
+
+        let inserted = new_visible_ids.len();
+        self.visible_node_ids.splice(start..end, new_visible_ids);
+
+        let reindex_end = if inserted == end - start {
+            start + inserted
+        } else {
+            self.visible_node_ids.len()
+        };
+        for flat_index in start..reindex_end {
+            self.flat_indices[self.visible_node_ids[flat_index]] =
+                Some(flat_index);
+        }
+
+        self.record_splice(start, end - start, inserted);
+    }
+
+    /// Merges the change into the change since the last `take_splice`. The
+    /// merged change covers both ranges and everything in between.
+    fn record_splice(&mut self, start: usize, removed: usize, inserted: usize) {
+        self.splice = Some(match self.splice {
+            None => Splice {
+                start,
+                removed,
+                inserted,
+            },
+            Some(previous) => {
+                let previous_end = previous.start + previous.inserted;
+                let end = start + removed;
+                let merged_start = previous.start.min(start);
+
+                // the end of the merged range before both changes
+                let old_end = if end > previous_end {
+                    end + previous.removed - previous.inserted
+                } else {
+                    previous.start + previous.removed
+                };
+
+                // the end of the merged range after both changes
+                let new_end = previous_end.max(end) + inserted - removed;
+
+                Splice {
+                    start: merged_start,
+                    removed: old_end - merged_start,
+                    inserted: new_end - merged_start,
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_expanded_path_tree() -> PathTree {
+        let mut path_tree = PathTree::from(PathNode::from("./tests/test_dirs"));
+        for child_indices in
+            &[&[][..], &[0], &[0, 0], &[1], &[1, 0], &[1, 0, 2]]
+        {
+            path_tree.expand_dir(
+                path_tree.get_node_id_at(child_indices),
+                PathNode::compare_dirs_top_simple,
+            );
+        }
+        path_tree
+    }
+
+    fn get_display_text(path_tree: &PathTree, node_id: NodeId) -> &str {
+        &path_tree.get_path_node(node_id).display_text
+    }
+
+    mod get_path_node_tests {
+        use super::*;
+
+        #[test]
+        fn first_dirs() {
+            let path_tree = {
+                let mut path_tree =
+                    PathTree::from(PathNode::from("./tests/test_dirs"));
+                for child_indices in &[&[][..], &[0], &[0, 0]] {
+                    path_tree.expand_dir(
+                        path_tree.get_node_id_at(child_indices),
+                        PathNode::compare_dirs_top_simple,
+                    );
+                }
+                path_tree
+            };
+
+            let node_id = path_tree.get_node_id_at(&[0, 0, 0]);
+
+            assert_eq!("file4", get_display_text(&path_tree, node_id));
+        }
+
+        #[test]
+        fn complex_dirs() {
+            let path_tree = get_expanded_path_tree();
+
+            let node_id = path_tree.get_node_id_at(&[1, 0, 2, 2]);
+
+            assert_eq!("file12", get_display_text(&path_tree, node_id));
+            assert_eq!(4, path_tree.get_path_node(node_id).depth);
+        }
+    }
+
+    mod find_node_id_tests {
+        use super::*;
+
+        #[test]
+        fn complex_dirs() {
+            let path_tree = get_expanded_path_tree();
+
+            assert_eq!(
+                Some(path_tree.get_node_id_at(&[1, 0, 2, 2])),
+                path_tree.find_node_id(Path::new(
+                    "./tests/test_dirs/dir1/dir6/dir9/file12"
+                ))
+            );
+            assert_eq!(
+                Some(PathTree::ROOT_ID),
+                path_tree.find_node_id(Path::new("./tests/test_dirs"))
+            );
+            assert_eq!(
+                None,
+                path_tree
+                    .find_node_id(Path::new("./tests/test_dirs/dir2/file20"))
+            );
+        }
+
+        #[test]
+        fn collapsed_and_expanded_again() {
+            let mut path_tree = get_expanded_path_tree();
+            let dir1_id = path_tree.get_node_id_at(&[1]);
+            let file12_path =
+                Path::new("./tests/test_dirs/dir1/dir6/dir9/file12");
+
+            path_tree.collapse_dir(dir1_id);
+
+            assert_eq!(None, path_tree.find_node_id(file12_path));
+            assert_eq!(
+                Some(dir1_id),
+                path_tree.find_node_id(Path::new("./tests/test_dirs/dir1"))
+            );
+
+            path_tree.expand_dir(dir1_id, PathNode::compare_dirs_top_simple);
+            let dir6_id = path_tree.get_node_id_at(&[1, 0]);
+            path_tree.expand_dir(dir6_id, PathNode::compare_dirs_top_simple);
+            let dir9_id = path_tree.get_node_id_at(&[1, 0, 2]);
+            path_tree.expand_dir(dir9_id, PathNode::compare_dirs_top_simple);
+
+            assert_eq!(
+                Some(path_tree.get_node_id_at(&[1, 0, 2, 2])),
+                path_tree.find_node_id(file12_path)
+            );
+        }
+    }
+
+    mod collapse_tests {
+        use super::*;
+
+        #[test]
+        fn collapse_all() {
+            let mut path_tree = get_expanded_path_tree();
+
+            path_tree.collapse_all();
+
+            assert!(path_tree.get_root().is_expanded);
+            assert_eq!(13, path_tree.len());
+            assert!(path_tree.get_expanded_paths().is_empty());
+        }
+
+        #[test]
+        fn collapse_siblings() {
+            let mut path_tree = get_expanded_path_tree();
+            path_tree.expand_dir(
+                path_tree.get_node_id_at(&[1, 0, 0]),
+                PathNode::compare_dirs_top_simple,
+            );
+
+            path_tree.collapse_siblings(path_tree.get_node_id_at(&[1, 0, 2]));
+
+            assert_eq!(
+                vec![
+                    PathBuf::from("./tests/test_dirs/dir1"),
+                    PathBuf::from("./tests/test_dirs/dir1/dir6"),
+                    PathBuf::from("./tests/test_dirs/dir1/dir6/dir9"),
+                ],
+                path_tree.get_expanded_paths()
+            );
+        }
+
+        #[test]
+        fn reuses_node_ids() {
+            let mut path_tree = get_expanded_path_tree();
+            let node_count = path_tree.nodes.len();
+
+            path_tree.collapse_dir(path_tree.get_node_id_at(&[1]));
+            path_tree.expand_dir(
+                path_tree.get_node_id_at(&[1]),
+                PathNode::compare_dirs_top_simple,
+            );
+
+            assert_eq!(node_count, path_tree.nodes.len());
+        }
+    }
+
+    mod get_expanded_paths_tests {
+        use super::*;
+
+        #[test]
+        fn complex_dirs() {
+            let path_tree = get_expanded_path_tree();
+
+            let expanded_paths = path_tree.get_expanded_paths();
+
+            assert_eq!(
+                vec![
+                    PathBuf::from("./tests/test_dirs/dir0"),
+                    PathBuf::from("./tests/test_dirs/dir0/dir3"),
+                    PathBuf::from("./tests/test_dirs/dir1"),
+                    PathBuf::from("./tests/test_dirs/dir1/dir6"),
+                    PathBuf::from("./tests/test_dirs/dir1/dir6/dir9"),
+                ],
+                expanded_paths
+            );
+        }
+
+        #[test]
+        fn collapsed() {
+            let path_tree = PathTree::from(PathNode::from("./tests/test_dirs"));
+
+            assert!(path_tree.get_expanded_paths().is_empty());
+        }
+    }
+
+    mod reveal_path_tests {
+        use super::*;
+
+        #[test]
+        fn nested_file() {
+            let mut path_tree =
+                PathTree::from(PathNode::from("./tests/test_dirs"));
+
+            let node_id = path_tree.reveal_path(
+                Path::new("dir1/dir6/dir9/file12"),
+                PathNode::compare_dirs_top_simple,
+            );
+
+            assert_eq!(Some(path_tree.get_node_id_at(&[1, 0, 2, 2])), node_id);
+            assert_eq!(
+                "file12",
+                get_display_text(&path_tree, node_id.unwrap())
+            );
+            assert_eq!(
+                Some(8),
+                path_tree.node_id_to_flat_index(node_id.unwrap())
+            );
+        }
+
+        #[test]
+        fn keeps_expansions() {
+            let mut path_tree = get_expanded_path_tree();
+
+            path_tree.reveal_path(
+                Path::new("dir2/file20"),
+                PathNode::compare_dirs_top_simple,
+            );
+
+            for child_indices in &[&[0][..], &[1, 0], &[2]] {
+                let node_id = path_tree.get_node_id_at(child_indices);
+                assert!(path_tree.get_path_node(node_id).is_expanded);
+            }
+        }
+
+        #[test]
+        fn empty_path() {
+            let mut path_tree =
+                PathTree::from(PathNode::from("./tests/test_dirs"));
+
+            let node_id = path_tree
+                .reveal_path(Path::new(""), PathNode::compare_dirs_top_simple);
+
+            assert_eq!(Some(PathTree::ROOT_ID), node_id);
+        }
+
+        #[test]
+        fn missing_path() {
+            let mut path_tree =
+                PathTree::from(PathNode::from("./tests/test_dirs"));
+
+            let node_id = path_tree.reveal_path(
+                Path::new("dir1/nonsense"),
+                PathNode::compare_dirs_top_simple,
+            );
+
+            assert_eq!(None, node_id);
+            assert_eq!(17, path_tree.len());
+        }
+    }
+
+    mod node_id_to_flat_index_tests {
+        use super::*;
+
+        #[test]
+        fn complex_dirs() {
+            let path_tree = get_expanded_path_tree();
+
+            let flat_index =
+                path_tree.node_id_to_flat_index(path_tree.get_node_id_at(&[4]));
+
+            assert_eq!(Some(22), flat_index);
+        }
+
+        #[test]
+        fn complex_dirs2() {
+            let path_tree = get_expanded_path_tree();
+
+            let flat_index =
+                path_tree.node_id_to_flat_index(path_tree.get_node_id_at(&[5]));
+
+            assert_eq!(Some(23), flat_index);
+        }
+
+        #[test]
+        fn complex_dirs3() {
+            let path_tree = get_expanded_path_tree();
+
+            let flat_index = path_tree
+                .node_id_to_flat_index(path_tree.get_node_id_at(&[1, 0, 4]));
+
+            assert_eq!(Some(15), flat_index);
+        }
+
+        #[test]
+        fn total_count() {
+            let path_tree = get_expanded_path_tree();
+
+            assert_eq!(31, path_tree.len());
+        }
+
+        #[test]
+        fn zero() {
+            let path_tree = get_expanded_path_tree();
+
+            let flat_index =
+                path_tree.node_id_to_flat_index(path_tree.get_node_id_at(&[0]));
+
+            assert_eq!(Some(0), flat_index);
+        }
+
+        #[test]
+        fn root() {
+            let path_tree = get_expanded_path_tree();
+
+            assert_eq!(
+                None,
+                path_tree.node_id_to_flat_index(PathTree::ROOT_ID)
+            );
+        }
+
+        #[test]
+        fn round_trip() {
+            let path_tree = get_expanded_path_tree();
+
+            for flat_index in 0..path_tree.len() {
+                let node_id = path_tree.flat_index_to_node_id(flat_index);
+                assert_eq!(
+                    Some(flat_index),
+                    path_tree.node_id_to_flat_index(node_id)
+                );
+            }
+            assert_eq!(
+                PathTree::ROOT_ID,
+                path_tree.flat_index_to_node_id(path_tree.len())
+            );
+        }
+    }
+
+    mod splice_tests {
+        use super::*;
+
+        #[test]
+        fn expand_dir() {
+            let mut path_tree = get_expanded_path_tree();
+            path_tree.take_splice();
+
+            path_tree.expand_dir(
+                path_tree.get_node_id_at(&[2]),
+                PathNode::compare_dirs_top_simple,
+            );
+
+            assert_eq!(
+                Some(Splice {
+                    start: 20,
+                    removed: 1,
+                    inserted: 2,
+                }),
+                path_tree.take_splice()
+            );
+            assert_eq!(None, path_tree.take_splice());
+        }
+
+        #[test]
+        fn collapse_dir() {
+            let mut path_tree = get_expanded_path_tree();
+            path_tree.take_splice();
+
+            path_tree.collapse_dir(path_tree.get_node_id_at(&[1, 0]));
+
+            assert_eq!(
+                Some(Splice {
+                    start: 7,
+                    removed: 10,
+                    inserted: 1,
+                }),
+                path_tree.take_splice()
+            );
+        }
+
+        #[test]
+        fn merged() {
+            let mut path_tree = get_expanded_path_tree();
+            path_tree.take_splice();
+
+            path_tree.collapse_dir(path_tree.get_node_id_at(&[1, 0]));
+            path_tree.collapse_dir(path_tree.get_node_id_at(&[0]));
+
+            assert_eq!(
+                Some(Splice {
+                    start: 0,
+                    removed: 17,
+                    inserted: 3,
+                }),
+                path_tree.take_splice()
+            );
+        }
+
+        #[test]
+        fn loading() {
+            let mut path_tree = get_expanded_path_tree();
+            path_tree.take_splice();
+
+            path_tree.set_loading(path_tree.get_node_id_at(&[2]), true);
+
+            assert_eq!(
+                Some(Splice {
+                    start: 20,
+                    removed: 1,
+                    inserted: 1,
+                }),
+                path_tree.take_splice()
+            );
+        }
+    }
+
+    mod subtree_tests {
+        use super::*;
+
+        #[test]
+        fn get_subtree() {
+            let path_tree = get_expanded_path_tree();
+
+            let subtree = path_tree.get_subtree(path_tree.get_node_id_at(&[1]));
+
+            assert_eq!("dir1", subtree.get_root().display_text);
+            assert_eq!(13, subtree.len());
+            assert_eq!(
+                vec![
+                    PathBuf::from("./tests/test_dirs/dir1/dir6"),
+                    PathBuf::from("./tests/test_dirs/dir1/dir6/dir9"),
+                ],
+                subtree.get_expanded_paths()
+            );
+            assert_eq!(
+                1,
+                subtree.get_path_node(subtree.get_node_id_at(&[0])).depth
+            );
+        }
+
+        #[test]
+        fn graft() {
+            let path_tree = get_expanded_path_tree();
+            let subtree = path_tree.get_subtree(path_tree.get_node_id_at(&[1]));
+            let mut path_tree =
+                PathTree::from(PathNode::from("./tests/test_dirs"));
+            path_tree.expand_dir(
+                PathTree::ROOT_ID,
+                PathNode::compare_dirs_top_simple,
+            );
+
+            path_tree.graft(
+                path_tree.get_node_id_at(&[1]),
+                &subtree,
+                PathTree::ROOT_ID,
+            );
+
+            assert_eq!(26, path_tree.len());
+            assert_eq!(
+                vec![
+                    PathBuf::from("./tests/test_dirs/dir1"),
+                    PathBuf::from("./tests/test_dirs/dir1/dir6"),
+                    PathBuf::from("./tests/test_dirs/dir1/dir6/dir9"),
+                ],
+                path_tree.get_expanded_paths()
+            );
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use crate::model::config::Config;
+use crate::model::path_node::PathNode;
+use crate::model::path_tree::NodeId;
+use crate::model::path_tree::PathTree;
+use crate::view::composer::Composer;
+use std::hint::black_box;
+use std::path::Path;
+use std::time::Duration;
+use std::time::Instant;
+
+// run with ./dev/bench.sh
+
+fn generate_children(path: &Path, count: usize) -> Vec<PathNode> {
+    (0..count)
+        .map(|i| {
+            let mut path_node =
+                PathNode::from(path.join(format!("entry{}", i)));
+            path_node.display_text = format!("entry{}", i);
+            path_node
+        })
+        .collect()
+}
+
+/// Generates a tree with `width` entries per directory which is expanded
+/// `depth` levels deep, e.g. 100 and 3 give 1010100 visible entries.
+fn generate_path_tree(width: usize, depth: usize) -> PathTree {
+    let mut path_tree = PathTree::from(PathNode::from("/generated"));
+    let mut level = vec![PathTree::ROOT_ID];
+
+    for _ in 0..depth {
+        let mut next_level = Vec::new();
+        for node_id in level {
+            let path = path_tree.get_path_node(node_id).path.clone();
+            path_tree
+                .set_children(node_id, Some(generate_children(&path, width)));
+            next_level
+                .extend(path_tree.get_path_node(node_id).children.clone());
+        }
+        level = next_level;
+    }
+
+    path_tree.take_splice();
+    path_tree
+}
+
+fn measure<F: FnMut()>(name: &str, iterations: u32, mut f: F) {
+    let start = Instant::now();
+    for _ in 0..iterations {
+        f();
+    }
+    println!("{:40}{:?}", name, start.elapsed() / iterations);
+}
+
+/// Finds every path of a large tree, which takes seconds if each lookup has
+/// to search the entries.
+#[test]
+fn find_node_id_large_tree() {
+    let path_tree = generate_path_tree(100, 2);
+    let node_ids: Vec<NodeId> = (0..path_tree.len())
+        .map(|flat_index| path_tree.flat_index_to_node_id(flat_index))
+        .collect();
+    assert_eq!(10100, node_ids.len());
+
+    let start = Instant::now();
+    for node_id in node_ids {
+        let path = &path_tree.get_path_node(node_id).path;
+        assert_eq!(Some(node_id), path_tree.find_node_id(path));
+    }
+
+    assert!(start.elapsed() < Duration::from_secs(1));
+}
+
+#[test]
+#[ignore]
+fn bench_large_tree() {
+    let start = Instant::now();
+    let mut path_tree = generate_path_tree(100, 3);
+    println!("{:40}{:?}", "generate 1010100 entries", start.elapsed());
+
+    let len = path_tree.len();
+    let composer = Composer::from(Config::default());
+
+    measure("flat_index_to_node_id", 100_000, || {
+        black_box(path_tree.flat_index_to_node_id(black_box(len / 2)));
+    });
+
+    let node_id = path_tree.flat_index_to_node_id(len / 2);
+    measure("node_id_to_flat_index", 100_000, || {
+        black_box(path_tree.node_id_to_flat_index(black_box(node_id)));
+    });
+
+    // a directory of the second level close to the bottom
+    let node_id: NodeId = path_tree.get_node_id_at(&[99, 98]);
+    let path = path_tree.get_path_node(node_id).path.clone();
+    let children = generate_children(&path, 100);
+    measure("collapse and expand near the bottom", 100, || {
+        path_tree.collapse_dir(node_id);
+        path_tree.set_children(node_id, Some(children.clone()));
+    });
+
+    let node_id: NodeId = path_tree.get_node_id_at(&[0, 0]);
+    let path = path_tree.get_path_node(node_id).path.clone();
+    let children = generate_children(&path, 100);
+    measure("collapse and expand at the top", 100, || {
+        path_tree.collapse_dir(node_id);
+        path_tree.set_children(node_id, Some(children.clone()));
+    });
+
+    path_tree.take_splice();
+    path_tree.collapse_dir(node_id);
+    let splice = path_tree.take_splice().unwrap();
+    measure("compose the changed entries", 100, || {
+        black_box(composer.compose_path_tree_range(
+            &path_tree,
+            splice.start..splice.start + splice.inserted,
+        ));
+    });
+
+    measure("compose all entries", 1, || {
+        black_box(composer.compose_path_tree(&path_tree));
+    });
+}
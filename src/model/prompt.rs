@@ -0,0 +1,374 @@
+use std::fs::read_dir;
+use std::path::Path;
+use std::path::PathBuf;
+
+/// The commands of the prompt, besides `!` for shell commands.
+pub const COMMANDS: [&str; 5] = ["cd", "mkdir", "reveal", "set", "sort"];
+
+/// Commands whose argument is a path.
+const PATH_COMMANDS: [&str; 3] = ["cd", "mkdir", "reveal"];
+
+/// The line of the command prompt, the cursor is a char index.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Prompt {
+    pub text: String,
+    pub cursor: usize,
+
+    /// The history entry shown and the text typed before browsing it.
+    history_index: Option<usize>,
+    draft: String,
+}
+
+impl Prompt {
+    pub fn insert(&mut self, c: char) {
+        let byte_index = self.get_byte_index(self.cursor);
+        self.text.insert(byte_index, c);
+        self.cursor += 1;
+    }
+
+    pub fn delete_before_cursor(&mut self) {
+        if self.cursor > 0 {
+            self.cursor -= 1;
+            let byte_index = self.get_byte_index(self.cursor);
+            self.text.remove(byte_index);
+        }
+    }
+
+    pub fn delete_at_cursor(&mut self) {
+        if self.cursor < self.text.chars().count() {
+            let byte_index = self.get_byte_index(self.cursor);
+            self.text.remove(byte_index);
+        }
+    }
+
+    /// Deletes the word in front of the cursor, like ctrl+w in a shell.
+    pub fn delete_word_before_cursor(&mut self) {
+        let chars: Vec<char> = self.text.chars().collect();
+        let mut start = self.cursor;
+        while start > 0 && chars[start - 1].is_whitespace() {
+            start -= 1;
+        }
+        while start > 0 && !chars[start - 1].is_whitespace() {
+            start -= 1;
+        }
+
+        let byte_range =
+            self.get_byte_index(start)..self.get_byte_index(self.cursor);
+        self.text.replace_range(byte_range, "");
+        self.cursor = start;
+    }
+
+    pub fn delete_line_before_cursor(&mut self) {
+        let byte_index = self.get_byte_index(self.cursor);
+        self.text.replace_range(..byte_index, "");
+        self.cursor = 0;
+    }
+
+    pub fn move_left(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    pub fn move_right(&mut self) {
+        self.cursor = (self.cursor + 1).min(self.text.chars().count());
+    }
+
+    pub fn move_home(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn move_end(&mut self) {
+        self.cursor = self.text.chars().count();
+    }
+
+    fn set_text(&mut self, text: String) {
+        self.text = text;
+        self.move_end();
+    }
+
+    /// Shows the previous entry of the history, whose last entry is the most
+    /// recent one.
+    pub fn history_up(&mut self, history: &[String]) {
+        let history_index = match self.history_index {
+            Some(0) => return,
+            Some(history_index) => history_index - 1,
+            None if history.is_empty() => return,
+            None => {
+                self.draft = self.text.clone();
+                history.len() - 1
+            }
+        };
+
+        self.history_index = Some(history_index);
+        self.set_text(history[history_index].clone());
+    }
+
+    /// Shows the next entry of the history, or the typed text after the last
+    /// one.
+    pub fn history_down(&mut self, history: &[String]) {
+        match self.history_index {
+            Some(history_index) if history_index + 1 < history.len() => {
+                self.history_index = Some(history_index + 1);
+                self.set_text(history[history_index + 1].clone());
+            }
+            Some(_) => {
+                self.history_index = None;
+                self.set_text(self.draft.clone());
+            }
+            None => {}
+        }
+    }
+
+    /// Completes the command name or the path in front of the cursor, paths
+    /// are relative to the base directory. Completes as far as all
+    /// candidates agree and returns the candidates when there are several.
+    pub fn complete(&mut self, base_dir: &Path) -> Vec<String> {
+        let byte_index = self.get_byte_index(self.cursor);
+        let before_cursor = &self.text[..byte_index];
+
+        let (word_start, candidates) = match get_completion_word(before_cursor)
+        {
+            Some((word_start, Completion::Command)) => {
+                let word = &before_cursor[word_start..];
+                let candidates = COMMANDS
+                    .iter()
+                    .filter(|command| command.starts_with(word))
+                    .map(|command| format!("{} ", command))
+                    .collect();
+                (word_start, candidates)
+            }
+            Some((word_start, Completion::Path)) => {
+                let word = &before_cursor[word_start..];
+                (word_start, complete_path(base_dir, word))
+            }
+            None => return Vec::new(),
+        };
+
+        let common_prefix = get_common_prefix(&candidates);
+        if common_prefix.len() > byte_index - word_start {
+            let completed =
+                format!("{}{}", &self.text[..word_start], common_prefix);
+            self.cursor = completed.chars().count();
+            self.text.replace_range(..byte_index, &completed);
+        }
+
+        if candidates.len() > 1 {
+            candidates
+        } else {
+            Vec::new()
+        }
+    }
+
+    fn get_byte_index(&self, char_index: usize) -> usize {
+        self.text
+            .char_indices()
+            .nth(char_index)
+            .map(|(byte_index, _)| byte_index)
+            .unwrap_or_else(|| self.text.len())
+    }
+}
+
+enum Completion {
+    Command,
+    Path,
+}
+
+/// Finds the start of the word to complete and what it is. Path commands
+/// take the rest of the line as their path, shell commands take paths as
+/// words.
+fn get_completion_word(before_cursor: &str) -> Option<(usize, Completion)> {
+    if let Some(shell_command) = before_cursor.strip_prefix('!') {
+        let word_start = shell_command
+            .rfind(char::is_whitespace)
+            .map(|index| index + 2)
+            .unwrap_or(1);
+        return Some((word_start, Completion::Path));
+    }
+
+    match before_cursor.find(' ') {
+        None => Some((0, Completion::Command)),
+        Some(end) if PATH_COMMANDS.contains(&&before_cursor[..end]) => {
+            Some((end + 1, Completion::Path))
+        }
+        Some(_) => None,
+    }
+}
+
+/// Resolves `~` and relative paths, which are relative to the base
+/// directory.
+pub fn resolve_path(base_dir: &Path, path: &str) -> PathBuf {
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+
+    match (path.strip_prefix('~'), home) {
+        (Some(rest), Some(home))
+            if rest.is_empty() || rest.starts_with('/') =>
+        {
+            home.join(rest.trim_start_matches('/'))
+        }
+        _ => base_dir.join(path),
+    }
+}
+
+/// Lists the entries starting with the last component of the partial path,
+/// directories end with a `/`. Hidden entries are only listed when the
+/// component starts with a `.`.
+fn complete_path(base_dir: &Path, partial_path: &str) -> Vec<String> {
+    let (dir_part, name_part) = match partial_path.rfind('/') {
+        Some(index) => partial_path.split_at(index + 1),
+        None => ("", partial_path),
+    };
+
+    let dir = resolve_path(
+        base_dir,
+        if dir_part.is_empty() { "." } else { dir_part },
+    );
+
+    let entries = match read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut candidates: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            if !name.starts_with(name_part)
+                || (name.starts_with('.') && !name_part.starts_with('.'))
+            {
+                return None;
+            }
+            let suffix = if entry.path().is_dir() { "/" } else { "" };
+            Some(format!("{}{}{}", dir_part, name, suffix))
+        })
+        .collect();
+    candidates.sort();
+    candidates
+}
+
+fn get_common_prefix(candidates: &[String]) -> String {
+    let first = match candidates.first() {
+        Some(first) => first,
+        None => return String::new(),
+    };
+
+    let mut prefix_len = first.len();
+    for candidate in &candidates[1..] {
+        prefix_len = first
+            .char_indices()
+            .zip(candidate.chars())
+            .take_while(|((_, a), b)| a == b)
+            .last()
+            .map(|((index, a), _)| index + a.len_utf8())
+            .unwrap_or(0)
+            .min(prefix_len);
+    }
+
+    String::from(&first[..prefix_len])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_prompt(text: &str) -> Prompt {
+        let mut prompt = Prompt::default();
+        prompt.set_text(String::from(text));
+        prompt
+    }
+
+    #[test]
+    fn edit() {
+        let mut prompt = get_prompt("cd ä");
+        prompt.move_left();
+        prompt.insert('b');
+        prompt.move_end();
+        prompt.insert('c');
+        assert_eq!("cd bäc", prompt.text);
+
+        prompt.delete_before_cursor();
+        prompt.move_home();
+        prompt.delete_at_cursor();
+        assert_eq!("d bä", prompt.text);
+        assert_eq!(0, prompt.cursor);
+
+        prompt.move_end();
+        prompt.delete_word_before_cursor();
+        assert_eq!("d ", prompt.text);
+
+        prompt.delete_line_before_cursor();
+        assert_eq!("", prompt.text);
+    }
+
+    #[test]
+    fn history() {
+        let history = vec![String::from("cd a"), String::from("cd b")];
+        let mut prompt = get_prompt("draft");
+
+        prompt.history_up(&history);
+        assert_eq!("cd b", prompt.text);
+        prompt.history_up(&history);
+        prompt.history_up(&history);
+        assert_eq!("cd a", prompt.text);
+
+        prompt.history_down(&history);
+        assert_eq!("cd b", prompt.text);
+        prompt.history_down(&history);
+        assert_eq!("draft", prompt.text);
+        assert_eq!(5, prompt.cursor);
+    }
+
+    #[test]
+    fn complete_command() {
+        let mut prompt = get_prompt("mk");
+
+        assert!(prompt.complete(Path::new(".")).is_empty());
+        assert_eq!("mkdir ", prompt.text);
+
+        let mut prompt = get_prompt("s");
+        assert_eq!(vec!["set ", "sort "], prompt.complete(Path::new(".")));
+        assert_eq!("s", prompt.text);
+    }
+
+    #[test]
+    fn complete_unique_path() {
+        let mut prompt = get_prompt("cd dir1/di");
+
+        assert_eq!(
+            vec!["dir1/dir6/", "dir1/dir7/"],
+            prompt.complete(Path::new("./tests/test_dirs"))
+        );
+        assert_eq!("cd dir1/dir", prompt.text);
+
+        let mut prompt = get_prompt("reveal dir1/f");
+        prompt.complete(Path::new("./tests/test_dirs"));
+        assert_eq!("reveal dir1/file", prompt.text);
+        assert_eq!(16, prompt.cursor);
+    }
+
+    #[test]
+    fn complete_shell_command_path() {
+        let mut prompt = get_prompt("!cat dir1/dir6/dir1");
+        prompt.insert(' ');
+        prompt.move_left();
+
+        prompt.complete(Path::new("./tests/test_dirs"));
+
+        assert_eq!("!cat dir1/dir6/dir10/ ", prompt.text);
+    }
+
+    #[test]
+    fn complete_nothing() {
+        let mut prompt = get_prompt("set compo");
+
+        assert!(prompt.complete(Path::new(".")).is_empty());
+        assert_eq!("set compo", prompt.text);
+    }
+
+    #[test]
+    fn resolve() {
+        let base_dir = Path::new("/base");
+
+        assert_eq!(PathBuf::from("/base/a"), resolve_path(base_dir, "a"));
+        assert_eq!(PathBuf::from("/abs"), resolve_path(base_dir, "/abs"));
+        assert_eq!(PathBuf::from("/base/~a"), resolve_path(base_dir, "~a"));
+    }
+}
@@ -0,0 +1,200 @@
+use crate::utils::read_file;
+use crate::utils::write_file;
+use log::warn;
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::path::PathBuf;
+
+/// The expanded directories and the cursor position of a tree, all paths are
+/// absolute.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Session {
+    pub cursor: PathBuf,
+    pub expanded: Vec<PathBuf>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+struct SessionsFile {
+    #[serde(default)]
+    sessions: BTreeMap<String, Session>,
+}
+
+impl Session {
+    /// Loads the session stored for the absolute working directory.
+    pub fn load(
+        config_dir: &Path,
+        working_dir: &Path,
+    ) -> std::io::Result<Option<Self>> {
+        let working_dir = Self::get_key(working_dir)?;
+        let mut sessions_file = Self::read_sessions_file(config_dir)?;
+
+        Ok(sessions_file.sessions.remove(working_dir))
+    }
+
+    /// Stores the session for the absolute working directory, sessions of
+    /// other working directories are kept. A sessions file which cannot be
+    /// read is not overwritten.
+    pub fn save(
+        &self,
+        config_dir: &Path,
+        working_dir: &Path,
+    ) -> std::io::Result<()> {
+        let working_dir = Self::get_key(working_dir)?;
+        let mut sessions_file = match Self::read_sessions_file(config_dir) {
+            Ok(sessions_file) => sessions_file,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                SessionsFile::default()
+            }
+            Err(err) => return Err(err),
+        };
+
+        sessions_file
+            .sessions
+            .insert(String::from(working_dir), self.get_storable());
+
+        Self::write_sessions_file(config_dir, &sessions_file)
+    }
+
+    /// toml strings cannot hold paths which are not valid UTF-8, sessions of
+    /// such working directories are not stored.
+    fn get_key(working_dir: &Path) -> std::io::Result<&str> {
+        working_dir.to_str().ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("{:?} is not valid UTF-8", working_dir),
+            )
+        })
+    }
+
+    /// The session without the paths which are not valid UTF-8, the cursor
+    /// moves up to the nearest directory which is.
+    fn get_storable(&self) -> Self {
+        let cursor = self
+            .cursor
+            .ancestors()
+            .find(|path| path.to_str().is_some())
+            .unwrap_or_else(|| Path::new(""))
+            .to_path_buf();
+        if cursor != self.cursor {
+            warn!("not storing the non UTF-8 cursor {:?}", self.cursor);
+        }
+
+        let mut expanded = Vec::new();
+        for expanded_path in &self.expanded {
+            if expanded_path.to_str().is_some() {
+                expanded.push(expanded_path.clone());
+            } else {
+                warn!(
+                    "not storing the non UTF-8 expansion {:?}",
+                    expanded_path
+                );
+            }
+        }
+
+        Self { cursor, expanded }
+    }
+
+    fn get_sessions_file_path(config_dir: &Path) -> PathBuf {
+        config_dir.join("sessions.toml")
+    }
+
+    fn read_sessions_file(config_dir: &Path) -> std::io::Result<SessionsFile> {
+        let sessions_file_content =
+            read_file(Self::get_sessions_file_path(config_dir))?;
+
+        toml::from_str(&sessions_file_content).map_err(|err| {
+            std::io::Error::new(std::io::ErrorKind::InvalidData, err)
+        })
+    }
+
+    fn write_sessions_file(
+        config_dir: &Path,
+        sessions_file: &SessionsFile,
+    ) -> std::io::Result<()> {
+        let sessions_file_content =
+            toml::to_string(sessions_file).map_err(|err| {
+                std::io::Error::new(std::io::ErrorKind::InvalidData, err)
+            })?;
+
+        write_file(
+            Self::get_sessions_file_path(config_dir),
+            &sessions_file_content,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::OsStr;
+    use std::fs::create_dir_all;
+    use std::fs::remove_dir_all;
+    use std::os::unix::ffi::OsStrExt;
+
+    #[test]
+    fn save_and_load() {
+        let config_dir = std::env::temp_dir().join(format!(
+            "twilight-commander-sessions-{}",
+            std::process::id()
+        ));
+        create_dir_all(&config_dir).unwrap();
+        let session = Session {
+            cursor: PathBuf::from("/project/src/main.rs"),
+            expanded: vec![PathBuf::from("/project/src")],
+        };
+
+        let missing = Session::load(&config_dir, Path::new("/project"));
+        session.save(&config_dir, Path::new("/project")).unwrap();
+        Session::default()
+            .save(&config_dir, Path::new("/other"))
+            .unwrap();
+        let loaded = Session::load(&config_dir, Path::new("/project"));
+        remove_dir_all(&config_dir).unwrap();
+
+        assert_eq!(std::io::ErrorKind::NotFound, missing.unwrap_err().kind());
+        assert_eq!(Some(session), loaded.unwrap());
+    }
+
+    #[test]
+    fn serialize_sessions_file() {
+        let mut sessions_file = SessionsFile::default();
+        sessions_file.sessions.insert(
+            String::from("/home/user/project"),
+            Session {
+                cursor: PathBuf::from("/home/user/project/src/main.rs"),
+                expanded: vec![
+                    PathBuf::from("/home/user/project/src"),
+                    PathBuf::from("/home/user/project/tests"),
+                ],
+            },
+        );
+        sessions_file
+            .sessions
+            .insert(String::from("/tmp"), Session::default());
+
+        let content = toml::to_string(&sessions_file).unwrap();
+
+        assert_eq!(sessions_file, toml::from_str(&content).unwrap());
+    }
+
+    #[test]
+    fn storable_session() {
+        let non_utf8_dir =
+            PathBuf::from(OsStr::from_bytes(b"/project/dir\xff"));
+        let session = Session {
+            cursor: non_utf8_dir.join("file"),
+            expanded: vec![PathBuf::from("/project/src"), non_utf8_dir.clone()],
+        };
+
+        let storable_session = session.get_storable();
+
+        assert_eq!(PathBuf::from("/project"), storable_session.cursor);
+        assert_eq!(
+            vec![PathBuf::from("/project/src")],
+            storable_session.expanded
+        );
+        assert!(Session::get_key(&non_utf8_dir).is_err());
+    }
+}
@@ -1,19 +1,56 @@
+use crate::model::config::reference::get_help;
+use crate::view::terminal::restore_terminal;
 use log::info;
+use std::fs::canonicalize;
+use std::fs::remove_file;
+use std::fs::rename;
 use std::fs::File;
 use std::io::Read;
+use std::io::Write;
 use std::panic::set_hook;
+use std::panic::take_hook;
+use std::path::Path;
+use std::path::PathBuf;
 use std::process::exit;
 
-pub fn read_file(file_name: &str) -> std::io::Result<String> {
+pub fn read_file<P: AsRef<Path>>(file_name: P) -> std::io::Result<String> {
     let mut file = File::open(file_name)?;
     let mut contents = String::new();
     file.read_to_string(&mut contents)?;
     Ok(contents)
 }
 
+/// Writes to a temporary file next to the file, which then replaces it, so a
+/// crash leaves either the old or the new contents. A symlink is followed
+/// instead of being replaced.
+pub fn write_file<P: AsRef<Path>>(
+    file_name: P,
+    contents: &str,
+) -> std::io::Result<()> {
+    let file_name = canonicalize_or_keep(file_name.as_ref());
+    let mut temp_file_name = file_name.clone().into_os_string();
+    temp_file_name.push(format!(".{}.tmp", std::process::id()));
+
+    let result = File::create(&temp_file_name)
+        .and_then(|mut file| {
+            file.write_all(contents.as_bytes())?;
+            file.sync_all()
+        })
+        .and_then(|_| rename(&temp_file_name, &file_name));
+
+    if result.is_err() {
+        let _ = remove_file(&temp_file_name);
+    }
+
+    result
+}
+
+pub fn canonicalize_or_keep(path: &Path) -> PathBuf {
+    canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
 pub fn print_help() {
-    println!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
-    println!(r#"usage: twilight-commander [--key1=value1 --key2=value2 ...]"#);
+    print!("{}", get_help());
     exit(0);
 }
 
@@ -39,16 +76,6 @@ pub fn setup_logger() -> Result<(), fern::InitError> {
         .chain(fern::log_file(log_file_path)?)
         .apply()?;
 
-    set_hook(Box::new(|panic_info| {
-        if let Some(p) = panic_info.payload().downcast_ref::<String>() {
-            info!("{:?}, \npayload: {}", panic_info, p,);
-        } else if let Some(p) = panic_info.payload().downcast_ref::<&str>() {
-            info!("{:?}, \npayload: {}", panic_info, p,);
-        } else {
-            info!("{:?}", panic_info);
-        }
-    }));
-
     info!(
         r#"starting...
                                                  
@@ -63,10 +90,45 @@ _|_    o|o _ |__|_  _ _ ._ _ ._ _  _.._  _| _ ._
     Ok(())
 }
 
+/// Restores the terminal before a panic is reported and exits, as the UI
+/// cannot recover from a panic in any of its threads.
+pub fn setup_panic_hook() {
+    let default_hook = take_hook();
+
+    set_hook(Box::new(move |panic_info| {
+        restore_terminal();
+
+        if let Some(p) = panic_info.payload().downcast_ref::<String>() {
+            info!("{:?}, \npayload: {}", panic_info, p,);
+        } else if let Some(p) = panic_info.payload().downcast_ref::<&str>() {
+            info!("{:?}, \npayload: {}", panic_info, p,);
+        } else {
+            info!("{:?}", panic_info);
+        }
+
+        default_hook(panic_info);
+        exit(101);
+    }));
+}
+
+/// The directory of the user config file, the bookmarks and the log.
 pub fn get_config_dir() -> std::io::Result<String> {
-    if let Ok(xdg_config_home) = std::env::var("XDG_CONFIG_HOME") {
-        Ok(xdg_config_home)
-    } else if let Ok(home) = std::env::var("HOME") {
+    get_config_dir_from(
+        std::env::var("XDG_CONFIG_HOME").ok(),
+        std::env::var("HOME").ok(),
+    )
+}
+
+fn get_config_dir_from(
+    xdg_config_home: Option<String>,
+    home: Option<String>,
+) -> std::io::Result<String> {
+    let xdg_config_home =
+        xdg_config_home.filter(|xdg_config_home| !xdg_config_home.is_empty());
+
+    if let Some(xdg_config_home) = xdg_config_home {
+        Ok(format!("{}/twilight-commander", xdg_config_home))
+    } else if let Some(home) = home {
         Ok(format!("{}/.config/twilight-commander", home))
     } else {
         Err(std::io::Error::new(
@@ -75,3 +137,56 @@ pub fn get_config_dir() -> std::io::Result<String> {
         ))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::create_dir_all;
+    use std::fs::read_dir;
+    use std::fs::remove_dir_all;
+    use std::os::unix::fs::symlink;
+
+    #[test]
+    fn config_dir() {
+        let some = |value: &str| Some(String::from(value));
+
+        assert_eq!(
+            "/xdg/twilight-commander",
+            get_config_dir_from(some("/xdg"), some("/home/user")).unwrap()
+        );
+        assert_eq!(
+            "/home/user/.config/twilight-commander",
+            get_config_dir_from(some(""), some("/home/user")).unwrap()
+        );
+        assert_eq!(
+            "/home/user/.config/twilight-commander",
+            get_config_dir_from(None, some("/home/user")).unwrap()
+        );
+        assert_eq!(
+            std::io::ErrorKind::NotFound,
+            get_config_dir_from(None, None).unwrap_err().kind()
+        );
+    }
+
+    #[test]
+    fn write_file_replaces_contents() {
+        let dir = std::env::temp_dir()
+            .join(format!("twilight-commander-write-{}", std::process::id()));
+        create_dir_all(&dir).unwrap();
+        let file = dir.join("file.toml");
+        let link = dir.join("link.toml");
+        symlink(&file, &link).unwrap();
+
+        write_file(&file, "old contents").unwrap();
+        write_file(&link, "new").unwrap();
+
+        let contents = read_file(&file).unwrap();
+        let is_symlink = link.symlink_metadata().unwrap().is_symlink();
+        let entries = read_dir(&dir).unwrap().count();
+        remove_dir_all(&dir).unwrap();
+
+        assert_eq!("new", contents);
+        assert!(is_symlink);
+        assert_eq!(2, entries);
+    }
+}
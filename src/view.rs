@@ -1,17 +1,28 @@
 use crate::model::config::Config;
+use crate::model::config::SharedConfig;
 use crate::view::composer::Composer;
 use log::info;
+use std::cell::RefCell;
 use std::io::Write;
+use std::rc::Rc;
 
 pub mod composer;
 mod print;
 mod scroll;
+pub mod terminal;
 mod update;
 
 pub struct Pager<W: Write> {
-    config: Config,
+    back_buffer: Vec<String>,
+    buffer: Vec<u8>,
+    config: SharedConfig,
     pub cursor_row: i32,
+    frame: Vec<String>,
     out: W,
+    overlay_entries: Vec<String>,
+    pending_keys: String,
+    prompt: Option<(String, usize)>,
+    status: String,
     terminal_cols: i32,
     terminal_rows: i32,
     text_row: i32,
@@ -24,42 +35,165 @@ impl<W: Write> Pager<W> {
         write!(
             out,
             "{}{}{}",
+            termion::screen::ToAlternateScreen,
             termion::cursor::Hide,
-            termion::cursor::Goto(1, 1),
             termion::clear::All,
         )
         .unwrap();
+        if config.behavior.mouse {
+            write!(out, "{}", terminal::ENABLE_MOUSE).unwrap();
+        }
+        out.flush().unwrap();
 
         Self {
-            config,
+            back_buffer: Vec::new(),
+            buffer: Vec::new(),
+            config: Rc::new(RefCell::new(config)),
             cursor_row: 0,
+            frame: Vec::new(),
             out,
+            overlay_entries: Vec::new(),
+            pending_keys: String::new(),
+            prompt: None,
+            status: String::new(),
             terminal_cols: 0,
             terminal_rows: 0,
             text_row: 0,
         }
     }
-}
 
-impl<W: Write> Drop for Pager<W> {
-    fn drop(&mut self) {
+    /// Reads the given config from now on instead of its own.
+    pub fn share_config(&mut self, config: SharedConfig) {
+        self.config = config;
+    }
+
+    /// Enables or disables mouse reporting after `behavior.mouse` changed.
+    pub fn update_mouse(&mut self) {
+        let mouse = if self.config.borrow().behavior.mouse {
+            terminal::ENABLE_MOUSE
+        } else {
+            terminal::DISABLE_MOUSE
+        };
+        write!(self.out, "{}", mouse).unwrap();
+        self.out.flush().unwrap();
+    }
+
+    /// Shows the entries on top of the text entries until the overlay is
+    /// cleared.
+    pub fn set_overlay(&mut self, overlay_entries: Vec<String>) {
+        self.overlay_entries = overlay_entries;
+    }
+
+    pub fn clear_overlay(&mut self) {
+        self.overlay_entries = Vec::new();
+    }
+
+    pub fn has_overlay(&self) -> bool {
+        !self.overlay_entries.is_empty()
+    }
+
+    /// Shows the keys of an incomplete key sequence in the footer.
+    pub fn set_pending_keys(&mut self, pending_keys: String) {
+        self.pending_keys = pending_keys;
+    }
+
+    /// Shows the text of the command prompt with the cursor at the given char
+    /// index in the footer, `None` shows the usual footer.
+    pub fn set_prompt(&mut self, prompt: Option<(String, usize)>) {
+        self.prompt = prompt;
+    }
+
+    /// Shows a message in the footer until it is cleared.
+    pub fn set_status(&mut self, status: String) {
+        self.status = status;
+    }
+
+    pub fn clear_status(&mut self) {
+        self.status = String::new();
+    }
+
+    pub fn get_status(&self) -> &str {
+        &self.status
+    }
+
+    /// Hands the terminal back for a command which runs in the foreground.
+    pub fn suspend(&mut self) {
+        if self.config.borrow().behavior.mouse {
+            write!(self.out, "{}", terminal::DISABLE_MOUSE).unwrap();
+        }
         write!(
-            self,
+            self.out,
+            "{}{}",
+            termion::cursor::Show,
+            termion::screen::ToMainScreen
+        )
+        .unwrap();
+        self.out.flush().unwrap();
+
+        terminal::suspend_raw_mode();
+    }
+
+    /// Takes the terminal back after `suspend`, the next update prints the
+    /// whole frame.
+    pub fn resume(&mut self) {
+        terminal::resume_raw_mode();
+
+        write!(
+            self.out,
             "{}{}{}",
+            termion::screen::ToAlternateScreen,
+            termion::cursor::Hide,
             termion::clear::All,
-            termion::cursor::Goto(1, 1),
-            termion::cursor::Show,
         )
         .unwrap();
+        if self.config.borrow().behavior.mouse {
+            write!(self.out, "{}", terminal::ENABLE_MOUSE).unwrap();
+        }
+        self.out.flush().unwrap();
+
+        self.back_buffer.clear();
+    }
+
+    #[cfg(test)]
+    pub fn set_terminal_size(
+        &mut self,
+        terminal_cols: i32,
+        terminal_rows: i32,
+    ) {
+        self.terminal_cols = terminal_cols;
+        self.terminal_rows = terminal_rows;
+    }
+}
+
+impl<W: Write> Drop for Pager<W> {
+    fn drop(&mut self) {
+        // the terminal may be gone already, e.g. after a SIGHUP
+        if self.config.borrow().behavior.mouse {
+            let _ = write!(self, "{}", terminal::DISABLE_MOUSE);
+        }
+        let _ = write!(
+            self,
+            "{}{}",
+            termion::cursor::Show,
+            termion::screen::ToMainScreen,
+        );
+        let _ = self.flush();
     }
 }
 
+/// Collects everything in a buffer which is written at once on `flush`, so
+/// that the terminal never shows a half drawn frame.
 impl<W: Write> Write for Pager<W> {
     fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
-        self.out.write(buf)
+        self.buffer.extend_from_slice(buf);
+        Ok(buf.len())
     }
 
     fn flush(&mut self) -> std::io::Result<()> {
+        if !self.buffer.is_empty() {
+            self.out.write_all(&self.buffer)?;
+            self.buffer.clear();
+        }
         self.out.flush()
     }
 }
@@ -1,19 +1,32 @@
+use crate::model::bookmarks::Bookmarks;
 use crate::model::config::Config;
+use crate::model::config::SharedConfig;
 use crate::model::path_node::PathNode;
+use crate::model::path_tree::PathTree;
 use log::info;
+use std::cell::RefCell;
+use std::ops::Range;
+use std::rc::Rc;
 
 pub struct Composer {
-    config: Config,
+    config: SharedConfig,
 }
 
 impl From<Config> for Composer {
     fn from(config: Config) -> Self {
         info!("initializing composer");
-        Self { config }
+        Self {
+            config: Rc::new(RefCell::new(config)),
+        }
     }
 }
 
 impl Composer {
+    /// Reads the given config from now on instead of its own.
+    pub fn share_config(&mut self, config: SharedConfig) {
+        self.config = config;
+    }
+
     pub fn truncate_string(string: &str, desired_char_count: usize) -> String {
         if desired_char_count < 1 {
             return String::new();
@@ -32,40 +45,122 @@ impl Composer {
         format!("{}~", truncated)
     }
 
-    pub fn compose_path_node(&self, path_node: &PathNode) -> Vec<String> {
-        let mut result = Vec::new();
+    pub fn compose_path_tree(&self, path_tree: &PathTree) -> Vec<String> {
+        self.compose_path_tree_range(path_tree, 0..path_tree.len())
+    }
 
-        self.compose_path_node_recursive(path_node, &mut result, 0);
+    /// Composes only the visible entries in the given rows.
+    pub fn compose_path_tree_range(
+        &self,
+        path_tree: &PathTree,
+        flat_indices: Range<usize>,
+    ) -> Vec<String> {
+        flat_indices
+            .map(|flat_index| {
+                let node_id = path_tree.flat_index_to_node_id(flat_index);
+                self.compose_path_node(path_tree.get_path_node(node_id))
+            })
+            .collect()
+    }
 
-        result
+    pub fn compose_path_node(&self, path_node: &PathNode) -> String {
+        format!(
+            "{}{}{}{}",
+            self.get_indent(path_node.depth - 1),
+            self.get_dir_prefix(path_node),
+            path_node.display_text,
+            self.get_dir_suffix(path_node),
+        )
     }
 
-    fn compose_path_node_recursive(
+    /// Tells if the column of a composed entry shows its expand marker or the
+    /// space after it, columns start at 1.
+    pub fn is_expand_marker_column(
         &self,
         path_node: &PathNode,
-        texts: &mut Vec<String>,
-        depth: usize,
-    ) {
-        for child in &path_node.children {
-            let dir_prefix = self.get_dir_prefix(child);
-            let dir_suffix = self.get_dir_suffix(child);
-            let indent = self.get_indent(depth);
-
-            let text = format!(
-                "{}{}{}{}",
-                indent,
-                dir_prefix,
-                child.display_text.clone(),
-                dir_suffix,
-            );
-            texts.push(text);
-            self.compose_path_node_recursive(child, texts, depth + 1);
+        column: usize,
+    ) -> bool {
+        let marker_column = 1 + (path_node.depth - 1) * self.get_indent_width();
+
+        path_node.is_dir
+            && (column == marker_column || column == marker_column + 1)
+    }
+
+    pub fn compose_bookmarks(&self, bookmarks: &Bookmarks) -> Vec<String> {
+        let mut result = vec![String::from("bookmarks:")];
+
+        for (mark, path) in bookmarks.iter() {
+            result.push(format!(
+                " {}  {}",
+                mark,
+                PathNode::escape_file_name(path.as_os_str())
+            ));
+        }
+
+        if result.len() == 1 {
+            result.push(String::from(" no bookmarks set"));
         }
+
+        result
+    }
+
+    pub fn compose_open_with(&self, entries: &[(char, String)]) -> Vec<String> {
+        let mut result = vec![String::from("open with:")];
+
+        for (menu_key, name) in entries {
+            result.push(format!(" {}  {}", menu_key, name));
+        }
+
+        result
+    }
+
+    pub fn compose_toggle_options(
+        &self,
+        entries: &[(char, &str, bool)],
+    ) -> Vec<String> {
+        let mut result = vec![String::from("toggle:")];
+
+        for (menu_key, key, value) in entries {
+            result.push(format!(" {}  {} = {}", menu_key, key, value));
+        }
+
+        result
+    }
+
+    pub fn compose_completions(&self, candidates: &[String]) -> Vec<String> {
+        let mut result = vec![String::from("completions:")];
+
+        for candidate in candidates {
+            result.push(format!(" {}", candidate));
+        }
+
+        result
+    }
+
+    /// Lists the output line by line, control characters which could move
+    /// the cursor are replaced.
+    pub fn compose_command_output(
+        &self,
+        name: &str,
+        output: &[u8],
+    ) -> Vec<String> {
+        let mut result = vec![format!("output of {}:", name)];
+
+        for line in String::from_utf8_lossy(output).lines() {
+            let line: String = line
+                .replace('\t', "    ")
+                .chars()
+                .map(|c| if c.is_control() { '?' } else { c })
+                .collect();
+            result.push(format!(" {}", line));
+        }
+
+        result
     }
 
     fn get_dir_prefix(&self, path_node: &PathNode) -> String {
         let (err_char, expanded_char, reduced_char) =
-            if self.config.composition.use_utf8 {
+            if self.config.borrow().composition.use_utf8 {
                 ('⨯', '▼', '▶')
             } else {
                 ('x', 'v', '>')
@@ -79,7 +174,7 @@ impl Composer {
             reduced_char
         };
 
-        if path_node.is_dir {
+        if path_node.is_dir || path_node.is_err {
             format!("{} ", expanded_indicator)
         } else {
             String::from("  ")
@@ -87,7 +182,13 @@ impl Composer {
     }
 
     fn get_dir_suffix(&self, path_node: &PathNode) -> String {
-        if path_node.is_dir {
+        if path_node.is_loading {
+            if self.config.borrow().composition.use_utf8 {
+                String::from("/ loading…")
+            } else {
+                String::from("/ loading...")
+            }
+        } else if path_node.is_dir {
             String::from("/")
         } else {
             String::from("")
@@ -95,23 +196,122 @@ impl Composer {
     }
 
     fn get_indent(&self, depth: usize) -> String {
-        let indent_char = if !self.config.composition.show_indent {
+        let indent_char = if !self.config.borrow().composition.show_indent {
             ' '
-        } else if self.config.composition.use_utf8 {
+        } else if self.config.borrow().composition.use_utf8 {
             '·'
         } else {
             '-'
         };
-        let indent = " ".repeat(self.config.composition.indent as usize - 1);
+        let indent = " ".repeat(self.get_indent_width() - 1);
 
         format!("{}{}", indent_char, indent).repeat(depth)
     }
+
+    /// The indent is at least one column wide, it may be set to anything at
+    /// runtime.
+    fn get_indent_width(&self) -> usize {
+        self.config.borrow().composition.indent.max(1) as usize
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn compose_bookmarks_test() {
+        let mut config = Config::default();
+        config.behavior.persist_bookmarks = false;
+        let composer = Composer::from(config.clone());
+        let mut bookmarks = Bookmarks::new(&config, None);
+
+        assert_eq!(
+            vec!["bookmarks:", " no bookmarks set"],
+            composer.compose_bookmarks(&bookmarks)
+        );
+
+        bookmarks.set('b', std::path::PathBuf::from("/b"));
+        bookmarks.set('a', std::path::PathBuf::from("/a"));
+        bookmarks.set('c', std::path::PathBuf::from("/c\u{1b}[2J"));
+
+        assert_eq!(
+            vec!["bookmarks:", " a  /a", " b  /b", " c  /c\\u{1b}[2J"],
+            composer.compose_bookmarks(&bookmarks)
+        );
+    }
+
+    #[test]
+    fn compose_open_with_test() {
+        let composer = Composer::from(Config::default());
+
+        assert_eq!(
+            vec!["open with:", " 1  feh %s", " 2  default"],
+            composer.compose_open_with(&[
+                ('1', String::from("feh %s")),
+                ('2', String::from("default")),
+            ])
+        );
+    }
+
+    #[test]
+    fn compose_command_output_test() {
+        let composer = Composer::from(Config::default());
+
+        assert_eq!(
+            vec!["output of ls:", " a    b", " ?[2Jc"],
+            composer.compose_command_output("ls", b"a\tb\n\x1b[2Jc\r\n")
+        );
+    }
+
+    #[test]
+    fn compose_loading_dir_test() {
+        let mut config = Config::default();
+        config.composition.use_utf8 = false;
+        let composer = Composer::from(config);
+        let mut path_tree = PathTree::from(PathNode::from("./tests/test_dirs"));
+        path_tree
+            .expand_dir(PathTree::ROOT_ID, PathNode::compare_dirs_top_simple);
+        path_tree.set_loading(path_tree.get_node_id_at(&[1]), true);
+
+        let text_entries = composer.compose_path_tree(&path_tree);
+
+        assert_eq!("> dir0/", text_entries[0]);
+        assert_eq!("> dir1/ loading...", text_entries[1]);
+    }
+
+    #[test]
+    fn compose_err_entry_test() {
+        let composer = Composer::from(Config::default());
+        let mut path_tree = PathTree::from(PathNode::from("./tests/test_dirs"));
+        let mut err_path_node = PathNode::from("./tests/test_dirs");
+        err_path_node.display_text = String::from("<entry not found>");
+        err_path_node.is_dir = false;
+        err_path_node.is_err = true;
+        path_tree.set_children(PathTree::ROOT_ID, Some(vec![err_path_node]));
+
+        let text_entries = composer.compose_path_tree(&path_tree);
+
+        assert_eq!("⨯ <entry not found>", text_entries[0]);
+    }
+
+    #[test]
+    fn is_expand_marker_column_test() {
+        let mut config = Config::default();
+        config.composition.indent = 3;
+        let composer = Composer::from(config);
+        let mut path_node = PathNode::from("./tests/test_dirs");
+        path_node.depth = 2;
+
+        assert!(!composer.is_expand_marker_column(&path_node, 3));
+        assert!(composer.is_expand_marker_column(&path_node, 4));
+        assert!(composer.is_expand_marker_column(&path_node, 5));
+        assert!(!composer.is_expand_marker_column(&path_node, 6));
+
+        path_node.is_dir = false;
+        assert!(!composer.is_expand_marker_column(&path_node, 4));
+    }
+
     #[test]
     fn truncate_string_test() {
         let tc = Composer::truncate_string;
@@ -4,72 +4,139 @@ use std::io::Write;
 use termion::{color, style};
 
 impl<W: Write> Pager<W> {
+    /// Clears the screen and forgets the last frame, so that the next frame
+    /// is printed completely.
     pub fn print_clear(&mut self) {
         write!(self, "{}", termion::clear::All).unwrap();
+        self.back_buffer = vec![String::new(); self.frame.len()];
+    }
+
+    /// Prints the rows of the frame which differ from the last frame.
+    pub fn print_frame(&mut self) {
+        if self.back_buffer.len() != self.frame.len() {
+            self.print_clear();
+        }
+
+        let frame = std::mem::take(&mut self.frame);
+        for (i, row_text) in frame.iter().enumerate() {
+            if &self.back_buffer[i] != row_text {
+                write!(
+                    self,
+                    "{}{}{}",
+                    termion::cursor::Goto(1, 1 + i as u16),
+                    termion::clear::CurrentLine,
+                    row_text,
+                )
+                .unwrap();
+            }
+        }
+        self.back_buffer = frame;
+    }
+
+    fn set_frame_row(&mut self, row: i32, row_text: String) {
+        if row >= 1 && row <= self.frame.len() as i32 {
+            self.frame[row as usize - 1] = row_text;
+        }
     }
 
     pub fn print_text_entry(&mut self, text_entry: &str, row: i32) {
-        write!(
-            self,
-            "{}{}{}",
-            termion::cursor::Goto(1, row as u16),
+        let row_text = format!(
+            "{}{}",
             Composer::truncate_string(text_entry, self.terminal_cols as usize),
             style::Reset
-        )
-        .unwrap();
+        );
+        self.set_frame_row(row, row_text);
     }
 
     pub fn print_text_entry_emphasized(&mut self, text_entry: &str, row: i32) {
-        write!(
-            self,
-            "{}{}{}{}",
-            termion::cursor::Goto(1, row as u16),
+        let row_text = format!(
+            "{}{}{}",
             color::Bg(color::Blue),
             Composer::truncate_string(text_entry, self.terminal_cols as usize),
             style::Reset
-        )
-        .unwrap();
+        );
+        self.set_frame_row(row, row_text);
+    }
+
+    pub fn print_overlay(&mut self) {
+        let spacing_bot = self.config.borrow().debug.spacing_bot;
+        let spacing_top = self.config.borrow().debug.spacing_top;
+        let last_row = self.terminal_rows - spacing_bot;
+
+        let overlay_entries = self.overlay_entries.clone();
+        for (i, overlay_entry) in overlay_entries.iter().enumerate() {
+            let row = 1 + spacing_top + i as i32;
+            if row > last_row {
+                break;
+            }
+
+            let row_text = format!(
+                "{}{}{}",
+                style::Invert,
+                Composer::truncate_string(
+                    overlay_entry,
+                    self.terminal_cols as usize
+                ),
+                style::Reset
+            );
+            self.set_frame_row(row, row_text);
+        }
     }
 
     pub fn print_header(&mut self, text: &str) {
-        write!(
-            self,
-            "{}{}",
-            termion::cursor::Goto(1, 1),
-            Composer::truncate_string(text, self.terminal_cols as usize),
-        )
-        .unwrap();
+        let row_text =
+            Composer::truncate_string(text, self.terminal_cols as usize);
+        self.set_frame_row(1, row_text);
     }
 
     pub fn print_footer(&mut self, text: &str) {
-        write!(
-            self,
-            "{}{}",
-            termion::cursor::Goto(1, 1 + self.terminal_rows as u16),
-            Composer::truncate_string(text, self.terminal_cols as usize),
-        )
-        .unwrap();
+        let row_text =
+            Composer::truncate_string(text, self.terminal_cols as usize);
+        self.set_frame_row(self.terminal_rows, row_text);
+    }
+
+    /// Prints the prompt in the footer, scrolled so that the cursor is
+    /// visible, which is shown inverted.
+    pub fn print_prompt(&mut self, text: &str, cursor: usize) {
+        let cols = self.terminal_cols.max(1) as usize;
+        // the trailing space holds the cursor at the end of the text
+        let chars: Vec<char> = format!(":{} ", text).chars().collect();
+        let cursor_index = (cursor + 1).min(chars.len() - 1);
+        let first_index = (cursor_index + 1).saturating_sub(cols);
+
+        let visible_chars =
+            &chars[first_index..chars.len().min(first_index + cols)];
+        let cursor_col = cursor_index - first_index;
+
+        let row_text = format!(
+            "{}{}{}{}{}",
+            visible_chars[..cursor_col].iter().collect::<String>(),
+            style::Invert,
+            visible_chars[cursor_col],
+            style::Reset,
+            visible_chars[cursor_col + 1..].iter().collect::<String>(),
+        );
+        self.set_frame_row(self.terminal_rows, row_text);
     }
 
+    /// Prints on top of the frame, hence the next frame is printed
+    /// completely.
     pub fn print_debug_info(&mut self) {
-        if !self.config.debug.enabled {
+        if !self.config.borrow().debug.enabled {
             return;
         }
 
-        let padding_bot = self.config.debug.padding_bot;
-        let padding_top = self.config.debug.padding_top;
-        let spacing_bot = self.config.debug.spacing_bot;
-        let spacing_top = self.config.debug.spacing_top;
+        self.back_buffer.clear();
+
+        let padding_bot = self.config.borrow().debug.padding_bot;
+        let padding_top = self.config.borrow().debug.padding_top;
+        let spacing_bot = self.config.borrow().debug.spacing_bot;
+        let spacing_top = self.config.borrow().debug.spacing_top;
 
         // line numbers
         for i in 0..self.terminal_rows {
-            write!(
-                self,
-                "{} L{}",
-                termion::cursor::Goto(50, 1 + i as u16),
-                i.to_string()
-            )
-            .unwrap();
+            write!(self, "{} L{}", termion::cursor::Goto(50, 1 + i as u16), i)
+                .unwrap();
         }
 
         // padding_top debug
@@ -159,7 +226,8 @@ mod tests {
         pager
     }
 
-    fn get_result(pager: Pager<Vec<u8>>) -> Option<String> {
+    fn get_result(mut pager: Pager<Vec<u8>>) -> Option<String> {
+        pager.flush().unwrap();
         let pager_out = pager.out.clone();
         Some(String::from(std::str::from_utf8(&pager_out).unwrap()))
     }
@@ -172,63 +240,143 @@ mod tests {
             get_result(pager)
         };
 
-        assert_eq!("\u{1b}[?25l\u{1b}[1;1H\u{1b}[2J\u{1b}[2J", result.unwrap());
+        assert_eq!(
+            "\u{1b}[?1049h\u{1b}[?25l\u{1b}[2J\u{1b}[2J",
+            result.unwrap()
+        );
     }
 
     #[test]
     fn print_text_entry_test() {
-        let result = {
-            let mut pager = prepare_pager();
-            pager.print_text_entry("--- test 123 ---", 42);
-            get_result(pager)
-        };
+        let mut pager = prepare_pager();
+        pager.frame = vec![String::new(); 10];
 
-        assert_eq!(
-            "\u{1b}[?25l\u{1b}[1;1H\u{1b}[2J\u{1b}[42;1H--- test 123 ---\u{1b}[m",
-            result.unwrap(),
-        );
+        pager.print_text_entry("--- test 123 ---", 4);
+        pager.print_text_entry("--- test 456 ---", 42);
+
+        assert_eq!("--- test 123 ---\u{1b}[m", pager.frame[3]);
+        assert_eq!(10, pager.frame.len());
     }
 
     #[test]
     fn print_text_entry_emphasized_test() {
-        let result = {
-            let mut pager = prepare_pager();
-            pager.print_text_entry_emphasized("--- test 123 ---", 42);
-            get_result(pager)
-        };
+        let mut pager = prepare_pager();
+        pager.frame = vec![String::new(); 10];
+
+        pager.print_text_entry_emphasized("--- test 123 ---", 4);
+
+        assert_eq!("\u{1b}[48;5;4m--- test 123 ---\u{1b}[m", pager.frame[3]);
+    }
+
+    #[test]
+    fn print_prompt_test() {
+        let mut pager = Pager::new(Config::default(), Vec::new());
+        pager.set_terminal_size(6, 2);
+        pager.frame = vec![String::new(); 2];
 
+        pager.print_prompt("cd", 1);
         assert_eq!(
-            "\u{1b}[?25l\u{1b}[1;1H\u{1b}[2J\u{1b}[42;1H\u{1b}[48;5;4m--- test 123 ---\u{1b}[m",
-            result.unwrap(),
+            format!(":c{}d{} ", style::Invert, style::Reset),
+            pager.frame[1]
+        );
+
+        pager.print_prompt("cd dir1", 7);
+        assert_eq!(
+            format!(" dir1{} {}", style::Invert, style::Reset),
+            pager.frame[1]
         );
     }
 
+    #[test]
+    fn print_overlay_test() {
+        let mut pager = prepare_pager();
+        pager.frame = vec![String::new(); 10];
+        pager.set_overlay(vec![
+            String::from("--- test 123 ---"),
+            String::from("--- test 456 ---"),
+        ]);
+
+        pager.print_overlay();
+
+        assert_eq!("\u{1b}[7m--- test 123 ---\u{1b}[m", pager.frame[1]);
+        assert_eq!("\u{1b}[7m--- test 456 ---\u{1b}[m", pager.frame[2]);
+    }
+
     #[test]
     fn print_header_test() {
-        let result = {
-            let mut pager = prepare_pager();
-            pager.print_header("--- test 123 ---");
-            get_result(pager)
-        };
+        let mut pager = prepare_pager();
+        pager.frame = vec![String::new(); 10];
 
-        assert_eq!(
-            "\u{1b}[?25l\u{1b}[1;1H\u{1b}[2J\u{1b}[1;1H--- test 123 ---",
-            result.unwrap(),
-        );
+        pager.print_header("--- test 123 ---");
+
+        assert_eq!("--- test 123 ---", pager.frame[0]);
     }
 
     #[test]
     fn print_footer_test() {
-        let result = {
+        let mut pager = prepare_pager();
+        pager.frame = vec![String::new(); 10];
+
+        pager.print_footer("--- test 123 ---");
+
+        assert_eq!("--- test 123 ---", pager.frame[9]);
+    }
+
+    mod print_frame_tests {
+        use super::*;
+
+        fn print_frame(pager: &mut Pager<Vec<u8>>, frame: &[&str]) -> String {
+            pager.out.clear();
+            pager.frame = frame.iter().map(|row| String::from(*row)).collect();
+            pager.print_frame();
+            pager.flush().unwrap();
+            String::from(std::str::from_utf8(&pager.out).unwrap())
+        }
+
+        #[test]
+        fn first_frame() {
             let mut pager = prepare_pager();
-            pager.print_footer("--- test 123 ---");
-            get_result(pager)
-        };
 
-        assert_eq!(
-            "\u{1b}[?25l\u{1b}[1;1H\u{1b}[2J\u{1b}[11;1H--- test 123 ---",
-            result.unwrap(),
-        );
+            let result = print_frame(&mut pager, &["a", "", "c"]);
+
+            assert_eq!(
+                "\u{1b}[2J\u{1b}[1;1H\u{1b}[2Ka\u{1b}[3;1H\u{1b}[2Kc",
+                result
+            );
+        }
+
+        #[test]
+        fn changed_rows() {
+            let mut pager = prepare_pager();
+            print_frame(&mut pager, &["a", "b", "c"]);
+
+            let result = print_frame(&mut pager, &["a", "x", ""]);
+
+            assert_eq!("\u{1b}[2;1H\u{1b}[2Kx\u{1b}[3;1H\u{1b}[2K", result);
+        }
+
+        #[test]
+        fn unchanged_frame() {
+            let mut pager = prepare_pager();
+            print_frame(&mut pager, &["a", "b", "c"]);
+
+            let result = print_frame(&mut pager, &["a", "b", "c"]);
+
+            assert_eq!("", result);
+        }
+
+        #[test]
+        fn resized_frame() {
+            let mut pager = prepare_pager();
+            print_frame(&mut pager, &["a", "b", "c"]);
+
+            let result = print_frame(&mut pager, &["a", "b"]);
+
+            assert_eq!(
+                "\u{1b}[2J\u{1b}[1;1H\u{1b}[2Ka\u{1b}[2;1H\u{1b}[2Kb",
+                result
+            );
+        }
     }
 
     #[test]
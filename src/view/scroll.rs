@@ -27,8 +27,8 @@ impl<W: Write> Pager<W> {
         cursor_row_delta: i32,
         text_entries_len: i32,
     ) -> i32 {
-        let spacing_bot = self.config.debug.spacing_bot;
-        let spacing_top = self.config.debug.spacing_top;
+        let spacing_bot = self.config.borrow().debug.spacing_bot;
+        let spacing_top = self.config.borrow().debug.spacing_top;
 
         let center_text_row = spacing_top - self.text_row
             + (self.terminal_rows - (spacing_bot + spacing_top)) / 2;
@@ -86,11 +86,33 @@ impl<W: Write> Pager<W> {
         self.text_row
     }
 
+    /// Maps a terminal row, e.g. of a mouse click, to the index of the text
+    /// entry printed there.
+    pub fn get_text_entry_index(
+        &self,
+        row: i32,
+        text_entries_len: i32,
+    ) -> Option<i32> {
+        let spacing_bot = self.config.borrow().debug.spacing_bot;
+        let spacing_top = self.config.borrow().debug.spacing_top;
+
+        if row <= spacing_top || row > self.terminal_rows - spacing_bot {
+            return None;
+        }
+
+        let index = row - 1 - self.text_row;
+        if index >= 0 && index < text_entries_len {
+            Some(index)
+        } else {
+            None
+        }
+    }
+
     pub fn scroll_like_editor(&self) -> i32 {
-        let padding_bot = self.config.debug.padding_bot;
-        let padding_top = self.config.debug.padding_top;
-        let spacing_bot = self.config.debug.spacing_bot;
-        let spacing_top = self.config.debug.spacing_top;
+        let padding_bot = self.config.borrow().debug.padding_bot;
+        let padding_top = self.config.borrow().debug.padding_top;
+        let spacing_bot = self.config.borrow().debug.spacing_bot;
+        let spacing_top = self.config.borrow().debug.spacing_top;
 
         if self.text_row + self.cursor_row < spacing_top + padding_top {
             return spacing_top + padding_top - self.cursor_row;
@@ -318,4 +340,34 @@ mod tests {
             assert_eq!(-2, text_row);
         }
     }
+
+    mod get_text_entry_index_tests {
+        use super::*;
+
+        #[test]
+        fn inside() {
+            let mut pager = prepare_pager();
+            pager.text_row = -3;
+
+            assert_eq!(Some(4), pager.get_text_entry_index(2, 17));
+            assert_eq!(Some(11), pager.get_text_entry_index(9, 17));
+        }
+
+        #[test]
+        fn spacing() {
+            let pager = prepare_pager();
+
+            assert_eq!(None, pager.get_text_entry_index(1, 17));
+            assert_eq!(None, pager.get_text_entry_index(10, 17));
+        }
+
+        #[test]
+        fn beyond_text_entries() {
+            let mut pager = prepare_pager();
+            pager.text_row = 1;
+
+            assert_eq!(Some(0), pager.get_text_entry_index(2, 3));
+            assert_eq!(None, pager.get_text_entry_index(5, 3));
+        }
+    }
 }
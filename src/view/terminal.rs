@@ -0,0 +1,88 @@
+use std::io::stdout;
+use std::io::Write;
+use std::sync::Mutex;
+
+/// Enables the reporting of mouse clicks and the mouse wheel.
+pub const ENABLE_MOUSE: &str = "\x1b[?1000h\x1b[?1006h";
+
+pub const DISABLE_MOUSE: &str = "\x1b[?1006l\x1b[?1000l";
+
+static ORIGINAL_ATTRIBUTES: Mutex<Option<libc::termios>> = Mutex::new(None);
+
+static RAW_ATTRIBUTES: Mutex<Option<libc::termios>> = Mutex::new(None);
+
+fn get_attributes() -> Option<libc::termios> {
+    let mut termios = unsafe { std::mem::zeroed() };
+
+    if unsafe { libc::tcgetattr(libc::STDOUT_FILENO, &mut termios) } == 0 {
+        Some(termios)
+    } else {
+        None
+    }
+}
+
+fn set_attributes(termios: &libc::termios) {
+    unsafe {
+        libc::tcsetattr(libc::STDOUT_FILENO, libc::TCSANOW, termios);
+    }
+}
+
+/// Remembers the attributes of the terminal, call this before switching to
+/// the raw mode.
+pub fn save_terminal_attributes() {
+    if let Some(termios) = get_attributes() {
+        if let Ok(mut original_attributes) = ORIGINAL_ATTRIBUTES.lock() {
+            *original_attributes = Some(termios);
+        }
+    }
+}
+
+/// Remembers the attributes of the raw mode, which are restored when the
+/// process is continued after being stopped.
+pub fn save_raw_attributes() {
+    if let Some(termios) = get_attributes() {
+        if let Ok(mut raw_attributes) = RAW_ATTRIBUTES.lock() {
+            *raw_attributes = Some(termios);
+        }
+    }
+}
+
+/// Restores the attributes saved before switching to the raw mode, so that
+/// a command or the shell can use the terminal.
+pub fn suspend_raw_mode() {
+    if let Ok(original_attributes) = ORIGINAL_ATTRIBUTES.lock() {
+        if let Some(termios) = original_attributes.as_ref() {
+            set_attributes(termios);
+        }
+    }
+}
+
+/// Restores the attributes saved by `save_raw_attributes`.
+pub fn resume_raw_mode() {
+    if let Ok(raw_attributes) = RAW_ATTRIBUTES.lock() {
+        if let Some(termios) = raw_attributes.as_ref() {
+            set_attributes(termios);
+        }
+    }
+}
+
+/// Leaves the alternate screen, shows the cursor and restores the saved
+/// attributes. This is meant for abnormal exits, where the pager is not
+/// dropped properly.
+pub fn restore_terminal() {
+    let mut out = stdout();
+    let _ = write!(
+        out,
+        "{}{}{}",
+        DISABLE_MOUSE,
+        termion::cursor::Show,
+        termion::screen::ToMainScreen
+    );
+    let _ = out.flush();
+
+    if let Ok(original_attributes) = ORIGINAL_ATTRIBUTES.lock() {
+        if let Some(termios) = original_attributes.as_ref() {
+            set_attributes(termios);
+        }
+    }
+}
@@ -4,9 +4,21 @@ use termion::terminal_size;
 
 impl<W: Write> Pager<W> {
     fn update_terminal_size(&mut self) {
-        let (terminal_cols_raw, terminal_rows_raw) = terminal_size().unwrap();
-        self.terminal_cols = i32::from(terminal_cols_raw);
-        self.terminal_rows = i32::from(terminal_rows_raw);
+        // keep the last known size when not attached to a terminal
+        if let Ok((terminal_cols_raw, terminal_rows_raw)) = terminal_size() {
+            let terminal_cols = i32::from(terminal_cols_raw);
+            let terminal_rows = i32::from(terminal_rows_raw);
+
+            // the terminal may have rearranged the old frame
+            if terminal_cols != self.terminal_cols
+                || terminal_rows != self.terminal_rows
+            {
+                self.back_buffer.clear();
+            }
+
+            self.terminal_cols = terminal_cols;
+            self.terminal_rows = terminal_rows;
+        }
     }
 
     fn update_cursor_row(
@@ -31,14 +43,14 @@ impl<W: Write> Pager<W> {
     ) {
         self.update_terminal_size();
 
-        let spacing_bot = self.config.debug.spacing_bot;
-        let spacing_top = self.config.debug.spacing_top;
+        let spacing_bot = self.config.borrow().debug.spacing_bot;
+        let spacing_top = self.config.borrow().debug.spacing_top;
 
         let text_entries_len = text_entries.len() as i32;
 
         self.update_cursor_row(cursor_row_delta, text_entries_len);
 
-        self.text_row = match self.config.behavior.scrolling.as_str() {
+        self.text_row = match self.config.borrow().behavior.scrolling.as_str() {
             "center" => {
                 self.scroll_like_center(cursor_row_delta, text_entries_len)
             }
@@ -50,8 +62,7 @@ impl<W: Write> Pager<W> {
 
         let first_index = spacing_top - self.text_row;
 
-        // clear screen
-        self.print_clear();
+        self.frame = vec![String::new(); self.terminal_rows.max(0) as usize];
 
         // print rows
         for i in 0..displayable_rows {
@@ -71,14 +82,120 @@ impl<W: Write> Pager<W> {
             }
         }
 
-        let footer_text =
+        let mut footer_text =
             format!("[{}/{}]", self.cursor_row + 1, text_entries_len);
+        if !self.pending_keys.is_empty() {
+            footer_text = format!("{} {}", footer_text, self.pending_keys);
+        }
+        if !self.status.is_empty() {
+            footer_text = format!("{} {}", footer_text, self.status);
+        }
+
+        self.print_overlay();
 
         self.print_header(&header_text);
-        self.print_footer(&footer_text);
+        match self.prompt.clone() {
+            Some((text, cursor)) => self.print_prompt(&text, cursor),
+            None => self.print_footer(&footer_text),
+        }
+
+        self.print_frame();
 
         self.print_debug_info();
 
         self.flush().unwrap();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::config::Config;
+
+    /// Remembers every write to check that a frame is written at once.
+    #[derive(Default)]
+    struct WriteLog {
+        writes: Vec<Vec<u8>>,
+    }
+
+    impl Write for WriteLog {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.writes.push(buf.to_vec());
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn prepare_pager() -> Pager<WriteLog> {
+        let mut pager = Pager::new(Config::default(), WriteLog::default());
+        pager.terminal_cols = 20;
+        pager.terminal_rows = 10;
+        pager.out.writes.clear();
+        pager
+    }
+
+    fn get_text_entries() -> Vec<String> {
+        vec![String::from("a"), String::from("b"), String::from("c")]
+    }
+
+    fn get_last_write(pager: &Pager<WriteLog>) -> String {
+        String::from_utf8(pager.out.writes.last().unwrap().clone()).unwrap()
+    }
+
+    #[test]
+    fn first_update() {
+        let mut pager = prepare_pager();
+
+        pager.update(0, &get_text_entries(), String::from("header"));
+
+        assert_eq!(1, pager.out.writes.len());
+        let result = get_last_write(&pager);
+        assert!(result.starts_with("\u{1b}[2J"));
+        assert!(result.contains("\u{1b}[1;1H\u{1b}[2Kheader"));
+        assert!(result.contains("\u{1b}[48;5;4ma\u{1b}[m"));
+        assert!(result.contains("b\u{1b}[m"));
+        assert!(result.contains("\u{1b}[10;1H\u{1b}[2K[1/3]"));
+    }
+
+    #[test]
+    fn cursor_move() {
+        let mut pager = prepare_pager();
+        pager.update(0, &get_text_entries(), String::from("header"));
+
+        pager.update(1, &get_text_entries(), String::from("header"));
+
+        assert_eq!(2, pager.out.writes.len());
+        let result = get_last_write(&pager);
+        assert!(!result.contains("\u{1b}[2J"));
+        assert!(!result.contains("header"));
+        assert!(!result.contains("c\u{1b}[m"));
+        assert_eq!(3, result.matches("\u{1b}[2K").count());
+        assert!(result.contains("a\u{1b}[m"));
+        assert!(result.contains("\u{1b}[48;5;4mb\u{1b}[m"));
+        assert!(result.contains("[2/3]"));
+    }
+
+    #[test]
+    fn pending_keys() {
+        let mut pager = prepare_pager();
+        pager.set_pending_keys(String::from("<space>g"));
+
+        pager.update(0, &get_text_entries(), String::from("header"));
+
+        let result = get_last_write(&pager);
+        assert!(result.contains("\u{1b}[10;1H\u{1b}[2K[1/3] <space>g"));
+    }
+
+    #[test]
+    fn unchanged_update() {
+        let mut pager = prepare_pager();
+        pager.update(0, &get_text_entries(), String::from("header"));
+
+        pager.update(0, &get_text_entries(), String::from("header"));
+
+        assert_eq!(1, pager.out.writes.len());
+    }
+}